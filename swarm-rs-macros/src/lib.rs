@@ -1,76 +1,92 @@
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, Ident, ItemImpl};
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, Attribute, Ident, ItemImpl, LitInt, LitStr, ReturnType, Type, Visibility,
+};
 
 #[proc_macro_attribute]
 pub fn agent_action(_attr: TokenStream, input: TokenStream) -> TokenStream {
-    input.into()
+    input
 }
 
 #[proc_macro_attribute]
 pub fn agent_workflow(_attr: TokenStream, input: TokenStream) -> TokenStream {
-    input.into()
+    input
 }
 
 #[proc_macro_attribute]
 pub fn agent(_attr: TokenStream, input: TokenStream) -> TokenStream {
-    let input_clone = input.clone();
-    let impl_decl = parse_macro_input!(input_clone as ItemImpl);
+    let impl_decl = parse_macro_input!(input as ItemImpl);
 
-    let struct_ident = if let syn::Type::Path(type_path) = impl_decl.self_ty.as_ref() {
-        let ident: &Ident = type_path.path.get_ident().unwrap();
-        // println!("\t- Name: \"{:?}\"", ident);
-        ident
-    } else {
-        panic!("Expected a struct with named fields");
+    let struct_ident = match agent_ident(&impl_decl) {
+        Ok(ident) => ident,
+        Err(error) => {
+            let error = error.to_compile_error();
+            return quote! { #impl_decl #error }.into();
+        }
     };
 
     let mut match_arms: Vec<proc_macro2::TokenStream> = vec![];
+    let mut descriptors: Vec<proc_macro2::TokenStream> = vec![];
+    let mut client_methods: Vec<proc_macro2::TokenStream> = vec![];
+    let mut errors: Vec<proc_macro2::TokenStream> = vec![];
+    let mut client_vis = Visibility::Inherited;
     for item in &impl_decl.items {
         if let syn::ImplItem::Fn(method) = item {
-            for attr in &method.attrs {
-                // Include function into dipatch
-                if attr.meta.path().is_ident("agent_action") {
-                    let fn_ident = method.sig.ident.clone();
-                    let match_arm = quote! {
-                        stringify!(#fn_ident) => {
-                            if let Ok(input_value) = action.get_payload() {
-                                let output = self.#fn_ident(input_value).await;
-                                match output {
-                                    Ok(output_value) => Output::new_success(output_value),
-                                    Err(message) => Output::new_error(&message)
-                                }
-                            }else {
-                                Output::new_error("Unable to get payload")
-                            }
-                        },
-                    };
-                    match_arms.push(match_arm);
-                } else if attr.meta.path().is_ident("agent_workflow") {
-                    let fn_ident = method.sig.ident.clone();
-                    let match_arm = quote! {
-                        stringify!(#fn_ident) => {
-                            if let Ok(input_value) = action.get_payload() {
-                                let output = self.#fn_ident(input_value, swarm).await;
-                                match output {
-                                    Ok(output_value) => Output::new_success(output_value),
-                                    Err(message) => Output::new_error(&message)
-                                }
-                            }else {
-                                Output::new_error("Unable to get payload")
-                            }
-                        },
-                    };
-                    match_arms.push(match_arm);
+            match ActionMethod::parse(method) {
+                Ok(Some(action)) => {
+                    descriptors.push(action.descriptor());
+                    client_methods.push(action.client_method());
+                    client_vis = widest_visibility(client_vis, &action.vis);
+                    match_arms.push(action.match_arm());
                 }
+                Ok(None) => {}
+                Err(error) => errors.push(error.to_compile_error()),
             }
         }
     }
 
+    let client_ident = format_ident!("{}Client", struct_ident);
+    let client_doc = format!(
+        "Typed handle executing the actions of a [`{}`] registered in the swarm.",
+        struct_ident
+    );
+
     let expanded: proc_macro2::TokenStream = quote! {
 
+        #(#errors)*
+
         #impl_decl
 
+        #[doc = #client_doc]
+        ///
+        /// Actions are still dispatched through the [`Swarm`], its middlewares applying.
+        #[allow(dead_code)]
+        #client_vis struct #client_ident<'a> {
+            swarm: &'a Swarm,
+            agent_id: String,
+        }
+
+        #[allow(dead_code)]
+        impl<'a> #client_ident<'a> {
+            #client_vis fn new(swarm: &'a Swarm, agent_id: &str) -> Self {
+                Self {
+                    swarm,
+                    agent_id: agent_id.to_string(),
+                }
+            }
+
+            #client_vis fn get_agent_id(&self) -> &str {
+                &self.agent_id
+            }
+
+            fn action_id(&self, action: &str) -> String {
+                format!("{}.{}", self.agent_id, action)
+            }
+
+            #(#client_methods)*
+        }
+
         #[async_trait]
         impl Agent for #struct_ident {
 
@@ -78,10 +94,16 @@ pub fn agent(_attr: TokenStream, input: TokenStream) -> TokenStream {
                 self
             }
 
+            #[allow(clippy::needless_borrow)]
+            fn describe(&self) -> Vec<ActionDescriptor> {
+                vec![#(#descriptors)*]
+            }
+
+            #[allow(clippy::needless_borrow)]
             async fn execute(&self, action: &Action, swarm: &Swarm) -> Output {
                 match action.get_name() {
                     #(#match_arms)*
-                    _ => Output::new_error("Unknown action"),
+                    _ => Output::from_error(AgentError::new(ErrorCode::UnknownAction, "Unknown action")),
                 }
             }
         }
@@ -90,3 +112,550 @@ pub fn agent(_attr: TokenStream, input: TokenStream) -> TokenStream {
 
     expanded.into()
 }
+
+/// Ident of the agent type, the only supported self type being a plain, non generic, type name.
+fn agent_ident(impl_decl: &ItemImpl) -> syn::Result<Ident> {
+    if let Some((_, path, _)) = &impl_decl.trait_ {
+        return Err(syn::Error::new_spanned(
+            path,
+            "#[agent] expects an inherent impl block, such as `impl MyAgent`",
+        ));
+    }
+    if !impl_decl.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &impl_decl.generics,
+            "#[agent] does not support generic agents",
+        ));
+    }
+    let type_path = match impl_decl.self_ty.as_ref() {
+        Type::Path(type_path) if type_path.qself.is_none() => type_path,
+        self_ty => {
+            return Err(syn::Error::new_spanned(
+                self_ty,
+                "#[agent] expects a struct or enum type",
+            ))
+        }
+    };
+    if let Some(ident) = type_path.path.get_ident() {
+        return Ok(ident.clone());
+    }
+    let message = match type_path.path.segments.last() {
+        Some(segment) if !segment.arguments.is_empty() => {
+            "#[agent] does not support generic agents"
+        }
+        _ => "#[agent] expects the agent type name, such as `impl MyAgent`, import the type instead of qualifying its path",
+    };
+    Err(syn::Error::new_spanned(type_path, message))
+}
+
+/// Parameter of an action method.
+enum Argument {
+    /// Deserialized from the payload, named after the parameter.
+    Payload { name: String, ty: Box<Type> },
+    /// `&Swarm`
+    Swarm,
+    /// `&CancellationToken` or `CancellationToken`, the token of the action.
+    Cancellation { by_ref: bool },
+}
+
+/// Value returned by an action method.
+struct ActionReturn {
+    /// `T` of `Result<T, E>`, or the returned type of the infallible actions.
+    ok: Type,
+    /// `E` of `Result<T, E>`, unknown for the aliases such as `io::Result<T>`.
+    err: Option<Type>,
+    is_result: bool,
+}
+
+/// Method marked with `#[agent_action]` or `#[agent_workflow]`.
+struct ActionMethod {
+    fn_ident: Ident,
+    vis: Visibility,
+    attrs: Vec<Attribute>,
+    is_async: bool,
+    is_workflow: bool,
+    options: ActionOptions,
+    name: String,
+    arguments: Vec<Argument>,
+    output: ActionReturn,
+}
+
+impl ActionMethod {
+    fn parse(method: &syn::ImplItemFn) -> syn::Result<Option<Self>> {
+        let mut action_attrs = method.attrs.iter().filter(|attr| {
+            attr.path().is_ident("agent_action") || attr.path().is_ident("agent_workflow")
+        });
+        let attr = match action_attrs.next() {
+            Some(attr) => attr,
+            None => return Ok(None),
+        };
+        if let Some(other) = action_attrs.next() {
+            return Err(syn::Error::new_spanned(
+                other,
+                "a method is either an #[agent_action] or an #[agent_workflow]",
+            ));
+        }
+        let signature = &method.sig;
+        if !signature.generics.params.is_empty() {
+            return Err(syn::Error::new_spanned(
+                &signature.generics,
+                "actions can not be generic, their payload type is needed to dispatch them",
+            ));
+        }
+        match signature.receiver() {
+            Some(receiver) if receiver.reference.is_some() && receiver.mutability.is_none() => {}
+            Some(receiver) => {
+                return Err(syn::Error::new_spanned(
+                    receiver,
+                    "actions take `&self`, the agent being shared by the swarm",
+                ))
+            }
+            None => {
+                return Err(syn::Error::new_spanned(
+                    &signature.ident,
+                    "actions take `&self`, associated functions can not be dispatched",
+                ))
+            }
+        }
+
+        let mut arguments = vec![];
+        let mut unnamed = None;
+        for input in &signature.inputs {
+            if let syn::FnArg::Typed(input) = input {
+                let argument = Argument::parse(input)?;
+                if let (Argument::Payload { .. }, false) =
+                    (&argument, matches!(input.pat.as_ref(), syn::Pat::Ident(_)))
+                {
+                    unnamed = Some(input.pat.clone());
+                }
+                arguments.push(argument);
+            }
+        }
+        let payload_count = arguments
+            .iter()
+            .filter(|argument| matches!(argument, Argument::Payload { .. }))
+            .count();
+        if let (Some(pat), true) = (unnamed, payload_count > 1) {
+            return Err(syn::Error::new_spanned(
+                pat,
+                "actions taking several arguments need named arguments, the payload being an object of them",
+            ));
+        }
+
+        let options = ActionOptions::parse(attr)?;
+        let fn_ident = signature.ident.clone();
+        let name = options
+            .name
+            .clone()
+            .unwrap_or_else(|| fn_ident.to_string());
+        Ok(Some(Self {
+            fn_ident,
+            vis: method.vis.clone(),
+            attrs: method.attrs.clone(),
+            is_async: signature.asyncness.is_some(),
+            is_workflow: attr.path().is_ident("agent_workflow"),
+            options,
+            name,
+            arguments,
+            output: ActionReturn::parse(&signature.output),
+        }))
+    }
+
+    fn payloads(&self) -> Vec<(&str, &Type)> {
+        self.arguments
+            .iter()
+            .filter_map(|argument| match argument {
+                Argument::Payload { name, ty } => Some((name.as_str(), ty.as_ref())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn descriptor(&self) -> proc_macro2::TokenStream {
+        let name = &self.name;
+        let kind = if self.is_workflow {
+            quote! { ActionKind::Workflow }
+        } else {
+            quote! { ActionKind::Action }
+        };
+        let input_schema = match self.payloads().as_slice() {
+            [] => quote! { (&SchemaProbe::<()>::new()).json_schema() },
+            [(_, ty)] => quote! { (&SchemaProbe::<#ty>::new()).json_schema() },
+            payloads => {
+                let arguments = payloads.iter().map(|(name, ty)| {
+                    let required = !is_option(ty);
+                    quote! { (#name, (&SchemaProbe::<#ty>::new()).json_schema(), #required) }
+                });
+                quote! { arguments_schema(vec![#(#arguments),*]) }
+            }
+        };
+        let ok_type = &self.output.ok;
+        let error_schema = match &self.output.err {
+            Some(err) => quote! { (&SchemaProbe::<#err>::new()).json_schema() },
+            None => quote! { None },
+        };
+        let settings = self.options.descriptor_settings(&self.attrs);
+        quote! {
+            // Resolves to `WithSchema` when the type implements `JsonSchema`
+            ActionDescriptor::new(#name)
+                .with_kind(#kind)
+                #settings
+                .with_input_schema(#input_schema)
+                .with_output_schema((&SchemaProbe::<#ok_type>::new()).json_schema())
+                .with_error_schema(#error_schema),
+        }
+    }
+
+    fn client_method(&self) -> proc_macro2::TokenStream {
+        let fn_ident = &self.fn_ident;
+        let vis = &self.vis;
+        let name = &self.name;
+        let docs = self.attrs.iter().filter(|attr| attr.path().is_ident("doc"));
+        let deprecated = self.options.deprecated.then(|| quote! { #[deprecated] });
+        let payloads = self.payloads();
+        let params = payloads.iter().map(|(name, ty)| {
+            let ident = format_ident!("{}", name);
+            quote! { #ident: #ty }
+        });
+        let payload = match payloads.as_slice() {
+            [] => quote! { &() },
+            [(name, _)] => {
+                let ident = format_ident!("{}", name);
+                quote! { &#ident }
+            }
+            payloads => {
+                let arguments = payloads.iter().map(|(name, _)| {
+                    let ident = format_ident!("{}", name);
+                    quote! { .with(#name, &#ident) }
+                });
+                quote! { &NamedArguments::new() #(#arguments)* }
+            }
+        };
+        let ok_type = &self.output.ok;
+        // Errors are converted into `AgentError` by the dispatch
+        quote! {
+            #(#docs)*
+            #deprecated
+            #vis async fn #fn_ident(&self, #(#params),*) -> Result<#ok_type, AgentError> {
+                let action_id = self.action_id(#name);
+                self.swarm.execute(&action_id, #payload).await.get_payload()
+            }
+        }
+    }
+
+    fn match_arm(&self) -> proc_macro2::TokenStream {
+        let fn_ident = &self.fn_ident;
+        let name = &self.name;
+        let aliases = &self.options.aliases;
+        let payloads = self.payloads();
+        let mut payload_index = 0_usize;
+        let mut bindings = vec![];
+        let mut call_arguments = vec![];
+        for argument in &self.arguments {
+            call_arguments.push(match argument {
+                Argument::Payload { name, .. } => {
+                    let binding = format_ident!("argument_{}", payload_index);
+                    payload_index += 1;
+                    // A single argument is the payload itself
+                    let value = if payloads.len() == 1 {
+                        quote! { action.get_payload() }
+                    } else {
+                        quote! { action.get_argument(#name) }
+                    };
+                    bindings.push(quote! {
+                        let #binding = match #value {
+                            Ok(value) => value,
+                            Err(error) => return Output::from_error(error),
+                        };
+                    });
+                    quote! { #binding }
+                }
+                Argument::Swarm => quote! { swarm },
+                Argument::Cancellation { by_ref: true } => quote! { action.cancellation_token() },
+                Argument::Cancellation { by_ref: false } => {
+                    quote! { action.cancellation_token().clone() }
+                }
+            });
+        }
+        let call = match (self.is_async, self.is_workflow) {
+            (true, false) => quote! { self.#fn_ident(#(#call_arguments),*).await },
+            (false, false) => quote! { self.#fn_ident(#(#call_arguments),*) },
+            // The actions of a workflow are attributed to a new run of it
+            (true, true) => quote! {
+                scope_workflow_run(
+                    &format!("{}.{}", action.get_agent(), #name),
+                    self.#fn_ident(#(#call_arguments),*),
+                ).await
+            },
+            (false, true) => quote! {
+                scope_workflow_run(
+                    &format!("{}.{}", action.get_agent(), #name),
+                    async { self.#fn_ident(#(#call_arguments),*) },
+                ).await
+            },
+        };
+        let output = if self.output.is_result {
+            quote! {
+                match #call {
+                    Ok(value) => Output::new_success(value),
+                    Err(error) => Output::from_error((&&&ErrorProbe::new(error)).agent_error()),
+                }
+            }
+        } else {
+            quote! { Output::new_success(#call) }
+        };
+        quote! {
+            #name #(| #aliases)* => {
+                #(#bindings)*
+                #output
+            },
+        }
+    }
+}
+
+impl Argument {
+    fn parse(input: &syn::PatType) -> syn::Result<Self> {
+        let ty = input.ty.as_ref();
+        if let Type::Reference(reference) = ty {
+            if type_name(&reference.elem).as_deref() == Some("Swarm") {
+                return Ok(Argument::Swarm);
+            }
+            if type_name(&reference.elem).as_deref() == Some("CancellationToken") {
+                return Ok(Argument::Cancellation { by_ref: true });
+            }
+            return Err(syn::Error::new_spanned(
+                ty,
+                "action arguments are deserialized from the payload and must be owned types, only `&Swarm` and `&CancellationToken` are passed by reference",
+            ));
+        }
+        if type_name(ty).as_deref() == Some("CancellationToken") {
+            return Ok(Argument::Cancellation { by_ref: false });
+        }
+        let name = match input.pat.as_ref() {
+            syn::Pat::Ident(pat) => pat.ident.to_string(),
+            _ => "payload".to_string(),
+        };
+        Ok(Argument::Payload {
+            name: name.trim_start_matches("r#").to_string(),
+            ty: Box::new(ty.clone()),
+        })
+    }
+}
+
+impl ActionReturn {
+    fn parse(output: &ReturnType) -> Self {
+        let return_type = match output {
+            ReturnType::Default => syn::parse_quote! { () },
+            ReturnType::Type(_, return_type) => return_type.as_ref().clone(),
+        };
+        match result_types(&return_type) {
+            Some((ok, err)) => ActionReturn {
+                ok,
+                err,
+                is_result: true,
+            },
+            None => ActionReturn {
+                ok: return_type,
+                err: None,
+                is_result: false,
+            },
+        }
+    }
+}
+
+/// Last segment of a type path, such as `Swarm` for `swarm_rs::swarm::Swarm`.
+fn type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// Visibility of the client, as visible as its most visible action so that it
+/// does not expose the agent beyond the methods it mirrors.
+fn widest_visibility(current: Visibility, vis: &Visibility) -> Visibility {
+    match (&current, vis) {
+        (Visibility::Public(_), _) | (Visibility::Restricted(_), Visibility::Inherited) => current,
+        _ => vis.clone(),
+    }
+}
+
+fn is_option(ty: &Type) -> bool {
+    type_name(ty).as_deref() == Some("Option")
+}
+
+/// Options of `#[agent_action(...)]` and `#[agent_workflow(...)]`.
+#[derive(Default)]
+struct ActionOptions {
+    name: Option<String>,
+    aliases: Vec<String>,
+    roles: Vec<String>,
+    timeout_ms: Option<u64>,
+    retry: Option<RetryOptions>,
+    deprecated: bool,
+    description: Option<String>,
+}
+
+struct RetryOptions {
+    max_attempts: u32,
+    initial_backoff_ms: Option<u64>,
+    max_backoff_ms: Option<u64>,
+}
+
+impl ActionOptions {
+    /// Parses `name = "..", aliases = [..], roles = [..], timeout_ms = .., deprecated,
+    /// description = "..", retry = 3` or `retry(max_attempts = 3, initial_backoff_ms = .., max_backoff_ms = ..)`.
+    fn parse(attr: &Attribute) -> syn::Result<Self> {
+        let mut options = ActionOptions::default();
+        if !matches!(attr.meta, syn::Meta::List(_)) {
+            return Ok(options);
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                options.name = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("aliases") {
+                options.aliases = parse_strings(meta.value()?)?;
+            } else if meta.path.is_ident("roles") {
+                options.roles = parse_strings(meta.value()?)?;
+            } else if meta.path.is_ident("timeout_ms") {
+                options.timeout_ms = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+            } else if meta.path.is_ident("deprecated") {
+                options.deprecated = true;
+            } else if meta.path.is_ident("description") {
+                options.description = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("retry") {
+                options.retry = Some(RetryOptions::parse(&meta)?);
+            } else {
+                return Err(meta.error(
+                    "expected one of name, aliases, roles, timeout_ms, retry, deprecated or description",
+                ));
+            }
+            Ok(())
+        })?;
+        Ok(options)
+    }
+
+    /// `ActionDescriptor` builder calls, the doc comment describing the action by default.
+    fn descriptor_settings(&self, attrs: &[Attribute]) -> proc_macro2::TokenStream {
+        let mut settings = vec![];
+        if let Some(description) = self.description.clone().or_else(|| doc_comment(attrs)) {
+            settings.push(quote! { .with_description(#description) });
+        }
+        if !self.aliases.is_empty() {
+            let aliases = &self.aliases;
+            settings.push(quote! { .with_aliases(&[#(#aliases),*]) });
+        }
+        if !self.roles.is_empty() {
+            let roles = &self.roles;
+            settings.push(quote! { .with_roles(&[#(#roles),*]) });
+        }
+        if let Some(timeout_ms) = self.timeout_ms {
+            settings.push(quote! { .with_timeout(::std::time::Duration::from_millis(#timeout_ms)) });
+        }
+        if let Some(retry) = &self.retry {
+            let max_attempts = retry.max_attempts;
+            let initial_backoff = retry
+                .initial_backoff_ms
+                .map(|backoff| quote! { .with_initial_backoff_ms(#backoff) });
+            let max_backoff = retry
+                .max_backoff_ms
+                .map(|backoff| quote! { .with_max_backoff_ms(#backoff) });
+            settings.push(quote! {
+                .with_retry(RetrySettings::new(#max_attempts) #initial_backoff #max_backoff)
+            });
+        }
+        if self.deprecated {
+            settings.push(quote! { .deprecated() });
+        }
+        quote! { #(#settings)* }
+    }
+}
+
+impl RetryOptions {
+    fn parse(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Self> {
+        let mut retry = RetryOptions {
+            max_attempts: 0,
+            initial_backoff_ms: None,
+            max_backoff_ms: None,
+        };
+        if meta.input.peek(syn::Token![=]) {
+            retry.max_attempts = meta.value()?.parse::<LitInt>()?.base10_parse()?;
+            return Ok(retry);
+        }
+        meta.parse_nested_meta(|nested| {
+            let value: u64 = nested.value()?.parse::<LitInt>()?.base10_parse()?;
+            if nested.path.is_ident("max_attempts") {
+                retry.max_attempts = value as u32;
+            } else if nested.path.is_ident("initial_backoff_ms") {
+                retry.initial_backoff_ms = Some(value);
+            } else if nested.path.is_ident("max_backoff_ms") {
+                retry.max_backoff_ms = Some(value);
+            } else {
+                return Err(nested.error(
+                    "expected one of max_attempts, initial_backoff_ms or max_backoff_ms",
+                ));
+            }
+            Ok(())
+        })?;
+        if retry.max_attempts == 0 {
+            return Err(meta.error("retry requires max_attempts"));
+        }
+        Ok(retry)
+    }
+}
+
+/// `["a", "b"]`
+fn parse_strings(input: syn::parse::ParseStream) -> syn::Result<Vec<String>> {
+    let content;
+    syn::bracketed!(content in input);
+    let strings = content.parse_terminated(|input| input.parse::<LitStr>(), syn::Token![,])?;
+    Ok(strings.iter().map(|string| string.value()).collect())
+}
+
+/// Doc comment lines of the method, joined.
+fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(text),
+                        ..
+                    }),
+                ..
+            }) => Some(text.value().trim().to_string()),
+            _ => None,
+        })
+        .collect();
+    let doc = lines.join("\n").trim().to_string();
+    if doc.is_empty() {
+        None
+    } else {
+        Some(doc)
+    }
+}
+
+/// `T` and `E` of `Result<T, E>`, or only `T` for the aliases such as `io::Result<T>`.
+fn result_types(return_type: &Type) -> Option<(Type, Option<Type>)> {
+    let Type::Path(type_path) = return_type else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "Result" {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    let mut types = arguments.args.iter().filter_map(|argument| match argument {
+        syn::GenericArgument::Type(argument_type) => Some(argument_type.clone()),
+        _ => None,
+    });
+    Some((types.next()?, types.next()))
+}
@@ -1,42 +1,119 @@
-use std::{any::Any, fmt::Display};
+use std::{any::Any, fmt::Display, time::Duration};
 
 use async_trait::async_trait;
+use schemars::JsonSchema;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Map, Value};
+use tokio::sync::mpsc::UnboundedSender;
+use tokio_util::sync::CancellationToken;
 
-use crate::prelude::Swarm;
+use crate::{
+    context::{ActionContext, ProgressEvent},
+    error::{AgentError, ErrorCode},
+    prelude::Swarm,
+    schema::ActionDescriptor,
+};
 
 #[async_trait]
 pub trait Agent: Any + Send + Sync {
     async fn execute(&self, input: &Action, swarm: &Swarm) -> Output;
     fn as_any(&self) -> &dyn Any;
+
+    /// Actions exposed by the agent.
+    fn describe(&self) -> Vec<ActionDescriptor> {
+        vec![]
+    }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Action {
     id: String,
     pub(crate) payload: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timeout_ms: Option<u64>,
+    #[serde(skip)]
+    context: ActionContext,
 }
 
 impl Action {
     pub fn new<T: Serialize>(id: &str, payload: T) -> Self {
+        let context = if let Some(parent) = ActionContext::current() {
+            parent.child()
+        } else {
+            ActionContext::new()
+        };
         Self {
             id: id.to_string(),
             payload: serde_json::to_value(payload).unwrap(),
+            timeout_ms: None,
+            context,
         }
     }
 
-    pub fn get_payload<T: DeserializeOwned>(&self) -> Result<T, String> {
-        if let Ok(payload) = serde_json::from_value(self.payload.clone()) {
-            Ok(payload)
-        } else {
-            Err("Unable to deserialize payload".to_string())
-        }
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout_ms = Some(timeout.as_millis() as u64);
+        self
+    }
+
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.context.set_cancellation(cancellation);
+        self
+    }
+
+    /// Attributes the action, and the actions it executes, to the user.
+    pub fn with_user(mut self, user_id: &str) -> Self {
+        self.context = self.context.with_user(user_id);
+        self
+    }
+
+    /// Roles granted to the user of the action, and of the actions it executes.
+    pub fn with_roles(mut self, roles: &[String]) -> Self {
+        self.context = self.context.with_roles(roles);
+        self
+    }
+
+    /// Forwards the progress events emitted while the action executes.
+    pub fn with_progress(mut self, sender: UnboundedSender<ProgressEvent>) -> Self {
+        self.context = self.context.with_progress(sender);
+        self
+    }
+
+    pub fn get_timeout(&self) -> Option<Duration> {
+        self.timeout_ms.map(Duration::from_millis)
+    }
+
+    pub fn get_context(&self) -> &ActionContext {
+        &self.context
+    }
+
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        self.context.cancellation_token()
+    }
+
+    pub fn get_payload<T: DeserializeOwned>(&self) -> Result<T, AgentError> {
+        serde_json::from_value(self.payload.clone()).map_err(|e| {
+            AgentError::new(ErrorCode::InvalidPayload, "Unable to get payload")
+                .with_origin(self.get_agent(), self.get_name())
+                .with_cause(AgentError::new(ErrorCode::Internal, &e.to_string()))
+        })
+    }
+
+    /// Field of the payload object, deserialized from `null` when missing.
+    pub fn get_argument<T: DeserializeOwned>(&self, name: &str) -> Result<T, AgentError> {
+        let value = self.payload.get(name).cloned().unwrap_or_default();
+        serde_json::from_value(value).map_err(|e| {
+            AgentError::new(
+                ErrorCode::InvalidPayload,
+                &format!("Unable to get argument {}", name),
+            )
+            .with_origin(self.get_agent(), self.get_name())
+            .with_cause(AgentError::new(ErrorCode::Internal, &e.to_string()))
+        })
     }
 
     pub fn get_name(&self) -> &str {
         if let Some((_, action_id)) = &self.id.split_once(".") {
-            *action_id
+            action_id
         } else {
             "default"
         }
@@ -44,7 +121,7 @@ impl Action {
 
     pub fn get_agent(&self) -> &str {
         if let Some((agent_id, _)) = &self.id.split_once(".") {
-            *agent_id
+            agent_id
         } else {
             &self.id
         }
@@ -55,53 +132,188 @@ impl Action {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+/// Payload object of the actions taking several arguments, built by the typed clients.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(transparent)]
+pub struct NamedArguments(Map<String, Value>);
+
+impl NamedArguments {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with<T: Serialize>(mut self, name: &str, value: &T) -> Self {
+        self.0.insert(
+            name.to_string(),
+            serde_json::to_value(value).unwrap_or_default(),
+        );
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum OutputStatus {
+    Success,
+    Error,
+    Timeout,
+    Cancelled,
+    CircuitOpen,
+}
+
+impl OutputStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputStatus::Success => "SUCCESS",
+            OutputStatus::Error => "ERROR",
+            OutputStatus::Timeout => "TIMEOUT",
+            OutputStatus::Cancelled => "CANCELLED",
+            OutputStatus::CircuitOpen => "CIRCUIT_OPEN",
+        }
+    }
+}
+
+impl Display for OutputStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Output {
     pub agent_id: String,
-    status: String,
+    status: OutputStatus,
     payload: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<AgentError>,
+    /// Execution details, such as `cache` for the outputs served from the cache.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    metadata: Map<String, Value>,
 }
 
 impl Output {
     pub fn new_success<T: Serialize>(payload: T) -> Self {
         Self {
             agent_id: "".to_string(),
-            status: "SUCCESS".to_string(),
+            status: OutputStatus::Success,
             payload: serde_json::to_value(payload).unwrap(),
+            error: None,
+            metadata: Map::new(),
         }
     }
 
     pub fn new_error(message: &str) -> Self {
+        Self::from_error(AgentError::action_failed(message))
+    }
+
+    pub fn new_timeout(message: &str) -> Self {
+        Self::from_error(AgentError::new(ErrorCode::Timeout, message))
+    }
+
+    pub fn new_cancelled(message: &str) -> Self {
+        Self::from_error(AgentError::new(ErrorCode::Cancelled, message))
+    }
+
+    pub fn from_error(error: AgentError) -> Self {
+        let status = match error.code {
+            ErrorCode::Timeout => OutputStatus::Timeout,
+            ErrorCode::Cancelled => OutputStatus::Cancelled,
+            ErrorCode::CircuitOpen => OutputStatus::CircuitOpen,
+            _ => OutputStatus::Error,
+        };
         Self {
-            agent_id: "".to_string(),
-            status: "ERROR".to_string(),
-            payload: serde_json::to_value(message).unwrap(),
+            agent_id: error.agent_id.to_string(),
+            status,
+            payload: Value::Null,
+            error: Some(error),
+            metadata: Map::new(),
         }
     }
 
-    pub fn get_payload<T: DeserializeOwned>(&self) -> T {
-        serde_json::from_value(self.payload.clone()).unwrap()
+    pub fn from_result<T: Serialize, E: Into<AgentError>>(result: Result<T, E>) -> Self {
+        match result {
+            Ok(payload) => Self::new_success(payload),
+            Err(error) => Self::from_error(error.into()),
+        }
+    }
+
+    /// Records the agent and action that produced this output.
+    pub fn set_origin(&mut self, action: &Action) {
+        self.agent_id = action.get_agent().to_string();
+        if let Some(error) = &mut self.error {
+            error.set_origin(action.get_agent(), action.get_name());
+        }
+    }
+
+    pub fn get_payload<T: DeserializeOwned>(&self) -> Result<T, AgentError> {
+        if let Some(error) = &self.error {
+            return Err(error.clone());
+        }
+        serde_json::from_value(self.payload.clone()).map_err(|e| {
+            AgentError::new(ErrorCode::InvalidOutput, "Unable to decode output payload")
+                .with_origin(&self.agent_id, "")
+                .with_cause(AgentError::new(ErrorCode::Internal, &e.to_string()))
+        })
     }
 
     pub fn get_value(&self) -> &Value {
         &self.payload
     }
 
+    pub fn get_error(&self) -> Option<&AgentError> {
+        self.error.as_ref()
+    }
+
     pub fn get_error_message(&self) -> String {
-        self.get_payload::<String>()
+        if let Some(error) = &self.error {
+            error.message.to_string()
+        } else {
+            "".to_string()
+        }
+    }
+
+    pub fn get_metadata(&self, key: &str) -> Option<&Value> {
+        self.metadata.get(key)
+    }
+
+    pub fn set_metadata<T: Serialize>(&mut self, key: &str, value: T) {
+        self.metadata.insert(
+            key.to_string(),
+            serde_json::to_value(value).unwrap_or_default(),
+        );
+    }
+
+    pub fn is_cache_hit(&self) -> bool {
+        self.get_metadata("cache")
+            .is_some_and(|cache| cache["hit"] == true)
+    }
+
+    pub fn get_status(&self) -> OutputStatus {
+        self.status
     }
 
     pub fn is_success(&self) -> bool {
-        self.status.as_str() == "SUCCESS"
+        self.status == OutputStatus::Success
+    }
+
+    pub fn is_timeout(&self) -> bool {
+        self.status == OutputStatus::Timeout
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.status == OutputStatus::Cancelled
+    }
+
+    pub fn is_circuit_open(&self) -> bool {
+        self.status == OutputStatus::CircuitOpen
     }
 }
 
 impl Display for Output {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.is_success() {
-            write!(f, "SUCCESS : {}", self.get_value())
-        } else {
-            write!(f, "ERROR : {}", self.get_error_message())
+        match &self.error {
+            Some(error) => write!(f, "{} : {}", self.status, error),
+            None => write!(f, "{} : {}", self.status, self.get_value()),
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,325 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    agent::{Action, Output},
+    error::AgentError,
+    utils::{json_directory::JsonDirectory, pattern::wildcard_match, time::current_time_iso},
+};
+
+/// Successful output cached for an action id and payload.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub key: String,
+    pub action_id: String,
+    /// User the action was executed for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    pub payload: Value,
+    pub output: Output,
+    pub created_at: String,
+    /// Expiration date in milliseconds since the epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+}
+
+impl CacheEntry {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= Utc::now().timestamp_millis())
+    }
+}
+
+/// Backend of a [`ResponseCache`].
+pub trait CacheStore: Send + Sync {
+    fn get(&self, key: &str) -> Result<Option<CacheEntry>, AgentError>;
+
+    fn put(&self, entry: &CacheEntry) -> Result<(), AgentError>;
+
+    fn remove(&self, key: &str) -> Result<(), AgentError>;
+}
+
+/// Keeps the `capacity` most recently used entries in memory.
+pub struct MemoryCacheStore {
+    capacity: usize,
+    entries: Mutex<LruEntries>,
+}
+
+#[derive(Default)]
+struct LruEntries {
+    entries: HashMap<String, CacheEntry>,
+    /// Keys from the least to the most recently used.
+    order: VecDeque<String>,
+}
+
+impl LruEntries {
+    fn touch(&mut self, key: &str) {
+        if let Some(position) = self.order.iter().position(|used| used == key) {
+            self.order.remove(position);
+        }
+        self.order.push_back(key.to_string());
+    }
+}
+
+impl MemoryCacheStore {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: Mutex::new(LruEntries::default()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .expect("Failed to lock cache")
+            .entries
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl CacheStore for MemoryCacheStore {
+    fn get(&self, key: &str) -> Result<Option<CacheEntry>, AgentError> {
+        let mut lru = self.entries.lock().expect("Failed to lock cache");
+        let entry = lru.entries.get(key).cloned();
+        if entry.is_some() {
+            lru.touch(key);
+        }
+        Ok(entry)
+    }
+
+    fn put(&self, entry: &CacheEntry) -> Result<(), AgentError> {
+        let mut lru = self.entries.lock().expect("Failed to lock cache");
+        lru.entries.insert(entry.key.to_string(), entry.clone());
+        lru.touch(&entry.key);
+        while lru.entries.len() > self.capacity {
+            if let Some(evicted) = lru.order.pop_front() {
+                lru.entries.remove(&evicted);
+            }
+        }
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> Result<(), AgentError> {
+        let mut lru = self.entries.lock().expect("Failed to lock cache");
+        lru.entries.remove(key);
+        lru.order.retain(|used| used != key);
+        Ok(())
+    }
+}
+
+/// Stores each entry as a JSON file named after its key, surviving restarts.
+pub struct FileCacheStore {
+    directory: JsonDirectory,
+}
+
+impl FileCacheStore {
+    pub fn new<P: AsRef<Path>>(base_dir: P) -> Self {
+        Self {
+            directory: JsonDirectory::new(base_dir),
+        }
+    }
+}
+
+impl CacheStore for FileCacheStore {
+    fn get(&self, key: &str) -> Result<Option<CacheEntry>, AgentError> {
+        self.directory.load(key)
+    }
+
+    fn put(&self, entry: &CacheEntry) -> Result<(), AgentError> {
+        self.directory.save(&entry.key, entry)
+    }
+
+    fn remove(&self, key: &str) -> Result<(), AgentError> {
+        self.directory.remove(key)
+    }
+}
+
+/// Cache of the successful outputs, keyed on the action id, the user and the canonical payload.
+///
+/// Only the actions matching an `include` pattern are cached, except the ones matching
+/// a `bypass` pattern. Patterns apply to the action ids, `*` matching any sequence of characters.
+pub struct ResponseCache {
+    store: Arc<dyn CacheStore>,
+    ttl: Option<Duration>,
+    action_ttls: Vec<(String, Duration)>,
+    include: Vec<String>,
+    bypass: Vec<String>,
+}
+
+impl ResponseCache {
+    pub fn new<T: CacheStore + 'static>(store: T) -> Self {
+        Self {
+            store: Arc::new(store),
+            ttl: None,
+            action_ttls: vec![],
+            include: vec![],
+            bypass: vec![],
+        }
+    }
+
+    /// Time to live of the entries, which never expire by default.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Time to live of the matching actions, the first matching pattern winning.
+    pub fn with_action_ttl(mut self, action_pattern: &str, ttl: Duration) -> Self {
+        self.action_ttls.push((action_pattern.to_string(), ttl));
+        self
+    }
+
+    /// Caches the matching actions, none being cached by default.
+    pub fn include(mut self, action_pattern: &str) -> Self {
+        self.include.push(action_pattern.to_string());
+        self
+    }
+
+    /// Executes the matching actions without the cache, even when included.
+    pub fn bypass(mut self, action_pattern: &str) -> Self {
+        self.bypass.push(action_pattern.to_string());
+        self
+    }
+
+    pub fn is_cached(&self, action_id: &str) -> bool {
+        let matches = |patterns: &[String]| {
+            patterns
+                .iter()
+                .any(|pattern| wildcard_match(pattern, action_id))
+        };
+        matches(&self.include) && !matches(&self.bypass)
+    }
+
+    fn get_ttl(&self, action_id: &str) -> Option<Duration> {
+        self.action_ttls
+            .iter()
+            .find(|(pattern, _)| wildcard_match(pattern, action_id))
+            .map(|(_, ttl)| *ttl)
+            .or(self.ttl)
+    }
+
+    /// Cached output of the action, flagged as a cache hit in its metadata.
+    pub fn lookup(&self, action: &Action) -> Result<Option<Output>, AgentError> {
+        let user_id = action.get_context().get_user_id();
+        let key = cache_key(action.get_id(), user_id, &action.payload);
+        let entry = match self.store.get(&key)? {
+            Some(entry)
+                if entry.action_id == action.get_id()
+                    && entry.user_id.as_deref() == user_id
+                    && entry.payload == action.payload =>
+            {
+                entry
+            }
+            _ => return Ok(None),
+        };
+        if entry.is_expired() {
+            self.store.remove(&key)?;
+            return Ok(None);
+        }
+        let mut output = entry.output;
+        output.set_metadata(
+            "cache",
+            json!({ "hit": true, "key": key, "created_at": entry.created_at }),
+        );
+        Ok(Some(output))
+    }
+
+    /// Caches the output when successful.
+    pub fn store(&self, action: &Action, output: &Output) -> Result<(), AgentError> {
+        if !output.is_success() {
+            return Ok(());
+        }
+        let user_id = action.get_context().get_user_id();
+        let entry = CacheEntry {
+            key: cache_key(action.get_id(), user_id, &action.payload),
+            action_id: action.get_id().to_string(),
+            user_id: user_id.map(str::to_string),
+            payload: action.payload.clone(),
+            output: output.clone(),
+            created_at: current_time_iso(),
+            expires_at: self
+                .get_ttl(action.get_id())
+                .map(|ttl| Utc::now().timestamp_millis() + ttl.as_millis() as i64),
+        };
+        self.store.put(&entry)
+    }
+
+    pub fn invalidate(&self, action: &Action) -> Result<(), AgentError> {
+        let user_id = action.get_context().get_user_id();
+        self.store
+            .remove(&cache_key(action.get_id(), user_id, &action.payload))
+    }
+}
+
+/// Key made of the action id and of a stable hash of the user and of the canonical payload.
+pub fn cache_key(action_id: &str, user_id: Option<&str>, payload: &Value) -> String {
+    let mut canonical = String::new();
+    write_canonical(&json!(user_id), &mut canonical);
+    canonical.push('\n');
+    write_canonical(payload, &mut canonical);
+    // FNV-1a, stable across runs unlike the std hasher
+    let hash = canonical.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    let action_id: String = action_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    format!("{}-{:016x}", action_id, hash)
+}
+
+/// JSON with the object keys sorted.
+fn write_canonical(value: &Value, output: &mut String) {
+    match value {
+        Value::Object(object) => {
+            let mut keys: Vec<&String> = object.keys().collect();
+            keys.sort();
+            output.push('{');
+            for (index, key) in keys.into_iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+                output.push_str(&Value::String(key.to_string()).to_string());
+                output.push(':');
+                write_canonical(&object[key], output);
+            }
+            output.push('}');
+        }
+        Value::Array(items) => {
+            output.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+                write_canonical(item, output);
+            }
+            output.push(']');
+        }
+        _ => output.push_str(&value.to_string()),
+    }
+}
+
+#[test]
+pub fn test_cache_key() {
+    let key = cache_key("searx.search", None, &json!({ "query": "rust", "page": { "size": 10, "index": 1 } }));
+    assert_eq!(
+        key,
+        cache_key("searx.search", None, &json!({ "page": { "index": 1, "size": 10 }, "query": "rust" }))
+    );
+    assert!(key.starts_with("searx_search-"));
+    assert_ne!(key, cache_key("searx.search", None, &json!({ "query": "rust" })));
+    assert_ne!(key, cache_key("searx.search", Some("alice"), &json!({ "query": "rust", "page": { "size": 10, "index": 1 } })));
+}
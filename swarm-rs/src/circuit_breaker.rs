@@ -0,0 +1,169 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AgentError, ErrorCode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+/// Opens the circuit of an agent after `failure_threshold` consecutive
+/// failures, and lets a single trial call through once `cooldown` elapsed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitBreakerConfig {
+    pub failure_threshold: u32,
+    pub cooldown_ms: u64,
+}
+
+impl CircuitBreakerConfig {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold,
+            cooldown_ms: cooldown.as_millis() as u64,
+        }
+    }
+
+    pub fn get_cooldown(&self) -> Duration {
+        Duration::from_millis(self.cooldown_ms)
+    }
+}
+
+struct BreakerState {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    trial_in_flight: bool,
+    /// Number of times the circuit opened or closed.
+    generation: u64,
+}
+
+pub(crate) struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(BreakerState {
+                consecutive_failures: 0,
+                opened_at: None,
+                trial_in_flight: false,
+                generation: 0,
+            }),
+        }
+    }
+
+    pub(crate) fn get_state(&self) -> CircuitState {
+        let state = self.state.lock().expect("Failed to lock circuit breaker");
+        self.current_state(&state)
+    }
+
+    fn current_state(&self, state: &BreakerState) -> CircuitState {
+        match state.opened_at {
+            None => CircuitState::Closed,
+            Some(opened_at) if opened_at.elapsed() >= self.config.get_cooldown() => {
+                CircuitState::HalfOpen
+            }
+            Some(_) => CircuitState::Open,
+        }
+    }
+
+    /// Lets a call go through when the circuit allows it, reserving the half-open trial if needed.
+    /// The trial is released when the permit is dropped without recording an outcome.
+    pub(crate) fn try_acquire(&self) -> Option<CircuitPermit<'_>> {
+        let mut state = self.state.lock().expect("Failed to lock circuit breaker");
+        let trial = match self.current_state(&state) {
+            CircuitState::Closed => false,
+            CircuitState::Open => return None,
+            CircuitState::HalfOpen => {
+                if state.trial_in_flight {
+                    return None;
+                }
+                state.trial_in_flight = true;
+                true
+            }
+        };
+        Some(CircuitPermit {
+            breaker: self,
+            trial,
+            generation: state.generation,
+        })
+    }
+
+    /// Applies the outcome of a call, ignored when the circuit opened or closed since it
+    /// was let through: the call then tells nothing about the service as it is now.
+    fn record(&self, trial: bool, generation: u64, error: Option<&AgentError>) -> CircuitState {
+        let mut state = self.state.lock().expect("Failed to lock circuit breaker");
+        if trial {
+            state.trial_in_flight = false;
+        }
+        if generation != state.generation {
+            return self.current_state(&state);
+        }
+        let previous = self.current_state(&state);
+        match error {
+            Some(error) if is_failure(error) => {
+                state.consecutive_failures += 1;
+                if previous == CircuitState::HalfOpen
+                    || state.consecutive_failures >= self.config.failure_threshold
+                {
+                    state.opened_at = Some(Instant::now());
+                    state.generation += 1;
+                }
+            }
+            Some(_) => {}
+            None => {
+                state.consecutive_failures = 0;
+                if state.opened_at.take().is_some() {
+                    state.generation += 1;
+                }
+            }
+        }
+        self.current_state(&state)
+    }
+}
+
+/// Call let through by the circuit breaker.
+pub(crate) struct CircuitPermit<'a> {
+    breaker: &'a CircuitBreaker,
+    trial: bool,
+    generation: u64,
+}
+
+impl CircuitPermit<'_> {
+    /// Records the outcome of the call and returns the resulting state.
+    pub(crate) fn record(mut self, error: Option<&AgentError>) -> CircuitState {
+        let trial = std::mem::replace(&mut self.trial, false);
+        self.breaker.record(trial, self.generation, error)
+    }
+}
+
+impl Drop for CircuitPermit<'_> {
+    fn drop(&mut self) {
+        if self.trial {
+            let mut state = self
+                .breaker
+                .state
+                .lock()
+                .expect("Failed to lock circuit breaker");
+            state.trial_in_flight = false;
+        }
+    }
+}
+
+/// Only errors raised by the agent itself count as failures, not the caller ones.
+fn is_failure(error: &AgentError) -> bool {
+    matches!(
+        error.code,
+        ErrorCode::ActionFailed | ErrorCode::Timeout | ErrorCode::RateLimited | ErrorCode::Internal
+    )
+}
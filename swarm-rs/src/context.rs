@@ -0,0 +1,164 @@
+use std::future::Future;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio_util::sync::CancellationToken;
+
+use crate::{utils::time::current_time_iso, workflow::new_run_id};
+
+tokio::task_local! {
+    static CURRENT_CONTEXT: ActionContext;
+}
+
+/// Partial result emitted by an action while it executes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgressEvent {
+    /// Id of the action emitting the event, possibly nested in the executed one.
+    pub action_id: String,
+    pub event: String,
+    pub data: Value,
+    pub timestamp: String,
+}
+
+/// Runtime context attached to an [`Action`](crate::agent::Action).
+///
+/// Actions created while another action is executing inherit a child of
+/// its context, so cancelling a workflow also cancels its nested calls,
+/// and their progress events reach the same listener. The user and the
+/// workflow run they are executed for are inherited as well.
+#[derive(Clone, Default)]
+pub struct ActionContext {
+    cancellation: CancellationToken,
+    progress: Option<UnboundedSender<ProgressEvent>>,
+    action_id: String,
+    user_id: Option<String>,
+    roles: Vec<String>,
+    workflow_id: Option<String>,
+    run_id: Option<String>,
+}
+
+impl ActionContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_cancellation(cancellation: CancellationToken) -> Self {
+        Self {
+            cancellation,
+            ..Self::default()
+        }
+    }
+
+    /// Forwards the progress events of the action and of its nested actions.
+    pub fn with_progress(mut self, sender: UnboundedSender<ProgressEvent>) -> Self {
+        self.progress = Some(sender);
+        self
+    }
+
+    /// Authenticated user the action is executed for.
+    pub fn with_user(mut self, user_id: &str) -> Self {
+        self.user_id = Some(user_id.to_string());
+        self
+    }
+
+    /// Roles granted to the user, checked against the roles declared by the actions.
+    pub fn with_roles(mut self, roles: &[String]) -> Self {
+        self.roles = roles.to_vec();
+        self
+    }
+
+    /// Returns the context of the action currently executing, if any.
+    pub fn current() -> Option<Self> {
+        CURRENT_CONTEXT.try_with(|context| context.clone()).ok()
+    }
+
+    pub fn child(&self) -> Self {
+        Self {
+            cancellation: self.cancellation.child_token(),
+            progress: self.progress.clone(),
+            action_id: String::new(),
+            user_id: self.user_id.clone(),
+            roles: self.roles.clone(),
+            workflow_id: self.workflow_id.clone(),
+            run_id: self.run_id.clone(),
+        }
+    }
+
+    pub fn get_action_id(&self) -> &str {
+        &self.action_id
+    }
+
+    pub fn get_user_id(&self) -> Option<&str> {
+        self.user_id.as_deref()
+    }
+
+    pub fn get_roles(&self) -> &[String] {
+        &self.roles
+    }
+
+    pub fn get_workflow_id(&self) -> Option<&str> {
+        self.workflow_id.as_deref()
+    }
+
+    /// Innermost workflow run the action belongs to.
+    pub fn get_run_id(&self) -> Option<&str> {
+        self.run_id.as_deref()
+    }
+
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancellation
+    }
+
+    pub fn cancel(&self) {
+        self.cancellation.cancel();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.is_cancelled()
+    }
+
+    /// Sends a progress event, ignored when nobody listens.
+    pub fn emit<T: Serialize>(&self, event: &str, data: T) {
+        if let Some(sender) = &self.progress {
+            let _ = sender.send(ProgressEvent {
+                action_id: self.action_id.to_string(),
+                event: event.to_string(),
+                data: serde_json::to_value(data).unwrap_or_default(),
+                timestamp: current_time_iso(),
+            });
+        }
+    }
+
+    pub(crate) fn set_cancellation(&mut self, cancellation: CancellationToken) {
+        self.cancellation = cancellation;
+    }
+
+    pub(crate) fn set_action_id(&mut self, action_id: &str) {
+        self.action_id = action_id.to_string();
+    }
+
+    pub(crate) fn set_run(&mut self, workflow_id: &str, run_id: &str) {
+        self.workflow_id = Some(workflow_id.to_string());
+        self.run_id = Some(run_id.to_string());
+    }
+
+    pub(crate) async fn scope<F: Future>(self, future: F) -> F::Output {
+        CURRENT_CONTEXT.scope(self, future).await
+    }
+}
+
+/// Executes a new run of the workflow, the actions it executes being attributed
+/// to the run, such as for the usage and budgets of the workflow.
+pub async fn scope_workflow_run<F: Future>(workflow_id: &str, future: F) -> F::Output {
+    let mut context = ActionContext::current().unwrap_or_default();
+    context.set_run(workflow_id, &new_run_id(workflow_id));
+    context.scope(future).await
+}
+
+/// Emits a progress event from the action currently executing.
+pub fn emit_progress<T: Serialize>(event: &str, data: T) {
+    if let Some(context) = ActionContext::current() {
+        context.emit(event, data);
+    }
+}
@@ -0,0 +1,245 @@
+use std::{cell::Cell, fmt::Display, time::Duration};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    AgentNotFound,
+    UnknownAction,
+    InvalidPayload,
+    InvalidOutput,
+    ActionFailed,
+    Timeout,
+    Cancelled,
+    CircuitOpen,
+    BudgetExceeded,
+    /// A service used by the agent asks to slow down.
+    RateLimited,
+    /// The action declares roles and is not executed for a user.
+    Unauthorized,
+    /// The user has none of the roles declared by the action.
+    Forbidden,
+    Internal,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::AgentNotFound => "AGENT_NOT_FOUND",
+            ErrorCode::UnknownAction => "UNKNOWN_ACTION",
+            ErrorCode::InvalidPayload => "INVALID_PAYLOAD",
+            ErrorCode::InvalidOutput => "INVALID_OUTPUT",
+            ErrorCode::ActionFailed => "ACTION_FAILED",
+            ErrorCode::Timeout => "TIMEOUT",
+            ErrorCode::Cancelled => "CANCELLED",
+            ErrorCode::CircuitOpen => "CIRCUIT_OPEN",
+            ErrorCode::BudgetExceeded => "BUDGET_EXCEEDED",
+            ErrorCode::RateLimited => "RATE_LIMITED",
+            ErrorCode::Unauthorized => "UNAUTHORIZED",
+            ErrorCode::Forbidden => "FORBIDDEN",
+            ErrorCode::Internal => "INTERNAL",
+        }
+    }
+
+    fn is_retryable(&self) -> bool {
+        matches!(self, ErrorCode::Timeout | ErrorCode::RateLimited)
+    }
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Error reported by an agent action, carried by an [`Output`](crate::agent::Output).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AgentError {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(default)]
+    pub agent_id: String,
+    #[serde(default)]
+    pub action: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cause: Option<Box<AgentError>>,
+    #[serde(default)]
+    pub retryable: bool,
+    /// Structured data of the error, such as the serialized error of the action when
+    /// it is not an `AgentError`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<Value>,
+}
+
+impl AgentError {
+    pub fn new(code: ErrorCode, message: &str) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+            agent_id: "".to_string(),
+            action: "".to_string(),
+            cause: None,
+            retryable: code.is_retryable(),
+            details: None,
+        }
+    }
+
+    pub fn action_failed(message: &str) -> Self {
+        Self::new(ErrorCode::ActionFailed, message)
+    }
+
+    pub fn with_cause(mut self, cause: AgentError) -> Self {
+        self.cause = Some(Box::new(cause));
+        self
+    }
+
+    pub fn with_retryable(mut self, retryable: bool) -> Self {
+        self.retryable = retryable;
+        self
+    }
+
+    /// Delay asked for before retrying, such as the `retry-after` of a rate limit,
+    /// kept in seconds as the `retry_after` of the details.
+    pub fn with_retry_after(mut self, retry_after: Duration) -> Self {
+        let mut details = match self.details.take() {
+            Some(Value::Object(details)) => details,
+            _ => Map::new(),
+        };
+        details.insert("retry_after".to_string(), json!(retry_after.as_secs()));
+        self.details = Some(Value::Object(details));
+        self
+    }
+
+    pub fn get_retry_after(&self) -> Option<Duration> {
+        self.details
+            .as_ref()
+            .and_then(|details| details["retry_after"].as_u64())
+            .map(Duration::from_secs)
+    }
+
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    pub fn with_origin(mut self, agent_id: &str, action: &str) -> Self {
+        self.set_origin(agent_id, action);
+        self
+    }
+
+    pub(crate) fn set_origin(&mut self, agent_id: &str, action: &str) {
+        if self.agent_id.is_empty() {
+            self.agent_id = agent_id.to_string();
+        }
+        if self.action.is_empty() {
+            self.action = action.to_string();
+        }
+    }
+
+    /// Iterates over this error and its causes, outermost first.
+    pub fn chain(&self) -> impl Iterator<Item = &AgentError> {
+        std::iter::successors(Some(self), |error| error.cause.as_deref())
+    }
+
+    pub fn root_cause(&self) -> &AgentError {
+        self.chain().last().unwrap_or(self)
+    }
+}
+
+impl Display for AgentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}", self.code, self.message)?;
+        if let Some(cause) = &self.cause {
+            write!(f, ", caused by {}", cause)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for AgentError {}
+
+impl From<String> for AgentError {
+    fn from(message: String) -> Self {
+        Self::action_failed(&message)
+    }
+}
+
+impl From<&str> for AgentError {
+    fn from(message: &str) -> Self {
+        Self::action_failed(message)
+    }
+}
+
+/// Converts the `Err` value of an action through [`IntoAgentError`], then
+/// [`DisplayAgentError`], then [`SerializeAgentError`], the first one implemented
+/// by the error type winning: `(&&&ErrorProbe::new(error)).agent_error()`.
+#[doc(hidden)]
+pub struct ErrorProbe<E>(Cell<Option<E>>);
+
+impl<E> ErrorProbe<E> {
+    pub fn new(error: E) -> Self {
+        Self(Cell::new(Some(error)))
+    }
+
+    fn take(&self) -> E {
+        self.0.take().expect("Error already converted")
+    }
+}
+
+#[doc(hidden)]
+pub trait IntoAgentError {
+    fn agent_error(self) -> AgentError;
+}
+
+impl<E: Into<AgentError>> IntoAgentError for &&&ErrorProbe<E> {
+    fn agent_error(self) -> AgentError {
+        self.take().into()
+    }
+}
+
+#[doc(hidden)]
+pub trait DisplayAgentError {
+    fn agent_error(self) -> AgentError;
+}
+
+impl<E: Display> DisplayAgentError for &&ErrorProbe<E> {
+    fn agent_error(self) -> AgentError {
+        AgentError::action_failed(&self.take().to_string())
+    }
+}
+
+#[doc(hidden)]
+pub trait SerializeAgentError {
+    fn agent_error(self) -> AgentError;
+}
+
+impl<E: Serialize> SerializeAgentError for &ErrorProbe<E> {
+    fn agent_error(self) -> AgentError {
+        let details = serde_json::to_value(self.take()).unwrap_or_default();
+        let message = match &details {
+            Value::String(message) => message.to_string(),
+            details => details.to_string(),
+        };
+        AgentError::action_failed(&message).with_details(details)
+    }
+}
+
+#[test]
+#[allow(clippy::needless_borrow)]
+pub fn test_error_probe() {
+    #[derive(Serialize)]
+    struct QuotaError {
+        remaining: u32,
+    }
+
+    let error = (&&&ErrorProbe::new("Missing terms".to_string())).agent_error();
+    assert_eq!(error.message, "Missing terms");
+    let error = (&&&ErrorProbe::new(std::fmt::Error)).agent_error();
+    assert_eq!(error.message, "an error occurred when formatting an argument");
+    let error = (&&&ErrorProbe::new(QuotaError { remaining: 0 })).agent_error();
+    assert_eq!(error.code, ErrorCode::ActionFailed);
+    assert_eq!(error.details, Some(serde_json::json!({ "remaining": 0 })));
+}
@@ -0,0 +1,186 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    agent::Output,
+    error::{AgentError, ErrorCode},
+    middleware::{Invocation, Middleware},
+    swarm::Swarm,
+    utils::json_io,
+};
+
+/// Environment variable switching [`SwarmFixture::from_env`] to the record mode when set to `record`.
+pub const FIXTURE_MODE_VAR: &str = "SWARM_FIXTURES";
+
+/// Action executed while recording, with its output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixtureEntry {
+    pub action_id: String,
+    pub payload: Value,
+    pub output: Output,
+}
+
+/// Recorded actions, in their execution order.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Fixture {
+    pub entries: Vec<FixtureEntry>,
+}
+
+impl Fixture {
+    pub fn load<P: AsRef<Path>>(file_path: P) -> Result<Self, String> {
+        json_io::load(file_path)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, file_path: P) -> Result<(), String> {
+        let file_path = file_path.as_ref();
+        json_io::write(file_path, self)
+            .map_err(|_| format!("Unable to save fixture {}", file_path.display()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FixtureMode {
+    /// Executes the actions and writes their outputs to the fixture file.
+    Record,
+    /// Serves the recorded outputs instead of executing the actions.
+    Replay,
+}
+
+struct FixtureState {
+    file_path: PathBuf,
+    mode: FixtureMode,
+    fixture: Fixture,
+    /// Times each entry was replayed.
+    replayed: Vec<usize>,
+    misses: Vec<String>,
+}
+
+/// Middleware recording the outputs of the actions into a fixture file, or
+/// replaying them so the tests run offline and deterministically.
+///
+/// Register it for the agents calling external services, such as
+/// `swarm.add_agent_middleware("searx_ng", fixture.clone())`, so the
+/// workflows calling them still execute. Identical actions are replayed in
+/// their recorded order, the last output being served once they are exhausted.
+/// Actions missing from the fixture fail with an `INTERNAL` error naming them.
+#[derive(Clone)]
+pub struct SwarmFixture {
+    state: Arc<Mutex<FixtureState>>,
+}
+
+impl SwarmFixture {
+    fn new(file_path: &Path, mode: FixtureMode, fixture: Fixture) -> Self {
+        let replayed = vec![0; fixture.entries.len()];
+        Self {
+            state: Arc::new(Mutex::new(FixtureState {
+                file_path: file_path.to_path_buf(),
+                mode,
+                fixture,
+                replayed,
+                misses: vec![],
+            })),
+        }
+    }
+
+    /// Records into the file, replacing its previous content.
+    pub fn record<P: AsRef<Path>>(file_path: P) -> Self {
+        Self::new(file_path.as_ref(), FixtureMode::Record, Fixture::default())
+    }
+
+    pub fn replay<P: AsRef<Path>>(file_path: P) -> Result<Self, String> {
+        let fixture = Fixture::load(&file_path)?;
+        Ok(Self::new(file_path.as_ref(), FixtureMode::Replay, fixture))
+    }
+
+    /// Replays the file, unless [`FIXTURE_MODE_VAR`] asks to record it again.
+    pub fn from_env<P: AsRef<Path>>(file_path: P) -> Result<Self, String> {
+        match std::env::var(FIXTURE_MODE_VAR).as_deref() {
+            Ok("record") => Ok(Self::record(file_path)),
+            _ => Self::replay(file_path),
+        }
+    }
+
+    pub fn get_mode(&self) -> FixtureMode {
+        self.lock().mode
+    }
+
+    /// Actions replayed without a recorded output.
+    pub fn get_misses(&self) -> Vec<String> {
+        self.lock().misses.clone()
+    }
+
+    pub fn get_fixture(&self) -> Fixture {
+        self.lock().fixture.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, FixtureState> {
+        self.state.lock().expect("Failed to lock fixture")
+    }
+}
+
+impl FixtureState {
+    fn replay(&mut self, action_id: &str, payload: &Value) -> Option<Output> {
+        let matching: Vec<usize> = self
+            .fixture
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.action_id == action_id && &entry.payload == payload)
+            .map(|(index, _)| index)
+            .collect();
+        let index = matching
+            .iter()
+            .find(|index| self.replayed[**index] == 0)
+            .or(matching.last())?;
+        self.replayed[*index] += 1;
+        Some(self.fixture.entries[*index].output.clone())
+    }
+}
+
+#[async_trait]
+impl Middleware for SwarmFixture {
+    async fn before(&self, invocation: &Invocation<'_>, _swarm: &Swarm) -> Option<Output> {
+        let action = invocation.get_action();
+        let mut state = self.lock();
+        if state.mode == FixtureMode::Record {
+            return None;
+        }
+        if let Some(output) = state.replay(action.get_id(), &action.payload) {
+            return Some(output);
+        }
+        let message = format!(
+            "Action {} with payload {} is not recorded in {}",
+            action.get_id(),
+            action.payload,
+            state.file_path.display()
+        );
+        state.misses.push(message.to_string());
+        Some(Output::from_error(AgentError::new(
+            ErrorCode::Internal,
+            &message,
+        )))
+    }
+
+    async fn after(&self, invocation: &Invocation<'_>, output: Output, swarm: &Swarm) -> Output {
+        let action = invocation.get_action();
+        let mut state = self.lock();
+        if state.mode == FixtureMode::Record {
+            state.fixture.entries.push(FixtureEntry {
+                action_id: action.get_id().to_string(),
+                payload: action.payload.clone(),
+                output: output.clone(),
+            });
+            if let Err(error) = state.fixture.save(&state.file_path) {
+                swarm.get_logger().warn("Fixture", &error);
+            }
+        }
+        output
+    }
+}
@@ -1,4 +1,9 @@
 pub mod agent;
+pub mod cache;
+pub mod circuit_breaker;
+pub mod context;
+pub mod error;
+pub mod fixture;
 pub mod swarm;
 pub mod llm_agent;
 pub mod searx_agent;
@@ -6,4 +11,10 @@ pub mod prelude;
 pub mod web;
 pub mod utils;
 pub mod logger;
+pub mod middleware;
+pub mod retry;
+pub mod schema;
+pub mod usage;
+pub mod workflow;
 
+pub use schemars;
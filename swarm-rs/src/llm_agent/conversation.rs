@@ -0,0 +1,199 @@
+use std::{collections::HashMap, path::Path, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::AgentError,
+    utils::{json_directory::JsonDirectory, time::current_time_iso},
+};
+
+use super::{llm_message::LLMMessage, llm_response::LLMUsage};
+
+/// User and assistant messages exchanged in a conversation, without the system prompt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Conversation {
+    pub conversation_id: String,
+    /// User owning the conversation, the only one allowed to continue it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    pub messages: Vec<LLMMessage>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl Conversation {
+    pub fn new(conversation_id: &str) -> Self {
+        let now = current_time_iso();
+        Self {
+            conversation_id: conversation_id.to_string(),
+            user_id: None,
+            messages: vec![],
+            created_at: now.to_string(),
+            updated_at: now,
+        }
+    }
+
+    pub fn with_user(mut self, user_id: Option<&str>) -> Self {
+        self.user_id = user_id.map(str::to_string);
+        self
+    }
+
+    /// Drops the oldest turns until the history fits in the budget, always
+    /// keeping the last turn.
+    pub fn trim(&mut self, budget: &ConversationBudget) {
+        loop {
+            let turns = self.turn_starts();
+            let over_turns = budget.max_turns.is_some_and(|max| turns.len() > max);
+            let over_tokens = budget
+                .max_tokens
+                .is_some_and(|max| estimate_tokens(&self.messages) > max);
+            if turns.len() <= 1 || !(over_turns || over_tokens) {
+                break;
+            }
+            self.messages.drain(..turns[1]);
+        }
+    }
+
+    /// Index of the user messages starting each turn.
+    fn turn_starts(&self) -> Vec<usize> {
+        self.messages
+            .iter()
+            .enumerate()
+            .filter(|(_, message)| message.role == "user")
+            .map(|(index, _)| index)
+            .collect()
+    }
+}
+
+/// Limits of the history sent to the model, a turn being a user message and its reply.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConversationBudget {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_turns: Option<usize>,
+    /// Estimated as a token per 4 characters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<usize>,
+}
+
+pub fn estimate_tokens(messages: &[LLMMessage]) -> usize {
+    messages
+        .iter()
+        .map(|message| message.content.chars().count().div_ceil(4))
+        .sum()
+}
+
+/// Payload of the `converse` action.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationTurn {
+    pub conversation_id: String,
+    pub message: String,
+    /// Values of the role, goal and rules templates.
+    #[serde(default)]
+    pub values: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationReply {
+    pub conversation_id: String,
+    pub message: String,
+    pub usage: LLMUsage,
+    /// Turns kept in the history, this one included.
+    pub turns: usize,
+}
+
+pub trait ConversationStore: Send + Sync {
+    fn load(&self, conversation_id: &str) -> Result<Option<Conversation>, AgentError>;
+
+    fn save(&self, conversation: &Conversation) -> Result<(), AgentError>;
+}
+
+/// Default store, losing the conversations when the process stops.
+#[derive(Default)]
+pub struct MemoryConversationStore {
+    conversations: Mutex<HashMap<String, Conversation>>,
+}
+
+impl MemoryConversationStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ConversationStore for MemoryConversationStore {
+    fn load(&self, conversation_id: &str) -> Result<Option<Conversation>, AgentError> {
+        let conversations = self
+            .conversations
+            .lock()
+            .expect("Failed to lock conversations");
+        Ok(conversations.get(conversation_id).cloned())
+    }
+
+    fn save(&self, conversation: &Conversation) -> Result<(), AgentError> {
+        let mut conversations = self
+            .conversations
+            .lock()
+            .expect("Failed to lock conversations");
+        conversations.insert(
+            conversation.conversation_id.to_string(),
+            conversation.clone(),
+        );
+        Ok(())
+    }
+}
+
+/// Stores each conversation as a JSON file named after its id.
+pub struct FileConversationStore {
+    directory: JsonDirectory,
+}
+
+impl FileConversationStore {
+    pub fn new<P: AsRef<Path>>(base_dir: P) -> Self {
+        Self {
+            directory: JsonDirectory::new(base_dir),
+        }
+    }
+}
+
+impl ConversationStore for FileConversationStore {
+    fn load(&self, conversation_id: &str) -> Result<Option<Conversation>, AgentError> {
+        self.directory.load(conversation_id)
+    }
+
+    fn save(&self, conversation: &Conversation) -> Result<(), AgentError> {
+        self.directory
+            .save(&conversation.conversation_id, conversation)
+    }
+}
+
+#[test]
+pub fn test_trim() {
+    let mut conversation = Conversation::new("trim");
+    for turn in 0..4 {
+        conversation
+            .messages
+            .push(LLMMessage::new("user", &format!("question {}", turn)));
+        conversation
+            .messages
+            .push(LLMMessage::new("assistant", &"a".repeat(40)));
+    }
+    conversation.trim(&ConversationBudget {
+        max_turns: Some(3),
+        max_tokens: None,
+    });
+    assert_eq!(conversation.messages.len(), 6);
+    assert_eq!(conversation.messages[0].content, "question 1");
+
+    conversation.trim(&ConversationBudget {
+        max_turns: None,
+        max_tokens: Some(30),
+    });
+    assert_eq!(conversation.messages.len(), 4);
+
+    // The last turn is kept whatever the budget
+    conversation.trim(&ConversationBudget {
+        max_turns: Some(0),
+        max_tokens: Some(1),
+    });
+    assert_eq!(conversation.messages[0].content, "question 3");
+    assert_eq!(conversation.messages.len(), 2);
+}
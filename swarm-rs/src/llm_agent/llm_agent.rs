@@ -1,17 +1,30 @@
-use std::{any::Any, collections::HashMap};
+use std::{
+    any::Any,
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
 
-use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use futures::StreamExt;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
 
 use crate::{
     agent::{Action, Agent, Output},
     prelude::*,
+    utils::time::current_time_iso,
 };
 
 use super::{
+    conversation::{
+        Conversation, ConversationBudget, ConversationReply, ConversationStore, ConversationTurn,
+        MemoryConversationStore,
+    },
     llm_client::LLMClient,
-    llm_message::{LLMMessage, LLMMessageBuilder},
-    llm_response::LLMResponse,
+    llm_error::LLMError,
+    llm_message::{LLMMessage, LLMMessageBuilder, LLMTool, LLMToolCall},
+    llm_output::{repair_prompt, LLMOutputValidator},
+    llm_response::{LLMResponse, LLMUsage},
+    llm_stream::{LLMCompletion, LLMStream, LLMStreamEvent},
 };
 
 #[derive(Serialize, Deserialize)]
@@ -19,6 +32,12 @@ pub struct LLMPrompt {
     values: HashMap<String, String>,
 }
 
+impl Default for LLMPrompt {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl LLMPrompt {
     pub fn new() -> Self {
         Self {
@@ -39,6 +58,44 @@ pub struct LLMAgent {
     goal: String,
     output_rules: String,
     output_format: Option<Value>,
+    /// Swarm action ids the model may call.
+    #[serde(default)]
+    tools: Vec<String>,
+    #[serde(default = "default_max_iterations")]
+    max_iterations: usize,
+    /// Times the model is asked to fix an output not matching `output_format`.
+    #[serde(default = "default_max_repairs")]
+    max_repairs: usize,
+    /// History kept by the `converse` action.
+    #[serde(default)]
+    conversation_budget: ConversationBudget,
+    #[serde(skip, default = "default_conversation_store")]
+    conversations: Arc<dyn ConversationStore>,
+    /// Locks serializing the turns of each conversation.
+    #[serde(skip)]
+    conversation_locks: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+fn default_max_iterations() -> usize {
+    5
+}
+
+fn default_max_repairs() -> usize {
+    2
+}
+
+fn default_conversation_store() -> Arc<dyn ConversationStore> {
+    Arc::new(MemoryConversationStore::new())
+}
+
+#[derive(Serialize)]
+struct ToolCallLog<'a> {
+    agent_id: &'a str,
+    call_id: &'a str,
+    tool: &'a str,
+    action: Option<&'a str>,
+    arguments: &'a str,
+    success: bool,
 }
 
 #[agent]
@@ -47,24 +104,330 @@ impl LLMAgent {
         self.id.to_string()
     }
 
+    /// Completes the prompt, calling the configured tools through the swarm
+    /// until the model gives its final answer.
+    ///
+    /// When `output_format` is set, the answer is validated against it, the model
+    /// being asked to repair it if needed, and the valid output is set on the response.
     #[agent_action]
-    pub async fn execute(&self, prompt: LLMPrompt) -> Result<LLMResponse, String> {
-        let output_format = if let Some(format) = &self.output_format {
-            Some(serde_json::to_string(format).unwrap())
-        } else {
-            None
-        };
+    pub async fn execute(
+        &self,
+        prompt: LLMPrompt,
+        swarm: &Swarm,
+    ) -> Result<LLMResponse, AgentError> {
         let messages = self.build_messages(&prompt);
-        let response = self
-            .client
-            .autocomplete(&messages, output_format.clone())
-            .await;
-        if let Ok(response) = response {
-            Ok(response)
+        if self.output_format.is_none() {
+            return self.complete(messages, swarm).await;
+        }
+        let (mut response, output) = self.complete_output(messages, swarm).await?;
+        response.output = Some(output);
+        Ok(response)
+    }
+
+    /// Completes the prompt into its validated JSON output only.
+    #[agent_action]
+    pub async fn extract(&self, prompt: LLMPrompt, swarm: &Swarm) -> Result<Value, AgentError> {
+        self.execute_as(&prompt, swarm).await
+    }
+
+    /// Typed variant of `extract`, for the Rust callers.
+    pub async fn execute_as<T: DeserializeOwned>(
+        &self,
+        prompt: &LLMPrompt,
+        swarm: &Swarm,
+    ) -> Result<T, AgentError> {
+        let (_, output) = self.complete_output(self.build_messages(prompt), swarm).await?;
+        serde_json::from_value(output).map_err(|e| {
+            AgentError::new(
+                ErrorCode::InvalidOutput,
+                &format!("Unable to decode output : {}", e),
+            )
+        })
+    }
+
+    /// Replies to the user message, continuing the conversation stored under its id.
+    ///
+    /// Conversations belong to the user of the action context, and their turns
+    /// are taken one at a time.
+    #[agent_action]
+    pub async fn converse(
+        &self,
+        turn: ConversationTurn,
+        swarm: &Swarm,
+    ) -> Result<ConversationReply, AgentError> {
+        let lock = self
+            .conversation_locks
+            .lock()
+            .expect("Failed to lock conversations")
+            .entry(turn.conversation_id.to_string())
+            .or_default()
+            .clone();
+        let guard = lock.lock().await;
+        let reply = self.converse_turn(&turn, swarm).await;
+        drop(guard);
+
+        let mut locks = self
+            .conversation_locks
+            .lock()
+            .expect("Failed to lock conversations");
+        if Arc::strong_count(&lock) == 2 {
+            locks.remove(&turn.conversation_id);
+        }
+        reply
+    }
+
+    async fn converse_turn(
+        &self,
+        turn: &ConversationTurn,
+        swarm: &Swarm,
+    ) -> Result<ConversationReply, AgentError> {
+        let context = ActionContext::current().unwrap_or_default();
+        let user_id = context.get_user_id();
+        let mut conversation = match self.conversations.load(&turn.conversation_id)? {
+            Some(conversation) if conversation.user_id.as_deref() != user_id => {
+                return Err(AgentError::new(
+                    ErrorCode::InvalidPayload,
+                    &format!("Conversation {} belongs to another user", turn.conversation_id),
+                ));
+            }
+            Some(conversation) => conversation,
+            None => Conversation::new(&turn.conversation_id).with_user(user_id),
+        };
+        conversation
+            .messages
+            .push(LLMMessage::new("user", &turn.message));
+        conversation.trim(&self.conversation_budget);
+
+        let messages = LLMMessageBuilder::new()
+            .add_system_message(&self.build_system_prompt(&turn.values))
+            .add_messages(&conversation.messages)
+            .build();
+        let response = self.complete(messages, swarm).await?;
+        let reply = response.get_message().unwrap_or_default();
+
+        conversation
+            .messages
+            .push(LLMMessage::new("assistant", &reply));
+        conversation.updated_at = current_time_iso();
+        self.conversations.save(&conversation)?;
+        Ok(ConversationReply {
+            conversation_id: conversation.conversation_id.to_string(),
+            message: reply,
+            usage: response.usage,
+            turns: conversation.messages.len() / 2,
+        })
+    }
+
+    async fn complete(
+        &self,
+        mut messages: Vec<LLMMessage>,
+        swarm: &Swarm,
+    ) -> Result<LLMResponse, AgentError> {
+        let output_format = self
+            .output_format
+            .as_ref()
+            .map(|format| serde_json::to_string(format).unwrap());
+        let tools = self.build_tools(swarm);
+        let context = ActionContext::current().unwrap_or_default();
+        for _ in 0..self.max_iterations {
+            swarm.get_usage_tracker().check_budget(&context)?;
+            let response = self
+                .client
+                .autocomplete_with_tools(&messages, output_format.clone(), &tools)
+                .await?;
+            self.record_usage(&context, &response.usage, swarm);
+
+            let tool_calls = response.get_tool_calls();
+            if tool_calls.is_empty() {
+                return Ok(response);
+            }
+            let mut assistant_message = LLMMessage::new("assistant", "");
+            assistant_message.tool_calls = Some(tool_calls.clone());
+            messages.push(assistant_message);
+            for tool_call in &tool_calls {
+                let result = self.call_tool(tool_call, swarm).await;
+                messages.push(LLMMessage::tool_result(&tool_call.id, &result));
+            }
+        }
+        Err(AgentError::action_failed(&format!(
+            "No final answer after {} iterations",
+            self.max_iterations
+        )))
+    }
+
+    /// Re-prompts the model with the validation errors until its output matches the schema,
+    /// returning the last response and its output.
+    async fn complete_output(
+        &self,
+        mut messages: Vec<LLMMessage>,
+        swarm: &Swarm,
+    ) -> Result<(LLMResponse, Value), AgentError> {
+        let validator = match &self.output_format {
+            Some(output_format) => Some(
+                LLMOutputValidator::new(output_format)
+                    .map_err(|error| AgentError::new(ErrorCode::InvalidPayload, &error))?,
+            ),
+            None => None,
+        };
+        let mut errors = vec![];
+        for attempt in 0..=self.max_repairs {
+            if attempt > 0 {
+                swarm.get_logger().warn(
+                    "OutputRepair",
+                    &json!({ "agent_id": self.id, "attempt": attempt, "errors": errors }),
+                );
+            }
+            let response = self.complete(messages.clone(), swarm).await?;
+            errors = match response.get_output::<Value>() {
+                Ok(output) => match validator.as_ref().map(|validator| validator.validate(&output)) {
+                    Some(Err(errors)) => errors,
+                    _ => return Ok((response, output)),
+                },
+                Err(error) => vec![error],
+            };
+            messages.push(LLMMessage::new(
+                "assistant",
+                &response.get_message().unwrap_or_default(),
+            ));
+            messages.push(LLMMessage::new("user", &repair_prompt(&errors)));
+        }
+        Err(AgentError::new(
+            ErrorCode::InvalidOutput,
+            &format!(
+                "Output still invalid after {} repairs : {}",
+                self.max_repairs,
+                errors.join(", ")
+            ),
+        ))
+    }
+
+    pub fn with_conversation_store<T: ConversationStore + 'static>(mut self, store: T) -> Self {
+        self.conversations = Arc::new(store);
+        self
+    }
+
+    /// Role, goal and output rules as a single system prompt.
+    pub fn build_system_prompt(&self, values: &HashMap<String, String>) -> String {
+        [&self.role, &self.goal, &self.output_rules]
+            .iter()
+            .map(|template| fill_template(template, values))
+            .filter(|text| !text.trim().is_empty())
+            .collect::<Vec<String>>()
+            .join("\n\n")
+    }
+
+    /// Streamed variant of `execute`, yielding the tokens as they are generated.
+    pub async fn stream(&self, prompt: &LLMPrompt) -> Result<LLMStream, LLMError> {
+        let output_format = self
+            .output_format
+            .as_ref()
+            .map(|format| serde_json::to_string(format).unwrap());
+        let messages = self.build_messages(prompt);
+        self.client.stream(&messages, output_format).await
+    }
+
+    /// Streams the completion, emitting each delta as a `token` progress event.
+    #[agent_action]
+    pub async fn execute_streaming(
+        &self,
+        prompt: LLMPrompt,
+        swarm: &Swarm,
+    ) -> Result<LLMCompletion, AgentError> {
+        let context = ActionContext::current().unwrap_or_default();
+        swarm.get_usage_tracker().check_budget(&context)?;
+        let mut stream = self.stream(&prompt).await?;
+        let mut completion = LLMCompletion::default();
+        while let Some(event) = stream.next().await {
+            match event? {
+                LLMStreamEvent::Delta(delta) => {
+                    emit_progress("token", &delta);
+                    completion.content.push_str(&delta);
+                }
+                LLMStreamEvent::Done {
+                    finish_reason,
+                    usage,
+                } => {
+                    completion.finish_reason = finish_reason;
+                    completion.usage = usage;
+                }
+            }
+        }
+        if let Some(usage) = &completion.usage {
+            self.record_usage(&context, usage, swarm);
+        }
+        Ok(completion)
+    }
+
+    fn record_usage(&self, context: &ActionContext, usage: &LLMUsage, swarm: &Swarm) {
+        let record = swarm
+            .get_usage_tracker()
+            .record(context, self.client.get_model(), usage);
+        swarm.get_logger().info("LLMUsage", &record);
+    }
+
+    /// Tools named after the action ids, `searx.search` becoming `searx__search`.
+    pub fn build_tools(&self, swarm: &Swarm) -> Vec<LLMTool> {
+        self.tools
+            .iter()
+            .map(|action_id| {
+                let schema = swarm
+                    .describe_action(action_id)
+                    .and_then(|descriptor| descriptor.input_schema);
+                let (parameters, _) = tool_parameters(schema);
+                LLMTool::function(&tool_name(action_id), action_id, parameters)
+            })
+            .collect()
+    }
+
+    async fn call_tool(&self, tool_call: &LLMToolCall, swarm: &Swarm) -> String {
+        let action_id = self
+            .tools
+            .iter()
+            .find(|action_id| tool_name(action_id) == tool_call.function.name);
+        let result = match action_id {
+            Some(action_id) => {
+                let schema = swarm
+                    .describe_action(action_id)
+                    .and_then(|descriptor| descriptor.input_schema);
+                let (_, wrapped) = tool_parameters(schema);
+                match serde_json::from_str::<Value>(&tool_call.function.arguments) {
+                    Ok(arguments) => {
+                        let payload = if wrapped {
+                            arguments["input"].clone()
+                        } else {
+                            arguments
+                        };
+                        emit_progress(
+                            "tool_call",
+                            json!({ "action": action_id, "payload": payload }),
+                        );
+                        let output = swarm.execute(action_id, &payload).await;
+                        if output.is_success() {
+                            Ok(output.get_value().to_string())
+                        } else {
+                            Err(output.get_error_message())
+                        }
+                    }
+                    Err(error) => Err(format!("Invalid arguments : {}", error)),
+                }
+            }
+            None => Err(format!("Unknown tool {}", tool_call.function.name)),
+        };
+
+        let log = ToolCallLog {
+            agent_id: &self.id,
+            call_id: &tool_call.id,
+            tool: &tool_call.function.name,
+            action: action_id.map(|action_id| action_id.as_str()),
+            arguments: &tool_call.function.arguments,
+            success: result.is_ok(),
+        };
+        if result.is_ok() {
+            swarm.get_logger().info("ToolCall", &log);
         } else {
-            println!("response : {:?}", response);
-            Err("Unable to get summary".to_string())
+            swarm.get_logger().warn("ToolCall", &log);
         }
+        result.unwrap_or_else(|error| json!({ "error": error }).to_string())
     }
 
     pub fn build_messages(&self, prompt: &LLMPrompt) -> Vec<LLMMessage> {
@@ -72,12 +435,11 @@ impl LLMAgent {
         let goal_text = fill_template(&self.goal, &prompt.values);
         let output_rules_text = fill_template(&self.output_rules, &prompt.values);
 
-        let messages = LLMMessageBuilder::new()
+        LLMMessageBuilder::new()
             .add_system_message(&role_text)
             .add_system_message(&goal_text)
             .add_system_message(&output_rules_text)
-            .build();
-        messages
+            .build()
     }
 }
 
@@ -90,3 +452,29 @@ pub fn fill_template(template: &str, values: &HashMap<String, String>) -> String
 
     output
 }
+
+fn tool_name(action_id: &str) -> String {
+    action_id.replace('.', "__")
+}
+
+/// Tool parameters must be an object, other payloads are wrapped in an `input` property.
+fn tool_parameters(schema: Option<Value>) -> (Value, bool) {
+    match schema {
+        Some(schema) if schema["type"] == "object" => (schema, false),
+        Some(mut schema) => {
+            let definitions = schema
+                .as_object_mut()
+                .and_then(|schema| schema.remove("$defs"));
+            let mut parameters = json!({
+                "type": "object",
+                "properties": { "input": schema },
+                "required": ["input"]
+            });
+            if let Some(definitions) = definitions {
+                parameters["$defs"] = definitions;
+            }
+            (parameters, true)
+        }
+        None => (json!({ "type": "object" }), false),
+    }
+}
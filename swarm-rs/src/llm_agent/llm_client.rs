@@ -1,16 +1,24 @@
-use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use reqwest::{header::RETRY_AFTER, Response};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
-use super::{llm_message::LLMMessage, llm_response::LLMResponse};
-
-
+use super::{
+    llm_error::{LLMError, LLMErrorKind},
+    llm_message::{LLMMessage, LLMTool},
+    llm_provider::{LLMProviderKind, LLMRequest},
+    llm_response::LLMResponse,
+    llm_stream::{parse_sse_stream, LLMStream},
+};
 
 #[derive(Serialize, Deserialize)]
 pub struct LLMClient {
     endpoint: String,
     model: String,
     api_key: Option<String>,
+    #[serde(default)]
+    provider: LLMProviderKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
 }
 
 impl LLMClient {
@@ -19,72 +27,145 @@ impl LLMClient {
             endpoint: endpoint.to_string(),
             model: model.to_string(),
             api_key,
+            provider: LLMProviderKind::default(),
+            max_tokens: None,
         }
     }
 
+    pub fn with_provider(mut self, provider: LLMProviderKind) -> Self {
+        self.provider = provider;
+        self
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    pub fn get_model(&self) -> &str {
+        &self.model
+    }
+
+    pub fn get_provider(&self) -> LLMProviderKind {
+        self.provider
+    }
+
     pub async fn autocomplete(
         &self,
-        messages: &Vec<LLMMessage>,
+        messages: &[LLMMessage],
         output_format: Option<String>,
-    ) -> Result<LLMResponse, String> {
-        let url = &self.endpoint;
+    ) -> Result<LLMResponse, LLMError> {
+        self.autocomplete_with_tools(messages, output_format, &[])
+            .await
+    }
 
-        let mut headers = HeaderMap::new();
-        if let Some(auth_key) = &self.api_key {
-            let header_value = HeaderValue::from_str(&format!("Bearer {}", auth_key))
-                .expect("Malformed header value");
-            headers.insert(AUTHORIZATION, header_value);
-        }
+    /// Completion where the model may answer with calls to the given tools.
+    pub async fn autocomplete_with_tools(
+        &self,
+        messages: &[LLMMessage],
+        output_format: Option<String>,
+        tools: &[LLMTool],
+    ) -> Result<LLMResponse, LLMError> {
+        let provider = self.provider.provider();
+        let request = self.request(messages, output_format, tools)?;
+        let json_body = provider.request_body(&request);
 
-        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        let client = reqwest::Client::new();
+        let response = client
+            .post(&self.endpoint)
+            .headers(provider.headers(self.api_key.as_deref())?)
+            .json(&json_body)
+            .send()
+            .await
+            .map_err(LLMError::transport)?;
+        let response = Self::check_status(response).await?;
+        let body = response.text().await.map_err(LLMError::transport)?;
 
-        let json_body = match output_format {
-            Some(output_format) => {
-                let format_value: Value = serde_json::from_str(&output_format).unwrap();
-                // println!("format : {:?}", format_value);
-                json!({
-                    "model": self.model,
-                    "messages": messages,
-                    "response_format" : {
-                        "type": "json_schema",
-                        "json_schema": format_value
-                    }
-                })
-            }
-            _ => json!({
-                "model": self.model,
-                "messages": messages
-            }),
-        };
+        let value = serde_json::from_str::<Value>(&body)
+            .map_err(|e| LLMError::decode(&format!("Invalid JSON body : {}", e), &body))?;
+        // Some servers answer errors with a success status
+        if !value["error"].is_null() {
+            return Err(LLMError::from_response(None, &body));
+        }
+        let mut response = provider
+            .parse_response(&request, value)
+            .map_err(|message| LLMError::decode(&message, &body))?;
+        response.raw = Some(body);
+        Ok(response)
+    }
 
-        // println!("Query: {}", json_body);
+    /// Same request as [`LLMClient::autocomplete`] with `"stream": true`, returning
+    /// the token deltas as they are generated, then the finish reason and usage.
+    /// Only available with OpenAI compatible endpoints.
+    pub async fn stream(
+        &self,
+        messages: &[LLMMessage],
+        output_format: Option<String>,
+    ) -> Result<LLMStream, LLMError> {
+        if self.provider != LLMProviderKind::OpenAI {
+            return Err(LLMError::new(
+                LLMErrorKind::InvalidRequest,
+                &format!(
+                    "Streaming is not supported by the {:?} provider",
+                    self.provider
+                ),
+            ));
+        }
+        let provider = self.provider.provider();
+        let request = self.request(messages, output_format, &[])?;
+        let mut json_body = provider.request_body(&request);
+        json_body["stream"] = json!(true);
+        json_body["stream_options"] = json!({ "include_usage": true });
 
         let client = reqwest::Client::new();
         let response = client
-            .post(url)
-            .headers(headers)
+            .post(&self.endpoint)
+            .headers(provider.headers(self.api_key.as_deref())?)
             .json(&json_body)
             .send()
-            .await;
+            .await
+            .map_err(LLMError::transport)?;
+        let response = Self::check_status(response).await?;
+        Ok(parse_sse_stream(response.bytes_stream()))
+    }
 
-        match response {
-            Ok(response) => {
-                let body = response.json::<Value>().await;
-                match body {
-                    Ok(value) => {
-                        // println!("LLM Response : {}", serde_json::to_string(&value).unwrap());
-                        
-                        let resp = serde_json::from_value::<LLMResponse>(value.clone());
-                        if let Ok(resp) = resp {
-                            Ok(resp)
-                        } else {
-                            Err("Unable to decode response".to_string())
-                        }
-                    }
-                    Err(e) => Err(e.to_string()),
-                }
-            }
-            Err(e) => Err(e.to_string()),
+    async fn check_status(response: Response) -> Result<Response, LLMError> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
         }
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok());
+        let body = response.text().await.unwrap_or_default();
+        Err(LLMError::from_response(Some(status.as_u16()), &body).with_retry_after(retry_after))
+    }
+
+    fn request(
+        &self,
+        messages: &[LLMMessage],
+        output_format: Option<String>,
+        tools: &[LLMTool],
+    ) -> Result<LLMRequest, LLMError> {
+        let output_format = match output_format {
+            Some(output_format) => {
+                Some(serde_json::from_str::<Value>(&output_format).map_err(|e| {
+                    LLMError::new(
+                        LLMErrorKind::InvalidRequest,
+                        &format!("Invalid output format : {}", e),
+                    )
+                })?)
+            }
+            None => None,
+        };
+        Ok(LLMRequest {
+            model: self.model.clone(),
+            messages: messages.to_vec(),
+            tools: tools.to_vec(),
+            output_format,
+            max_tokens: self.max_tokens,
+        })
     }
 }
@@ -0,0 +1,200 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::error::{AgentError, ErrorCode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LLMErrorKind {
+    /// The request could not be built from the client configuration.
+    InvalidRequest,
+    /// The request could not be sent or its response not read.
+    Transport,
+    Auth,
+    RateLimit,
+    ContextLength,
+    /// Any other error reported by the provider.
+    Provider,
+    /// The response is not a completion in the expected format.
+    Decode,
+}
+
+impl LLMErrorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LLMErrorKind::InvalidRequest => "invalid_request",
+            LLMErrorKind::Transport => "transport",
+            LLMErrorKind::Auth => "auth",
+            LLMErrorKind::RateLimit => "rate_limit",
+            LLMErrorKind::ContextLength => "context_length",
+            LLMErrorKind::Provider => "provider",
+            LLMErrorKind::Decode => "decode",
+        }
+    }
+}
+
+/// Error of an [`LLMClient`](super::llm_client::LLMClient) call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LLMError {
+    pub kind: LLMErrorKind,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// Seconds to wait before retrying, from the `retry-after` header.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after: Option<u64>,
+    /// Raw response body.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+}
+
+impl LLMError {
+    pub fn new(kind: LLMErrorKind, message: &str) -> Self {
+        Self {
+            kind,
+            message: message.to_string(),
+            status: None,
+            retry_after: None,
+            body: None,
+        }
+    }
+
+    pub fn transport<E: ToString>(error: E) -> Self {
+        Self::new(LLMErrorKind::Transport, &error.to_string())
+    }
+
+    pub fn decode(message: &str, body: &str) -> Self {
+        Self::new(LLMErrorKind::Decode, message).with_body(body)
+    }
+
+    /// Error reported by the provider, either with an error status or an `error` field.
+    pub fn from_response(status: Option<u16>, body: &str) -> Self {
+        let value = serde_json::from_str::<Value>(body).unwrap_or(Value::Null);
+        let error = &value["error"];
+        let message = error["message"]
+            .as_str()
+            .or(error.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| match body.trim() {
+                "" => status
+                    .map(|status| format!("HTTP status {}", status))
+                    .unwrap_or_default(),
+                text => text.to_string(),
+            });
+        // OpenAI sets `code` and `type`, Anthropic `type`
+        let code = [&error["code"], &error["type"]]
+            .iter()
+            .filter_map(|code| code.as_str())
+            .collect::<Vec<&str>>()
+            .join(" ")
+            .to_lowercase();
+
+        let mut llm_error = Self::new(Self::classify(status, &code, &message), &message);
+        llm_error.status = status;
+        llm_error.with_body(body)
+    }
+
+    fn classify(status: Option<u16>, code: &str, message: &str) -> LLMErrorKind {
+        let message = message.to_lowercase();
+        if matches!(status, Some(401) | Some(403))
+            || ["auth", "api_key", "permission"]
+                .iter()
+                .any(|pattern| code.contains(pattern))
+        {
+            LLMErrorKind::Auth
+        } else if status == Some(429) || code.contains("rate_limit") {
+            LLMErrorKind::RateLimit
+        } else if code.contains("context_length")
+            || [
+                "context length",
+                "context window",
+                "maximum context",
+                "prompt is too long",
+                "too many tokens",
+            ]
+            .iter()
+            .any(|pattern| message.contains(pattern))
+        {
+            LLMErrorKind::ContextLength
+        } else {
+            LLMErrorKind::Provider
+        }
+    }
+
+    pub fn with_body(mut self, body: &str) -> Self {
+        self.body = Some(body.to_string());
+        self
+    }
+
+    pub fn with_retry_after(mut self, retry_after: Option<u64>) -> Self {
+        self.retry_after = retry_after;
+        self
+    }
+
+    /// Rate limits, transport failures and server errors may succeed later.
+    pub fn is_retryable(&self) -> bool {
+        match self.kind {
+            LLMErrorKind::RateLimit | LLMErrorKind::Transport => true,
+            LLMErrorKind::Provider => self.status.is_some_and(|status| status >= 500),
+            _ => false,
+        }
+    }
+}
+
+impl Display for LLMError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LLM {} error", self.kind.as_str())?;
+        if let Some(status) = self.status {
+            write!(f, " ({})", status)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for LLMError {}
+
+/// Rate limits get their own code, and every error keeps its kind, status and
+/// `retry_after` in the details.
+impl From<LLMError> for AgentError {
+    fn from(error: LLMError) -> Self {
+        let code = match error.kind {
+            LLMErrorKind::RateLimit => ErrorCode::RateLimited,
+            LLMErrorKind::Decode => ErrorCode::InvalidOutput,
+            _ => ErrorCode::ActionFailed,
+        };
+        AgentError::new(code, &error.to_string())
+            .with_retryable(error.is_retryable())
+            .with_details(json!({
+                "kind": error.kind,
+                "status": error.status,
+                "retry_after": error.retry_after
+            }))
+    }
+}
+
+#[test]
+pub fn test_error_classification() {
+    let error = LLMError::from_response(
+        Some(400),
+        r#"{"error":{"message":"This model's maximum context length is 8192 tokens","code":"context_length_exceeded"}}"#,
+    );
+    assert_eq!(error.kind, LLMErrorKind::ContextLength);
+    assert!(!error.is_retryable());
+
+    let error = LLMError::from_response(
+        Some(429),
+        r#"{"type":"error","error":{"type":"rate_limit_error","message":"Slow down"}}"#,
+    );
+    assert_eq!(error.kind, LLMErrorKind::RateLimit);
+    assert_eq!(error.to_string(), "LLM rate_limit error (429): Slow down");
+
+    let error = LLMError::from_response(None, r#"{"error":"model 'llama' not found"}"#);
+    assert_eq!(error.kind, LLMErrorKind::Provider);
+    assert_eq!(error.message, "model 'llama' not found");
+
+    let error = LLMError::from_response(Some(502), "Bad Gateway");
+    assert_eq!(error.message, "Bad Gateway");
+    assert!(error.is_retryable());
+}
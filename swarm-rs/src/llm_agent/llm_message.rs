@@ -1,37 +1,127 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LLMMessage {
     pub role: String,
+    /// Empty for the assistant messages only made of tool calls.
+    #[serde(default, deserialize_with = "null_as_empty")]
     pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<LLMToolCall>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+impl LLMMessage {
+    pub fn new(role: &str, content: &str) -> Self {
+        Self {
+            role: role.to_string(),
+            content: content.to_string(),
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
+
+    /// Result of a tool call, sent back to the model.
+    pub fn tool_result(tool_call_id: &str, content: &str) -> Self {
+        let mut message = Self::new("tool", content);
+        message.tool_call_id = Some(tool_call_id.to_string());
+        message
+    }
+}
+
+pub(crate) fn null_as_empty<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Function the model may call, described by the JSON Schema of its arguments.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LLMTool {
+    #[serde(rename = "type")]
+    pub tool_type: String,
+    pub function: LLMFunction,
+}
+
+impl LLMTool {
+    pub fn function(name: &str, description: &str, parameters: Value) -> Self {
+        Self {
+            tool_type: "function".to_string(),
+            function: LLMFunction {
+                name: name.to_string(),
+                description: description.to_string(),
+                parameters,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LLMFunction {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub parameters: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LLMToolCall {
+    pub id: String,
+    #[serde(rename = "type", default = "default_tool_type")]
+    pub tool_type: String,
+    pub function: LLMFunctionCall,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LLMFunctionCall {
+    pub name: String,
+    /// JSON encoded arguments.
+    #[serde(default)]
+    pub arguments: String,
+}
+
+fn default_tool_type() -> String {
+    "function".to_string()
 }
 
 pub struct LLMMessageBuilder {
     messages: Vec<LLMMessage>,
 }
 
+impl Default for LLMMessageBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl LLMMessageBuilder {
     pub fn new() -> Self {
         Self { messages: vec![] }
     }
     pub fn add_system_message(mut self, message: &str) -> Self {
-        let llm_message = LLMMessage {
-            role: "system".to_string(),
-            content: message.to_string(),
-        };
+        let llm_message = LLMMessage::new("system", message);
         self.messages.push(llm_message);
         self
     }
 
     pub fn add_user_message(mut self, message: &str) -> Self {
-        let llm_message = LLMMessage {
-            role: "user".to_string(),
-            content: message.to_string(),
-        };
+        let llm_message = LLMMessage::new("user", message);
         self.messages.push(llm_message);
         self
     }
 
+    pub fn add_assistant_message(mut self, message: &str) -> Self {
+        let llm_message = LLMMessage::new("assistant", message);
+        self.messages.push(llm_message);
+        self
+    }
+
+    pub fn add_messages(mut self, messages: &[LLMMessage]) -> Self {
+        self.messages.extend_from_slice(messages);
+        self
+    }
+
     pub fn build(self) -> Vec<LLMMessage> {
         self.messages
     }
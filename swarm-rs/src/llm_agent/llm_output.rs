@@ -0,0 +1,73 @@
+use jsonschema::Validator;
+use serde_json::Value;
+
+use super::llm_provider::schema_of;
+
+/// Checks the model outputs against the JSON Schema of an `output_format`.
+pub struct LLMOutputValidator {
+    validator: Validator,
+}
+
+impl LLMOutputValidator {
+    pub fn new(output_format: &Value) -> Result<Self, String> {
+        let validator = jsonschema::validator_for(&schema_of(output_format))
+            .map_err(|e| format!("Invalid output schema : {}", e))?;
+        Ok(Self { validator })
+    }
+
+    /// Validation errors of the output, each one located by the path of the invalid value.
+    pub fn validate(&self, output: &Value) -> Result<(), Vec<String>> {
+        let errors: Vec<String> = self
+            .validator
+            .iter_errors(output)
+            .map(|error| match error.instance_path().as_str() {
+                "" => error.to_string(),
+                path => format!("{} : {}", path, error),
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// User message asking the model to fix its previous answer.
+pub fn repair_prompt(errors: &[String]) -> String {
+    let errors = errors
+        .iter()
+        .map(|error| format!("- {}", error))
+        .collect::<Vec<String>>()
+        .join("\n");
+    format!(
+        "Your previous answer is not valid :\n{}\nAnswer again with only the corrected JSON output.",
+        errors
+    )
+}
+
+#[test]
+pub fn test_output_validation() {
+    let validator = LLMOutputValidator::new(&serde_json::json!({
+        "name": "summary_schema",
+        "schema": {
+            "type": "object",
+            "properties": {
+                "summary": { "type": "string" },
+                "keywords": { "type": "array", "items": { "type": "string" } }
+            },
+            "required": ["summary", "keywords"]
+        }
+    }))
+    .unwrap();
+
+    assert!(validator
+        .validate(&serde_json::json!({ "summary": "Agentic AI", "keywords": ["ai"] }))
+        .is_ok());
+    let errors = validator
+        .validate(&serde_json::json!({ "keywords": ["ai", 1] }))
+        .unwrap_err();
+    assert_eq!(errors.len(), 2);
+    assert!(errors.iter().any(|error| error.contains("\"summary\" is a required property")));
+    assert!(errors.iter().any(|error| error.starts_with("/keywords/1 : ")));
+}
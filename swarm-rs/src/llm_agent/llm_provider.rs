@@ -0,0 +1,436 @@
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use super::{
+    llm_error::{LLMError, LLMErrorKind},
+    llm_message::{LLMFunctionCall, LLMMessage, LLMTool, LLMToolCall},
+    llm_response::{LLMChoice, LLMResponse, LLMUsage},
+};
+
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+const ANTHROPIC_MAX_TOKENS: u32 = 4096;
+
+/// Provider independent completion request.
+#[derive(Debug, Clone)]
+pub struct LLMRequest {
+    pub model: String,
+    pub messages: Vec<LLMMessage>,
+    pub tools: Vec<LLMTool>,
+    /// OpenAI style `json_schema` object: `{"name", "strict", "schema"}`.
+    pub output_format: Option<Value>,
+    pub max_tokens: Option<u32>,
+}
+
+/// Maps the common message/response model to and from a provider's chat API.
+pub trait LLMProvider: Send + Sync {
+    fn headers(&self, api_key: Option<&str>) -> Result<HeaderMap, LLMError>;
+
+    fn request_body(&self, request: &LLMRequest) -> Value;
+
+    fn parse_response(&self, request: &LLMRequest, body: Value) -> Result<LLMResponse, String>;
+}
+
+/// Provider selected by the `provider` field of the client configuration.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LLMProviderKind {
+    #[default]
+    #[serde(rename = "openai")]
+    OpenAI,
+    Anthropic,
+    Ollama,
+}
+
+impl LLMProviderKind {
+    pub fn provider(&self) -> &'static dyn LLMProvider {
+        match self {
+            LLMProviderKind::OpenAI => &OpenAIProvider,
+            LLMProviderKind::Anthropic => &AnthropicProvider,
+            LLMProviderKind::Ollama => &OllamaProvider,
+        }
+    }
+}
+
+fn json_headers() -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    headers
+}
+
+/// Header value holding the API key, which is left out of the error.
+fn api_key_value(value: &str) -> Result<HeaderValue, LLMError> {
+    HeaderValue::from_str(value).map_err(|_| {
+        LLMError::new(
+            LLMErrorKind::InvalidRequest,
+            "Malformed API key, not a valid header value",
+        )
+    })
+}
+
+fn bearer_headers(api_key: Option<&str>) -> Result<HeaderMap, LLMError> {
+    let mut headers = json_headers();
+    if let Some(api_key) = api_key {
+        headers.insert(AUTHORIZATION, api_key_value(&format!("Bearer {}", api_key))?);
+    }
+    Ok(headers)
+}
+
+pub(crate) fn schema_of(output_format: &Value) -> Value {
+    output_format.get("schema").cloned().unwrap_or(json!({}))
+}
+
+/// OpenAI `/v1/chat/completions` and compatible servers.
+pub struct OpenAIProvider;
+
+impl LLMProvider for OpenAIProvider {
+    fn headers(&self, api_key: Option<&str>) -> Result<HeaderMap, LLMError> {
+        bearer_headers(api_key)
+    }
+
+    fn request_body(&self, request: &LLMRequest) -> Value {
+        let mut body = json!({
+            "model": request.model,
+            "messages": request.messages,
+        });
+        if let Some(output_format) = &request.output_format {
+            body["response_format"] = json!({
+                "type": "json_schema",
+                "json_schema": output_format
+            });
+        }
+        if !request.tools.is_empty() {
+            body["tools"] = json!(request.tools);
+        }
+        if let Some(max_tokens) = request.max_tokens {
+            body["max_tokens"] = json!(max_tokens);
+        }
+        body
+    }
+
+    fn parse_response(&self, _request: &LLMRequest, body: Value) -> Result<LLMResponse, String> {
+        serde_json::from_value::<LLMResponse>(body)
+            .map_err(|e| format!("Unable to decode response : {}", e))
+    }
+}
+
+/// Anthropic Messages API (`/v1/messages`).
+///
+/// System messages go to the top level `system` field and structured output is
+/// obtained by forcing a tool whose input schema is the expected output.
+pub struct AnthropicProvider;
+
+impl AnthropicProvider {
+    fn output_tool_name(request: &LLMRequest) -> Option<String> {
+        request.output_format.as_ref().map(|format| {
+            format
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or("output")
+                .to_string()
+        })
+    }
+
+    fn content_blocks(message: &LLMMessage) -> Vec<Value> {
+        let mut blocks = vec![];
+        if !message.content.is_empty() {
+            blocks.push(json!({ "type": "text", "text": message.content }));
+        }
+        for tool_call in message.tool_calls.iter().flatten() {
+            let input =
+                serde_json::from_str::<Value>(&tool_call.function.arguments).unwrap_or(json!({}));
+            blocks.push(json!({
+                "type": "tool_use",
+                "id": tool_call.id,
+                "name": tool_call.function.name,
+                "input": input
+            }));
+        }
+        blocks
+    }
+
+    fn messages(messages: &[LLMMessage]) -> (Vec<String>, Vec<Value>) {
+        let mut system = vec![];
+        let mut converted: Vec<Value> = vec![];
+        for message in messages {
+            let (role, blocks) = match message.role.as_str() {
+                "system" => {
+                    system.push(message.content.clone());
+                    continue;
+                }
+                "tool" => (
+                    "user",
+                    vec![json!({
+                        "type": "tool_result",
+                        "tool_use_id": message.tool_call_id,
+                        "content": message.content
+                    })],
+                ),
+                "assistant" => ("assistant", Self::content_blocks(message)),
+                _ => ("user", Self::content_blocks(message)),
+            };
+            // Consecutive messages of a same role are merged, the API expects them to alternate.
+            match converted.last_mut() {
+                Some(last) if last["role"] == role => {
+                    if let Some(content) = last["content"].as_array_mut() {
+                        content.extend(blocks);
+                    }
+                }
+                _ => converted.push(json!({ "role": role, "content": blocks })),
+            }
+        }
+        // The conversation must start with a user message, prompts only made of system
+        // messages keep the first one as system prompt and send the others as the user turn.
+        let starts_with_user = converted
+            .first()
+            .is_some_and(|first| first["role"] == "user");
+        if !starts_with_user && !system.is_empty() {
+            let user = if system.len() > 1 {
+                system.split_off(1)
+            } else {
+                std::mem::take(&mut system)
+            };
+            converted.insert(
+                0,
+                json!({
+                    "role": "user",
+                    "content": [{ "type": "text", "text": user.join("\n\n") }]
+                }),
+            );
+        }
+        (system, converted)
+    }
+}
+
+impl LLMProvider for AnthropicProvider {
+    fn headers(&self, api_key: Option<&str>) -> Result<HeaderMap, LLMError> {
+        let mut headers = json_headers();
+        if let Some(api_key) = api_key {
+            headers.insert(HeaderName::from_static("x-api-key"), api_key_value(api_key)?);
+        }
+        headers.insert(
+            HeaderName::from_static("anthropic-version"),
+            HeaderValue::from_static(ANTHROPIC_VERSION),
+        );
+        Ok(headers)
+    }
+
+    fn request_body(&self, request: &LLMRequest) -> Value {
+        let (system, messages) = Self::messages(&request.messages);
+        let mut body = json!({
+            "model": request.model,
+            "max_tokens": request.max_tokens.unwrap_or(ANTHROPIC_MAX_TOKENS),
+            "messages": messages,
+        });
+        if !system.is_empty() {
+            body["system"] = json!(system.join("\n\n"));
+        }
+
+        let mut tools: Vec<Value> = request
+            .tools
+            .iter()
+            .map(|tool| {
+                json!({
+                    "name": tool.function.name,
+                    "description": tool.function.description,
+                    "input_schema": tool.function.parameters
+                })
+            })
+            .collect();
+        if let (Some(output_format), Some(name)) =
+            (&request.output_format, Self::output_tool_name(request))
+        {
+            tools.push(json!({
+                "name": name,
+                "description": "Respond with the final answer.",
+                "input_schema": schema_of(output_format)
+            }));
+            if request.tools.is_empty() {
+                body["tool_choice"] = json!({ "type": "tool", "name": name });
+            }
+        }
+        if !tools.is_empty() {
+            body["tools"] = json!(tools);
+        }
+        body
+    }
+
+    fn parse_response(&self, request: &LLMRequest, body: Value) -> Result<LLMResponse, String> {
+        let blocks = body
+            .get("content")
+            .and_then(Value::as_array)
+            .ok_or("Unable to decode response")?;
+        let output_tool = Self::output_tool_name(request);
+
+        let mut content = String::new();
+        let mut tool_calls = vec![];
+        let mut structured = None;
+        for block in blocks {
+            match block["type"].as_str() {
+                Some("text") => content.push_str(block["text"].as_str().unwrap_or_default()),
+                Some("tool_use") => {
+                    let name = block["name"].as_str().unwrap_or_default().to_string();
+                    if Some(&name) == output_tool.as_ref() {
+                        structured = Some(block["input"].to_string());
+                    } else {
+                        tool_calls.push(LLMToolCall {
+                            id: block["id"].as_str().unwrap_or_default().to_string(),
+                            tool_type: "function".to_string(),
+                            function: LLMFunctionCall {
+                                name,
+                                arguments: block["input"].to_string(),
+                            },
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let finish_reason = if !tool_calls.is_empty() {
+            "tool_calls"
+        } else {
+            match body["stop_reason"].as_str() {
+                Some("max_tokens") => "length",
+                _ => "stop",
+            }
+        };
+        let mut message = LLMMessage::new("assistant", &structured.unwrap_or(content));
+        if !tool_calls.is_empty() {
+            message.tool_calls = Some(tool_calls);
+        }
+
+        let prompt_tokens = body["usage"]["input_tokens"].as_u64().unwrap_or(0) as usize;
+        let completion_tokens = body["usage"]["output_tokens"].as_u64().unwrap_or(0) as usize;
+        Ok(LLMResponse {
+            created: 0,
+            choices: vec![LLMChoice {
+                finish_reason: finish_reason.to_string(),
+                index: 0,
+                message,
+            }],
+            model: body["model"].as_str().unwrap_or(&request.model).to_string(),
+            usage: LLMUsage {
+                completion_tokens,
+                prompt_tokens,
+                total_tokens: prompt_tokens + completion_tokens,
+            },
+            system_fingerprint: String::new(),
+            object: "chat.completion".to_string(),
+            output: None,
+            raw: None,
+        })
+    }
+}
+
+/// Ollama native chat API (`/api/chat`), the output schema is sent as `format`.
+pub struct OllamaProvider;
+
+impl LLMProvider for OllamaProvider {
+    fn headers(&self, api_key: Option<&str>) -> Result<HeaderMap, LLMError> {
+        bearer_headers(api_key)
+    }
+
+    fn request_body(&self, request: &LLMRequest) -> Value {
+        let messages: Vec<Value> = request
+            .messages
+            .iter()
+            .map(|message| {
+                let mut converted = Map::new();
+                converted.insert("role".to_string(), json!(message.role));
+                converted.insert("content".to_string(), json!(message.content));
+                if let Some(tool_calls) = &message.tool_calls {
+                    let tool_calls: Vec<Value> = tool_calls
+                        .iter()
+                        .map(|tool_call| {
+                            let arguments =
+                                serde_json::from_str::<Value>(&tool_call.function.arguments)
+                                    .unwrap_or(json!({}));
+                            json!({
+                                "function": {
+                                    "name": tool_call.function.name,
+                                    "arguments": arguments
+                                }
+                            })
+                        })
+                        .collect();
+                    converted.insert("tool_calls".to_string(), json!(tool_calls));
+                }
+                Value::Object(converted)
+            })
+            .collect();
+
+        let mut body = json!({
+            "model": request.model,
+            "messages": messages,
+            "stream": false,
+        });
+        if let Some(output_format) = &request.output_format {
+            body["format"] = schema_of(output_format);
+        }
+        if !request.tools.is_empty() {
+            body["tools"] = json!(request.tools);
+        }
+        if let Some(max_tokens) = request.max_tokens {
+            body["options"] = json!({ "num_predict": max_tokens });
+        }
+        body
+    }
+
+    fn parse_response(&self, request: &LLMRequest, body: Value) -> Result<LLMResponse, String> {
+        let message = body.get("message").ok_or("Unable to decode response")?;
+        let tool_calls: Vec<LLMToolCall> = message["tool_calls"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .map(|(index, tool_call)| LLMToolCall {
+                // Ollama does not identify tool calls, results are matched by position.
+                id: tool_call["id"]
+                    .as_str()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("call_{}", index)),
+                tool_type: "function".to_string(),
+                function: LLMFunctionCall {
+                    name: tool_call["function"]["name"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                    arguments: tool_call["function"]["arguments"].to_string(),
+                },
+            })
+            .collect();
+
+        let finish_reason = if !tool_calls.is_empty() {
+            "tool_calls".to_string()
+        } else {
+            body["done_reason"].as_str().unwrap_or("stop").to_string()
+        };
+        let mut llm_message =
+            LLMMessage::new("assistant", message["content"].as_str().unwrap_or_default());
+        if !tool_calls.is_empty() {
+            llm_message.tool_calls = Some(tool_calls);
+        }
+
+        let prompt_tokens = body["prompt_eval_count"].as_u64().unwrap_or(0) as usize;
+        let completion_tokens = body["eval_count"].as_u64().unwrap_or(0) as usize;
+        Ok(LLMResponse {
+            created: 0,
+            choices: vec![LLMChoice {
+                finish_reason,
+                index: 0,
+                message: llm_message,
+            }],
+            model: body["model"].as_str().unwrap_or(&request.model).to_string(),
+            usage: LLMUsage {
+                completion_tokens,
+                prompt_tokens,
+                total_tokens: prompt_tokens + completion_tokens,
+            },
+            system_fingerprint: String::new(),
+            object: "chat.completion".to_string(),
+            output: None,
+            raw: None,
+        })
+    }
+}
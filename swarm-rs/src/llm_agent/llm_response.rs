@@ -1,38 +1,71 @@
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
 
-use super::llm_message::LLMMessage;
+use super::llm_message::{null_as_empty, LLMMessage, LLMToolCall};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LLMResponse {
+    #[serde(default)]
     pub created: u32,
     pub choices: Vec<LLMChoice>,
+    #[serde(default)]
     pub model: String,
+    #[serde(default)]
     pub usage: LLMUsage,
+    #[serde(default)]
     pub system_fingerprint: String,
+    #[serde(default)]
     pub object: String,
+    /// Output validated against the `output_format` of the agent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<Value>,
+    /// Body received from the provider, not serialized.
+    #[serde(skip)]
+    pub raw: Option<String>,
 }
 
 impl LLMResponse {
     pub fn get_message(&self) -> Option<String> {
-        if let Some(first_choice) = self.choices.first() {
-            Some(first_choice.message.content.to_string())
-        } else {
-            None
-        }
+        self.choices.first().map(|first_choice| first_choice.message.content.to_string())
     }
 
-    pub fn get_output<T: DeserializeOwned>(& self) -> Option<T> {
-        if let Some(output_str) = self.get_message() {
-            let queries = serde_json::from_str::<T>(&output_str);
-            if let Ok(queries) = queries {
-                return Some(queries);
-            }
+    /// Tool calls requested by the model instead of a final answer.
+    pub fn get_tool_calls(&self) -> Vec<LLMToolCall> {
+        self.choices
+            .first()
+            .and_then(|first_choice| first_choice.message.tool_calls.clone())
+            .unwrap_or_default()
+    }
+
+    /// Validated output, or else the message parsed as JSON, the error telling why it could not be.
+    pub fn get_output<T: DeserializeOwned>(&self) -> Result<T, String> {
+        if let Some(output) = &self.output {
+            return serde_json::from_value(output.clone())
+                .map_err(|e| format!("Invalid output : {}", e));
         }
-        None
+        let choice = self.choices.first().ok_or("Response has no choice")?;
+        let content = choice.message.content.trim();
+        if content.is_empty() {
+            return Err(match choice.message.tool_calls {
+                Some(_) => "Response only contains tool calls".to_string(),
+                None => format!(
+                    "Response message is empty (finish reason : {})",
+                    choice.finish_reason
+                ),
+            });
+        }
+        serde_json::from_str::<T>(content).map_err(|e| {
+            let mut error = format!("Invalid JSON output : {}", e);
+            if choice.finish_reason == "length" {
+                error.push_str(", the output was truncated by the token limit");
+            }
+            error
+        })
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct LLMUsage {
     pub completion_tokens: usize,
     pub prompt_tokens: usize,
@@ -41,7 +74,9 @@ pub struct LLMUsage {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LLMChoice {
+    #[serde(default, deserialize_with = "null_as_empty")]
     pub finish_reason: String,
+    #[serde(default)]
     pub index: usize,
     pub message: LLMMessage,
 }
\ No newline at end of file
@@ -0,0 +1,171 @@
+use std::{collections::VecDeque, pin::Pin};
+
+use futures::{stream, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{llm_error::LLMError, llm_response::LLMUsage};
+
+/// Chunk of a streamed completion.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LLMStreamEvent {
+    /// Text generated since the previous delta.
+    Delta(String),
+    /// Last event of the stream.
+    Done {
+        finish_reason: Option<String>,
+        usage: Option<LLMUsage>,
+    },
+}
+
+/// Streamed completion once all its deltas are received.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LLMCompletion {
+    pub content: String,
+    pub finish_reason: Option<String>,
+    pub usage: Option<LLMUsage>,
+}
+
+pub type LLMStream = Pin<Box<dyn Stream<Item = Result<LLMStreamEvent, LLMError>> + Send>>;
+
+/// Splits a Server-Sent Events body into the payloads of its `data:` fields.
+#[derive(Default)]
+pub struct SseParser {
+    /// Raw bytes, only decoded once their event is complete.
+    buffer: Vec<u8>,
+}
+
+impl SseParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds received bytes and returns the data of the events completed by them.
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buffer
+            .extend(bytes.iter().filter(|byte| **byte != b'\r'));
+        let mut events = vec![];
+        while let Some(end) = self.buffer.windows(2).position(|bytes| bytes == b"\n\n") {
+            let event: Vec<u8> = self.buffer.drain(..end + 2).collect();
+            let event = String::from_utf8_lossy(&event);
+            let data: Vec<&str> = event
+                .lines()
+                .filter_map(|line| line.strip_prefix("data:"))
+                .map(|data| data.strip_prefix(' ').unwrap_or(data))
+                .collect();
+            if !data.is_empty() {
+                events.push(data.join("\n"));
+            }
+        }
+        events
+    }
+}
+
+struct StreamState<S> {
+    bytes: S,
+    parser: SseParser,
+    events: VecDeque<Result<LLMStreamEvent, LLMError>>,
+    finish_reason: Option<String>,
+    usage: Option<LLMUsage>,
+    finished: bool,
+}
+
+impl<S> StreamState<S> {
+    fn read_chunk(&mut self, data: &str) {
+        if data.trim() == "[DONE]" {
+            self.finish();
+            return;
+        }
+        let chunk = match serde_json::from_str::<Value>(data) {
+            Ok(chunk) => chunk,
+            Err(_) => {
+                self.events
+                    .push_back(Err(LLMError::decode("Malformed stream chunk", data)));
+                return;
+            }
+        };
+        if chunk.get("error").is_some() {
+            self.events
+                .push_back(Err(LLMError::from_response(None, data)));
+            return;
+        }
+        let choice = &chunk["choices"][0];
+        if let Some(content) = choice["delta"]["content"].as_str() {
+            if !content.is_empty() {
+                self.events
+                    .push_back(Ok(LLMStreamEvent::Delta(content.to_string())));
+            }
+        }
+        if let Some(finish_reason) = choice["finish_reason"].as_str() {
+            self.finish_reason = Some(finish_reason.to_string());
+        }
+        if let Ok(usage) = serde_json::from_value::<LLMUsage>(chunk["usage"].clone()) {
+            self.usage = Some(usage);
+        }
+    }
+
+    fn finish(&mut self) {
+        if !self.finished {
+            self.finished = true;
+            self.events.push_back(Ok(LLMStreamEvent::Done {
+                finish_reason: self.finish_reason.take(),
+                usage: self.usage.take(),
+            }));
+        }
+    }
+}
+
+/// Turns the body of an OpenAI style streamed completion into [`LLMStreamEvent`]s.
+pub fn parse_sse_stream<S, B, E>(bytes: S) -> LLMStream
+where
+    S: Stream<Item = Result<B, E>> + Unpin + Send + 'static,
+    B: AsRef<[u8]>,
+    E: ToString,
+{
+    let state = StreamState {
+        bytes,
+        parser: SseParser::new(),
+        events: VecDeque::new(),
+        finish_reason: None,
+        usage: None,
+        finished: false,
+    };
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(event) = state.events.pop_front() {
+                return Some((event, state));
+            }
+            if state.finished {
+                return None;
+            }
+            match state.bytes.next().await {
+                Some(Ok(bytes)) => {
+                    for data in state.parser.push(bytes.as_ref()) {
+                        state.read_chunk(&data);
+                    }
+                }
+                Some(Err(error)) => {
+                    state.finished = true;
+                    return Some((Err(LLMError::transport(error)), state));
+                }
+                // Some servers close the connection without a [DONE] event
+                None => state.finish(),
+            }
+        }
+    })
+    .boxed()
+}
+
+#[test]
+pub fn test_sse_parser() {
+    let mut parser = SseParser::new();
+    assert!(parser.push(b"data: {\"a\":").is_empty());
+    assert_eq!(
+        parser.push(b"1}\r\n\r\n: keep-alive\n\ndata: [DONE]\n\n"),
+        vec!["{\"a\":1}", "[DONE]"]
+    );
+    // Multi-byte characters may be split across chunks
+    let text = "data: café\n\n".as_bytes();
+    assert!(parser.push(&text[..10]).is_empty());
+    assert_eq!(parser.push(&text[10..]), vec!["café"]);
+}
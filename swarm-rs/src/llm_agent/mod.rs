@@ -1,4 +1,10 @@
+pub mod conversation;
+#[allow(clippy::module_inception)]
 pub mod llm_agent;
 pub mod llm_client;
+pub mod llm_error;
 pub mod llm_message;
-pub mod llm_response;
\ No newline at end of file
+pub mod llm_output;
+pub mod llm_provider;
+pub mod llm_response;
+pub mod llm_stream;
\ No newline at end of file
@@ -5,19 +5,10 @@ use std::{
     sync::{Arc, Mutex},
 };
 
-use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde::Serialize;
 
 use crate::utils::{file_io, time::today_with_format};
 
-#[derive(Serialize, Deserialize)]
-struct LogEntry {
-    level: String,
-    event_type: String,
-    data: Value,
-    timestamp: String,
-}
-
 pub struct Logger {
     file_prefix: String,
     base_dir: PathBuf,
@@ -58,7 +49,7 @@ impl Logger {
         let json_data = serde_json::to_string(data).expect("Failed to serialize log data");
 
         let current_file = Self::current_file_name(&self.file_prefix);
-        let mut file = if &current_file == &self.file_name {
+        let mut file = if current_file == self.file_name {
             let file = self.file.lock().expect("Failed to lock log file");
             file
         }else {
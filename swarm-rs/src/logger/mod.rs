@@ -1,2 +1,3 @@
+#[allow(clippy::module_inception)]
 mod logger;
 pub use logger::*;
\ No newline at end of file
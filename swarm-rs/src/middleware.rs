@@ -0,0 +1,110 @@
+use std::{sync::Arc, time::Instant};
+
+use async_trait::async_trait;
+
+use crate::{
+    agent::{Action, Output},
+    logger::Logger,
+    swarm::Swarm,
+    utils::pattern::wildcard_match,
+};
+
+/// A single execution of an action through the swarm, shared by the middlewares.
+pub struct Invocation<'a> {
+    action: &'a Action,
+    event_type: String,
+    started_at: Instant,
+}
+
+impl<'a> Invocation<'a> {
+    pub(crate) fn new(action: &'a Action, event_type: String) -> Self {
+        Self {
+            action,
+            event_type,
+            started_at: Instant::now(),
+        }
+    }
+
+    pub fn get_action(&self) -> &Action {
+        self.action
+    }
+
+    /// Unique event type of this execution, used as the log key.
+    pub fn get_event_type(&self) -> &str {
+        &self.event_type
+    }
+
+    pub fn elapsed_ms(&self) -> u64 {
+        self.started_at.elapsed().as_millis() as u64
+    }
+}
+
+/// Hooks run by the [`Swarm`] around every executed action.
+///
+/// `before` hooks run in registration order and may short-circuit the agent
+/// by returning an output. `after` hooks run in reverse order, for every
+/// middleware whose `before` hook ran, and may replace the output.
+#[async_trait]
+pub trait Middleware: Send + Sync {
+    async fn before(&self, _invocation: &Invocation<'_>, _swarm: &Swarm) -> Option<Output> {
+        None
+    }
+
+    async fn after(&self, _invocation: &Invocation<'_>, output: Output, _swarm: &Swarm) -> Output {
+        output
+    }
+}
+
+pub(crate) struct MiddlewareEntry {
+    agent_pattern: Option<String>,
+    middleware: Arc<dyn Middleware>,
+}
+
+impl MiddlewareEntry {
+    pub(crate) fn new(agent_pattern: Option<&str>, middleware: Arc<dyn Middleware>) -> Self {
+        Self {
+            agent_pattern: agent_pattern.map(|pattern| pattern.to_string()),
+            middleware,
+        }
+    }
+
+    pub(crate) fn applies_to(&self, action: &Action) -> bool {
+        match &self.agent_pattern {
+            Some(pattern) => wildcard_match(pattern, action.get_agent()),
+            None => true,
+        }
+    }
+
+    pub(crate) fn middleware(&self) -> &dyn Middleware {
+        self.middleware.as_ref()
+    }
+}
+
+/// Logs the action when it starts and its output when it ends.
+pub struct LoggingMiddleware {
+    logger: Arc<Logger>,
+}
+
+impl LoggingMiddleware {
+    pub fn new(logger: Arc<Logger>) -> Self {
+        Self { logger }
+    }
+}
+
+#[async_trait]
+impl Middleware for LoggingMiddleware {
+    async fn before(&self, invocation: &Invocation<'_>, _swarm: &Swarm) -> Option<Output> {
+        self.logger
+            .info(invocation.get_event_type(), invocation.get_action());
+        None
+    }
+
+    async fn after(&self, invocation: &Invocation<'_>, output: Output, _swarm: &Swarm) -> Output {
+        if output.is_success() {
+            self.logger.info(invocation.get_event_type(), &output);
+        } else {
+            self.logger.error(invocation.get_event_type(), &output);
+        }
+        output
+    }
+}
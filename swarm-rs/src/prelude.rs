@@ -1,5 +1,7 @@
-pub use crate::{agent::*, swarm::*, llm_agent::llm_agent::*, searx_agent::*, utils::*};
+pub use crate::{agent::*, cache::*, circuit_breaker::*, context::*, error::*, fixture::*, middleware::*, retry::*, schema::*, swarm::*, usage::*, llm_agent::llm_agent::*, searx_agent::*, utils::*, workflow::*};
 pub use async_trait::async_trait;
 pub use rocket::{launch, Build, Rocket};
+pub use schemars::JsonSchema;
 pub use std::any::Any;
+pub use tokio_util::sync::CancellationToken;
 pub use swarm_rs_macros::*;
@@ -0,0 +1,175 @@
+use std::{sync::Arc, time::Duration};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    agent::OutputStatus,
+    error::{AgentError, ErrorCode},
+};
+
+type RetryPredicate = Arc<dyn Fn(&AgentError) -> bool + Send + Sync>;
+
+/// Declarative retry policy applied by the swarm to failed actions.
+///
+/// The delay before attempt `n + 1` is `initial_backoff * multiplier^(n - 1)`,
+/// capped by `max_backoff` and randomly spread by `jitter` (a ratio in `0..=1`).
+/// By default the errors flagged as retryable are retried, along with the
+/// `ActionFailed` and `Timeout` ones, so that plain `Err(String)` results are too.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub multiplier: f64,
+    pub jitter: f64,
+    retry_on: RetryPredicate,
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: 0.2,
+            retry_on: Arc::new(is_retried),
+        }
+    }
+
+    pub fn with_backoff(mut self, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn retry_on<F: Fn(&AgentError) -> bool + Send + Sync + 'static>(
+        mut self,
+        predicate: F,
+    ) -> Self {
+        self.retry_on = Arc::new(predicate);
+        self
+    }
+
+    /// Tells whether a failed `attempt` (starting at 1) should be retried.
+    pub fn should_retry(&self, error: &AgentError, attempt: u32) -> bool {
+        attempt < self.max_attempts && error.code != ErrorCode::Cancelled && (self.retry_on)(error)
+    }
+
+    /// Delay to wait after the failed `attempt`, never shorter than the `retry_after`
+    /// asked for by the error.
+    pub fn delay(&self, error: &AgentError, attempt: u32) -> Duration {
+        let backoff = self.backoff(attempt);
+        error
+            .get_retry_after()
+            .map_or(backoff, |retry_after| retry_after.max(backoff))
+    }
+
+    /// Delay to wait after the failed `attempt` (starting at 1).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1) as i32;
+        let base = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+        let spread = if self.jitter > 0.0 {
+            rand::thread_rng().gen_range(1.0 - self.jitter..=1.0 + self.jitter)
+        } else {
+            1.0
+        };
+        let delay = (base * spread).min(self.max_backoff.as_secs_f64());
+        Duration::from_secs_f64(delay.max(0.0))
+    }
+}
+
+/// Default predicate of the policies.
+fn is_retried(error: &AgentError) -> bool {
+    error.retryable || matches!(error.code, ErrorCode::ActionFailed | ErrorCode::Timeout)
+}
+
+/// Serializable settings of a [`RetryPolicy`], as declared by
+/// `#[agent_action(retry(max_attempts = 3, initial_backoff_ms = 100, max_backoff_ms = 2000))]`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetrySettings {
+    pub max_attempts: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initial_backoff_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_backoff_ms: Option<u64>,
+}
+
+impl RetrySettings {
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            initial_backoff_ms: None,
+            max_backoff_ms: None,
+        }
+    }
+
+    pub fn with_initial_backoff_ms(mut self, initial_backoff_ms: u64) -> Self {
+        self.initial_backoff_ms = Some(initial_backoff_ms);
+        self
+    }
+
+    pub fn with_max_backoff_ms(mut self, max_backoff_ms: u64) -> Self {
+        self.max_backoff_ms = Some(max_backoff_ms);
+        self
+    }
+
+    /// Policy retrying the failures of the action, with the default backoff unless set.
+    pub fn to_policy(&self) -> RetryPolicy {
+        let policy = RetryPolicy::new(self.max_attempts);
+        let initial_backoff = self
+            .initial_backoff_ms
+            .map(Duration::from_millis)
+            .unwrap_or(policy.initial_backoff);
+        let max_backoff = self
+            .max_backoff_ms
+            .map(Duration::from_millis)
+            .unwrap_or(policy.max_backoff)
+            .max(initial_backoff);
+        policy.with_backoff(initial_backoff, max_backoff)
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct AttemptLog<'a> {
+    pub attempt: u32,
+    pub max_attempts: u32,
+    pub status: OutputStatus,
+    pub error: Option<&'a AgentError>,
+    pub retry_in_ms: Option<u64>,
+}
+
+#[test]
+pub fn test_retry_backoff() {
+    let policy = RetryPolicy::new(5)
+        .with_backoff(Duration::from_millis(100), Duration::from_millis(500))
+        .with_jitter(0.0);
+    assert_eq!(policy.backoff(1), Duration::from_millis(100));
+    assert_eq!(policy.backoff(2), Duration::from_millis(200));
+    assert_eq!(policy.backoff(3), Duration::from_millis(400));
+    assert_eq!(policy.backoff(4), Duration::from_millis(500));
+
+    // The delay asked for by the error wins over a shorter backoff
+    let error = AgentError::new(ErrorCode::RateLimited, "Slow down")
+        .with_retry_after(Duration::from_secs(2));
+    assert_eq!(policy.delay(&error, 1), Duration::from_secs(2));
+    let error = error.with_retry_after(Duration::from_millis(10));
+    assert_eq!(policy.delay(&error, 2), Duration::from_millis(200));
+
+    let policy = policy.with_jitter(0.5);
+    for attempt in 1..5 {
+        let delay = policy.backoff(attempt);
+        assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(500));
+    }
+}
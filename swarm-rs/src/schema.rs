@@ -0,0 +1,244 @@
+use std::{marker::PhantomData, time::Duration};
+
+use schemars::{schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::retry::RetrySettings;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionKind {
+    /// Handles its payload on its own, `#[agent_action]`.
+    #[default]
+    Action,
+    /// Executes other actions through the swarm, `#[agent_workflow]`.
+    Workflow,
+}
+
+/// Action exposed by an agent, generated by `#[agent]` for its actions and workflows.
+///
+/// The schemas are only set for the types implementing [`JsonSchema`]. The
+/// options of `#[agent_action(...)]` and `#[agent_workflow(...)]` are enforced
+/// by the [`Swarm`](crate::swarm::Swarm), except the roles checked by the web layer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionDescriptor {
+    /// Public name, the method name unless renamed with `name = "..."`.
+    pub name: String,
+    #[serde(default)]
+    pub kind: ActionKind,
+    /// `description = "..."` option, or the doc comment of the method.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Other names dispatched to the action.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// Roles allowed to execute the action through the web API, any user being allowed when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>,
+    /// Deadline of the actions that do not define their own timeout.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Retry policy, unless the swarm sets one for the action id.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetrySettings>,
+    #[serde(default)]
+    pub deprecated: bool,
+    /// JSON Schema of the payload.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_schema: Option<Value>,
+    /// JSON Schema of the `Ok` value.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<Value>,
+    /// JSON Schema of the `Err` value.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_schema: Option<Value>,
+}
+
+impl ActionDescriptor {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            kind: ActionKind::Action,
+            description: None,
+            aliases: vec![],
+            roles: vec![],
+            timeout_ms: None,
+            retry: None,
+            deprecated: false,
+            input_schema: None,
+            output_schema: None,
+            error_schema: None,
+        }
+    }
+
+    pub fn with_kind(mut self, kind: ActionKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    pub fn with_aliases(mut self, aliases: &[&str]) -> Self {
+        self.aliases = aliases.iter().map(|alias| alias.to_string()).collect();
+        self
+    }
+
+    pub fn with_roles(mut self, roles: &[&str]) -> Self {
+        self.roles = roles.iter().map(|role| role.to_string()).collect();
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout_ms = Some(timeout.as_millis() as u64);
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetrySettings) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    pub fn deprecated(mut self) -> Self {
+        self.deprecated = true;
+        self
+    }
+
+    pub fn get_timeout(&self) -> Option<Duration> {
+        self.timeout_ms.map(Duration::from_millis)
+    }
+
+    /// Tells whether the action is dispatched under this name.
+    pub fn is_named(&self, name: &str) -> bool {
+        self.name == name || self.aliases.iter().any(|alias| alias == name)
+    }
+
+    pub fn with_input_schema(mut self, schema: Option<Value>) -> Self {
+        self.input_schema = schema;
+        self
+    }
+
+    pub fn with_output_schema(mut self, schema: Option<Value>) -> Self {
+        self.output_schema = schema;
+        self
+    }
+
+    pub fn with_error_schema(mut self, schema: Option<Value>) -> Self {
+        self.error_schema = schema;
+        self
+    }
+}
+
+/// Actions of a registered agent, as listed by [`Swarm::describe_agents`](crate::swarm::Swarm::describe_agents).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentDescriptor {
+    pub agent_id: String,
+    pub actions: Vec<ActionDescriptor>,
+}
+
+impl AgentDescriptor {
+    /// Ids of the actions, such as `searx.search`.
+    pub fn action_ids(&self) -> Vec<String> {
+        self.actions
+            .iter()
+            .map(|action| format!("{}.{}", self.agent_id, action.name))
+            .collect()
+    }
+}
+
+/// Schema of the payload object of an action taking several arguments, from
+/// their name, schema and whether they are required. The definitions of the
+/// argument schemas are merged.
+#[doc(hidden)]
+pub fn arguments_schema(arguments: Vec<(&str, Option<Value>, bool)>) -> Option<Value> {
+    let mut properties = Map::new();
+    let mut definitions = Map::new();
+    let mut required = vec![];
+    for (name, schema, is_required) in arguments {
+        let mut schema = schema.unwrap_or_else(|| json!({}));
+        if let Some(Value::Object(argument_definitions)) = schema
+            .as_object_mut()
+            .and_then(|schema| schema.remove("$defs"))
+        {
+            definitions.extend(argument_definitions);
+        }
+        properties.insert(name.to_string(), schema);
+        if is_required {
+            required.push(name);
+        }
+    }
+    let mut schema = json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    });
+    if !definitions.is_empty() {
+        schema["$defs"] = Value::Object(definitions);
+    }
+    Some(schema)
+}
+
+/// Returns the JSON Schema of `T` through [`WithSchema`], or `None` through
+/// [`WithoutSchema`] when `T` does not implement [`JsonSchema`]:
+/// `(&SchemaProbe::<T>::new()).json_schema()`.
+#[doc(hidden)]
+pub struct SchemaProbe<T: ?Sized>(PhantomData<T>);
+
+impl<T: ?Sized> SchemaProbe<T> {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: ?Sized> Clone for SchemaProbe<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for SchemaProbe<T> {}
+
+#[doc(hidden)]
+pub trait WithSchema {
+    fn json_schema(self) -> Option<Value>;
+}
+
+impl<T: JsonSchema + ?Sized> WithSchema for &SchemaProbe<T> {
+    fn json_schema(self) -> Option<Value> {
+        let mut schema = serde_json::to_value(schema_for!(T)).ok()?;
+        if let Some(schema) = schema.as_object_mut() {
+            schema.remove("$schema");
+        }
+        Some(schema)
+    }
+}
+
+#[doc(hidden)]
+pub trait WithoutSchema {
+    fn json_schema(self) -> Option<Value>;
+}
+
+impl<T: ?Sized> WithoutSchema for SchemaProbe<T> {
+    fn json_schema(self) -> Option<Value> {
+        None
+    }
+}
+
+#[test]
+#[allow(clippy::needless_borrow)]
+pub fn test_schema_probe() {
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    struct Query {
+        terms: String,
+    }
+    struct Opaque {}
+
+    let schema = (&SchemaProbe::<Query>::new()).json_schema().unwrap();
+    assert_eq!(schema["properties"]["terms"]["type"], "string");
+    assert!((&SchemaProbe::<Opaque>::new()).json_schema().is_none());
+}
@@ -1,4 +1,5 @@
 mod searx;
+#[allow(clippy::module_inception)]
 mod searx_agent;
 pub use searx::{SearxResponse, SearxQuery};
 pub use searx_agent::{SearchQuery, SearxAgent};
\ No newline at end of file
@@ -66,7 +66,7 @@ pub async fn search(engine_url: &str, query: &SearxQuery) -> SearxResponse {
     } else {
         "".to_string()
     };
-    let engines_params = if query.engines.len() > 0 {
+    let engines_params = if !query.engines.is_empty() {
         format!("&engines={}", query.engines.join(","))
     } else {
         "".to_string()
@@ -1,17 +1,20 @@
 use std::any::Any;
 
 use async_trait::async_trait;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use swarm_rs_macros::{agent, agent_action};
 
 use crate::{
     agent::{Action, Agent, Output},
+    error::{AgentError, ErrorCode, ErrorProbe, IntoAgentError},
     prelude::Swarm,
+    schema::{ActionDescriptor, ActionKind, SchemaProbe, WithSchema, WithoutSchema},
 };
 
 use super::searx::{search, SearxQuery, SearxResponse, SearxResultEntry};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct SearchQuery {
     pub terms: String,
     pub lang: Option<String>,
@@ -60,8 +63,8 @@ impl SearxAgent {
             }
             if success {
                 let resp = SearxResponse {
-                    success: success,
-                    results: results,
+                    success,
+                    results,
                 };
                Ok(resp)
             } else {
@@ -1,37 +1,311 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
 
 use chrono::Utc;
-use serde::Serialize;
+use futures::{
+    future::BoxFuture,
+    stream::{self, BoxStream, FuturesUnordered},
+    FutureExt, StreamExt,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{self, UnboundedReceiver};
 
 use crate::{
     agent::{Action, Agent, Output},
+    cache::ResponseCache,
+    circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitState},
+    context::ProgressEvent,
+    error::{AgentError, ErrorCode},
     logger::Logger,
+    retry::{AttemptLog, RetryPolicy},
+    schema::{ActionDescriptor, AgentDescriptor},
+    middleware::{Invocation, LoggingMiddleware, Middleware, MiddlewareEntry},
+    usage::UsageTracker,
+    workflow::{Approvals, CheckpointStore},
 };
 
-pub struct Swarm {
-    agents: HashMap<String, Box<dyn Agent>>,
-    logging: Logger,
+/// How [`Swarm::execute_join`] reacts when one of the joined actions fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JoinMode {
+    /// Stop scheduling on the first failure and cancel the actions still running.
+    FailFast,
+    /// Run every action and collect all the outputs.
+    CollectAll,
 }
 
-impl Swarm {
-    pub fn default() -> Self {
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JoinOptions {
+    pub max_concurrency: Option<usize>,
+    pub mode: JoinMode,
+}
+
+impl Default for JoinOptions {
+    fn default() -> Self {
         Self {
-            agents: HashMap::new(),
-            logging: Logger::new("logs", "swarm"),
+            max_concurrency: None,
+            mode: JoinMode::CollectAll,
         }
     }
+}
+
+impl JoinOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = Some(max_concurrency);
+        self
+    }
+
+    pub fn fail_fast(mut self) -> Self {
+        self.mode = JoinMode::FailFast;
+        self
+    }
+
+    pub fn collect_all(mut self) -> Self {
+        self.mode = JoinMode::CollectAll;
+        self
+    }
+}
+
+/// Item of [`Swarm::execute_stream`], the last one being the output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ActionEvent {
+    Progress(ProgressEvent),
+    Output(Output),
+}
+
+struct StreamState<'a> {
+    receiver: UnboundedReceiver<ProgressEvent>,
+    execution: Option<BoxFuture<'a, Output>>,
+    output: Option<Output>,
+}
+
+/// Execution settings of a registered agent.
+#[derive(Clone, Default)]
+pub struct AgentOptions {
+    pub retry: Option<RetryPolicy>,
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+}
+
+impl AgentOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    pub fn with_circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.circuit_breaker = Some(config);
+        self
+    }
+}
+
+pub struct Swarm {
+    agents: HashMap<String, Box<dyn Agent>>,
+    descriptors: HashMap<String, Vec<ActionDescriptor>>,
+    agent_options: HashMap<String, AgentOptions>,
+    action_retry_policies: HashMap<String, RetryPolicy>,
+    default_circuit_breaker: Option<CircuitBreakerConfig>,
+    circuit_breakers: Mutex<HashMap<String, Arc<CircuitBreaker>>>,
+    logging: Arc<Logger>,
+    middlewares: Vec<MiddlewareEntry>,
+    sequence: AtomicU64,
+    default_timeout: Option<Duration>,
+    checkpoint_store: Option<Arc<dyn CheckpointStore>>,
+    approvals: Option<Approvals>,
+    usage: UsageTracker,
+    cache: Option<ResponseCache>,
+}
+
+impl Default for Swarm {
+    fn default() -> Self {
+        Self::new("logs")
+    }
+}
 
+impl Swarm {
     pub fn new(logs_base_dir: &str) -> Self {
+        let logging = Arc::new(Logger::new(logs_base_dir, "swarm"));
+        let logging_middleware = LoggingMiddleware::new(logging.clone());
         Self {
             agents: HashMap::new(),
-            logging: Logger::new(logs_base_dir, "swarm"),
+            descriptors: HashMap::new(),
+            agent_options: HashMap::new(),
+            action_retry_policies: HashMap::new(),
+            default_circuit_breaker: None,
+            circuit_breakers: Mutex::new(HashMap::new()),
+            logging,
+            middlewares: vec![MiddlewareEntry::new(None, Arc::new(logging_middleware))],
+            sequence: AtomicU64::new(0),
+            default_timeout: None,
+            checkpoint_store: None,
+            approvals: None,
+            usage: UsageTracker::new(),
+            cache: None,
         }
     }
 
+    /// Deadline applied to the actions that do not define their own timeout.
+    pub fn set_default_timeout(&mut self, timeout: Option<Duration>) {
+        self.default_timeout = timeout;
+    }
+
+    /// Store where the workflows persist their runs, enabling resume.
+    pub fn set_checkpoint_store<T: CheckpointStore + 'static>(&mut self, store: T) {
+        self.checkpoint_store = Some(Arc::new(store));
+    }
+
+    pub fn get_checkpoint_store(&self) -> Option<Arc<dyn CheckpointStore>> {
+        self.checkpoint_store.clone()
+    }
+
+    /// Enables the approval steps of the workflows.
+    pub fn set_approvals(&mut self, approvals: Approvals) {
+        self.approvals = Some(approvals);
+    }
+
+    pub fn get_approvals(&self) -> Option<&Approvals> {
+        self.approvals.as_ref()
+    }
+
+    /// Tracker pricing the LLM calls and enforcing the budgets.
+    pub fn set_usage_tracker(&mut self, tracker: UsageTracker) {
+        self.usage = tracker;
+    }
+
+    pub fn get_usage_tracker(&self) -> &UsageTracker {
+        &self.usage
+    }
+
+    /// Serves the outputs of the actions already executed with the same payload.
+    pub fn set_response_cache(&mut self, cache: Option<ResponseCache>) {
+        self.cache = cache;
+    }
+
+    pub fn get_response_cache(&self) -> Option<&ResponseCache> {
+        self.cache.as_ref()
+    }
+
     pub fn register_agent<T: Agent + 'static>(&mut self, agent_id: &str, agent: T) {
+        self.descriptors
+            .insert(agent_id.to_string(), agent.describe());
         self.agents.insert(agent_id.to_string(), Box::new(agent));
     }
 
+    pub fn register_agent_with_options<T: Agent + 'static>(
+        &mut self,
+        agent_id: &str,
+        agent: T,
+        options: AgentOptions,
+    ) {
+        self.register_agent(agent_id, agent);
+        self.agent_options.insert(agent_id.to_string(), options);
+    }
+
+    /// Retry policy of a single action id, taking precedence over the agent one.
+    pub fn set_action_retry_policy(&mut self, action_id: &str, policy: RetryPolicy) {
+        self.action_retry_policies
+            .insert(action_id.to_string(), policy);
+    }
+
+    /// Circuit breaker applied to the agents registered without their own one.
+    pub fn set_default_circuit_breaker(&mut self, config: Option<CircuitBreakerConfig>) {
+        self.default_circuit_breaker = config;
+    }
+
+    /// Current circuit state of an agent, or `None` when it has no circuit breaker.
+    pub fn get_circuit_state(&self, agent_id: &str) -> Option<CircuitState> {
+        self.get_circuit_breaker(agent_id)
+            .map(|breaker| breaker.get_state())
+    }
+
+    fn get_circuit_breaker(&self, agent_id: &str) -> Option<Arc<CircuitBreaker>> {
+        let config = self
+            .agent_options
+            .get(agent_id)
+            .and_then(|options| options.circuit_breaker.as_ref())
+            .or(self.default_circuit_breaker.as_ref())?;
+        let mut breakers = self
+            .circuit_breakers
+            .lock()
+            .expect("Failed to lock circuit breakers");
+        let breaker = breakers
+            .entry(agent_id.to_string())
+            .or_insert_with(|| Arc::new(CircuitBreaker::new(config.clone())));
+        Some(breaker.clone())
+    }
+
+    fn get_retry_policy(&self, action: &Action) -> Option<RetryPolicy> {
+        if let Some(policy) = self.action_retry_policies.get(action.get_id()) {
+            return Some(policy.clone());
+        }
+        if let Some(retry) = self
+            .find_descriptor(action)
+            .and_then(|descriptor| descriptor.retry.as_ref())
+        {
+            return Some(retry.to_policy());
+        }
+        self.agent_options
+            .get(action.get_agent())
+            .and_then(|options| options.retry.clone())
+    }
+
+    /// Descriptor of the action, dispatched under its public name or one of its aliases.
+    fn find_descriptor(&self, action: &Action) -> Option<&ActionDescriptor> {
+        self.descriptors
+            .get(action.get_agent())?
+            .iter()
+            .find(|descriptor| descriptor.is_named(action.get_name()))
+    }
+
+    /// Checks that the action is executed for a user having one of the roles it declares,
+    /// whoever executes it: a client, another agent, a workflow step or an LLM tool call.
+    pub fn check_roles(&self, action: &Action) -> Result<(), AgentError> {
+        let roles = match self.find_descriptor(action) {
+            Some(descriptor) if !descriptor.roles.is_empty() => &descriptor.roles,
+            _ => return Ok(()),
+        };
+        let context = action.get_context();
+        if context.get_user_id().is_none() {
+            return Err(AgentError::new(
+                ErrorCode::Unauthorized,
+                &format!("{} requires an authenticated user", action.get_id()),
+            ));
+        }
+        if !context.get_roles().iter().any(|role| roles.contains(role)) {
+            return Err(AgentError::new(
+                ErrorCode::Forbidden,
+                &format!("{} requires one of the roles {}", action.get_id(), roles.join(", ")),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Registers a middleware run around every action.
+    pub fn add_middleware<T: Middleware + 'static>(&mut self, middleware: T) {
+        self.middlewares
+            .push(MiddlewareEntry::new(None, Arc::new(middleware)));
+    }
+
+    /// Registers a middleware run around the actions of the agents matching
+    /// `agent_pattern`, where `*` matches any sequence of characters.
+    pub fn add_agent_middleware<T: Middleware + 'static>(&mut self, agent_pattern: &str, middleware: T) {
+        self.middlewares
+            .push(MiddlewareEntry::new(Some(agent_pattern), Arc::new(middleware)));
+    }
+
     pub async fn execute<T: Serialize>(&self, action_id: &str, payload: &T) -> Output {
         let action = Action::new(action_id, payload);
         let output = self.execute_action(&action).await;
@@ -40,25 +314,306 @@ impl Swarm {
 
     pub async fn execute_action(&self, action: &Action) -> Output {
         let action_ts = Utc::now().timestamp_millis() as u64;
-        let event_type = format!("Action[{}:{}]", action.get_id(), action_ts);
-        self.logging.info(&event_type, &action);
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed);
+        let event_type = format!("Action[{}:{}-{}]", action.get_id(), action_ts, sequence);
+        let invocation = Invocation::new(action, event_type);
 
-        let agent_id = action.get_agent();
-        if let Some(agent) = self.agents.get(agent_id) {
-            // println!("Agent Found downcasting");
-            // let agent:Option<Box<&dyn Agent>> = agent.downcast_ref();
-            // if let Some(agent) = agent {
-            let mut output = agent.execute(action, &self).await;
-            output.agent_id = agent_id.to_string();
-            self.logging.info(&event_type, &output);
-            return output;
-            // }
-        }
-        let output = Output::new_error("Agent Not Found");
-        self.logging.error(&event_type, &output);
+        let middlewares: Vec<&dyn Middleware> = self
+            .middlewares
+            .iter()
+            .filter(|entry| entry.applies_to(action))
+            .map(|entry| entry.middleware())
+            .collect();
+
+        let mut entered = 0;
+        let mut short_circuit = None;
+        for middleware in &middlewares {
+            entered += 1;
+            if let Some(output) = middleware.before(&invocation, self).await {
+                short_circuit = Some(output);
+                break;
+            }
+        }
+
+        let mut output = match short_circuit {
+            Some(output) => output,
+            None => self.dispatch_cached(&invocation).await,
+        };
+        output.set_origin(action);
+
+        for middleware in middlewares[..entered].iter().rev() {
+            output = middleware.after(&invocation, output, self).await;
+        }
+        output
+    }
+
+    async fn dispatch_cached(&self, invocation: &Invocation<'_>) -> Output {
+        let action = invocation.get_action();
+        let cache = match &self.cache {
+            Some(cache) if cache.is_cached(action.get_id()) => cache,
+            _ => return self.dispatch(invocation).await,
+        };
+        // Checked by `dispatch` too late for a cached output, which only allowed users get
+        if let Err(error) = self.check_roles(action) {
+            return Output::from_error(error);
+        }
+        let log_type = format!("Cache[{}]", action.get_id());
+        match cache.lookup(action) {
+            Ok(Some(output)) => {
+                self.logging
+                    .info(&log_type, &output.get_metadata("cache"));
+                return output;
+            }
+            Ok(None) => {}
+            Err(error) => self.logging.warn(&log_type, &error),
+        }
+
+        let output = self.dispatch(invocation).await;
+        if let Err(error) = cache.store(action, &output) {
+            self.logging.warn(&log_type, &error);
+        }
+        output
+    }
+
+    async fn dispatch(&self, invocation: &Invocation<'_>) -> Output {
+        let action = invocation.get_action();
+        let agent = if let Some(agent) = self.agents.get(action.get_agent()) {
+            agent.as_ref()
+        } else {
+            return Output::from_error(AgentError::new(
+                ErrorCode::AgentNotFound,
+                "Agent Not Found",
+            ));
+        };
+        if let Err(error) = self.check_roles(action) {
+            return Output::from_error(error);
+        }
+        if self
+            .find_descriptor(action)
+            .is_some_and(|descriptor| descriptor.deprecated)
+        {
+            self.logging
+                .warn(&format!("Deprecated[{}]", action.get_id()), &action.get_id());
+        }
+        let breaker = self.get_circuit_breaker(action.get_agent());
+        let policy = if let Some(policy) = self.get_retry_policy(action) {
+            policy
+        } else {
+            return self.run_guarded(agent, action, breaker.as_deref()).await;
+        };
+
+        let mut attempt = 1;
+        loop {
+            let output = self.run_guarded(agent, action, breaker.as_deref()).await;
+            let retry_in = match output.get_error() {
+                Some(error) if policy.should_retry(error, attempt) => Some(policy.delay(error, attempt)),
+                _ => None,
+            };
+
+            let attempt_log = AttemptLog {
+                attempt,
+                max_attempts: policy.max_attempts,
+                status: output.get_status(),
+                error: output.get_error(),
+                retry_in_ms: retry_in.map(|delay| delay.as_millis() as u64),
+            };
+            if output.is_success() {
+                self.logging.info(invocation.get_event_type(), &attempt_log);
+            } else {
+                self.logging.warn(invocation.get_event_type(), &attempt_log);
+            }
+
+            if let Some(delay) = retry_in {
+                tokio::select! {
+                    _ = action.cancellation_token().cancelled() => {
+                        return Output::new_cancelled("Action cancelled");
+                    }
+                    _ = tokio::time::sleep(delay) => {}
+                }
+                attempt += 1;
+            } else {
+                return output;
+            }
+        }
+    }
+
+    async fn run_guarded(
+        &self,
+        agent: &dyn Agent,
+        action: &Action,
+        breaker: Option<&CircuitBreaker>,
+    ) -> Output {
+        let breaker = if let Some(breaker) = breaker {
+            breaker
+        } else {
+            return self.run_agent(agent, action).await;
+        };
+        let permit = if let Some(permit) = breaker.try_acquire() {
+            permit
+        } else {
+            return Output::from_error(AgentError::new(
+                ErrorCode::CircuitOpen,
+                "Circuit open",
+            ));
+        };
+
+        let output = self.run_agent(agent, action).await;
+        let state = permit.record(output.get_error());
+        if state == CircuitState::Open {
+            self.logging
+                .warn(&format!("Circuit[{}]", action.get_agent()), &state);
+        }
         output
     }
 
+    async fn run_agent(&self, agent: &dyn Agent, action: &Action) -> Output {
+        let mut context = action.get_context().clone();
+        context.set_action_id(action.get_id());
+        let cancellation = context.cancellation_token().clone();
+        let execution = context.scope(agent.execute(action, self));
+
+        let timeout = action
+            .get_timeout()
+            .or_else(|| self.find_descriptor(action)?.get_timeout())
+            .or(self.default_timeout);
+        tokio::select! {
+            biased;
+            _ = cancellation.cancelled() => Output::new_cancelled("Action cancelled"),
+            output = async {
+                if let Some(timeout) = timeout {
+                    match tokio::time::timeout(timeout, execution).await {
+                        Ok(output) => output,
+                        Err(_) => Output::new_timeout(&format!(
+                            "Action timed out after {}ms",
+                            timeout.as_millis()
+                        )),
+                    }
+                } else {
+                    execution.await
+                }
+            } => output,
+        }
+    }
+
+    /// Executes the action, streaming the progress events emitted by it and
+    /// by its nested actions, then its output.
+    pub fn execute_stream<'a>(&'a self, action: &Action) -> BoxStream<'a, ActionEvent> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let action = action.clone().with_progress(sender);
+        let state = StreamState {
+            receiver,
+            execution: Some(async move { self.execute_action(&action).await }.boxed()),
+            output: None,
+        };
+        stream::unfold(state, |mut state| async move {
+            if let Some(execution) = state.execution.as_mut() {
+                tokio::select! {
+                    biased;
+                    Some(event) = state.receiver.recv() => {
+                        return Some((ActionEvent::Progress(event), state));
+                    }
+                    output = execution => {
+                        state.execution = None;
+                        state.output = Some(output);
+                    }
+                }
+            }
+            // Events sent just before the output
+            if let Ok(event) = state.receiver.try_recv() {
+                return Some((ActionEvent::Progress(event), state));
+            }
+            let output = state.output.take()?;
+            Some((ActionEvent::Output(output), state))
+        })
+        .boxed()
+    }
+
+    /// Runs all the actions concurrently and collects every output, in the actions order.
+    pub async fn execute_all(&self, actions: &[Action]) -> Vec<Output> {
+        self.execute_join(actions, &JoinOptions::default()).await
+    }
+
+    /// Runs the actions concurrently, at most `max_concurrency` at a time.
+    /// Returns one output per action, in the actions order.
+    pub async fn execute_join(&self, actions: &[Action], options: &JoinOptions) -> Vec<Output> {
+        let max_concurrency = options.max_concurrency.unwrap_or(actions.len()).max(1);
+        let mut outputs: Vec<Option<Output>> = actions.iter().map(|_| None).collect();
+        let mut pending = actions.iter().enumerate();
+        let mut running = FuturesUnordered::new();
+
+        loop {
+            while running.len() < max_concurrency {
+                if let Some((index, action)) = pending.next() {
+                    running.push(async move { (index, self.execute_action(action).await) });
+                } else {
+                    break;
+                }
+            }
+            if let Some((index, output)) = running.next().await {
+                let failed = !output.is_success();
+                outputs[index] = Some(output);
+                if failed && options.mode == JoinMode::FailFast {
+                    break;
+                }
+            } else {
+                break;
+            }
+        }
+        // Dropping the remaining futures cancels the actions still in flight
+        drop(running);
+
+        outputs
+            .into_iter()
+            .zip(actions)
+            .map(|(output, action)| match output {
+                Some(output) => output,
+                None => {
+                    let mut output = Output::new_cancelled("Cancelled");
+                    output.set_origin(action);
+                    self.logging.warn(&format!("Action[{}]", action.get_id()), &output);
+                    output
+                }
+            })
+            .collect()
+    }
+
+    /// Descriptor of an action id such as `searx.search`, the action name being
+    /// its public name or one of its aliases.
+    pub fn describe_action(&self, action_id: &str) -> Option<ActionDescriptor> {
+        let (agent_id, name) = action_id.split_once('.').unwrap_or((action_id, "default"));
+        self.descriptors
+            .get(agent_id)?
+            .iter()
+            .find(|descriptor| descriptor.is_named(name))
+            .cloned()
+    }
+
+    /// Actions of every registered agent, sorted by agent id.
+    pub fn describe_agents(&self) -> Vec<AgentDescriptor> {
+        let mut agents: Vec<AgentDescriptor> = self
+            .descriptors
+            .iter()
+            .map(|(agent_id, actions)| AgentDescriptor {
+                agent_id: agent_id.to_string(),
+                actions: actions.clone(),
+            })
+            .collect();
+        agents.sort_by(|a, b| a.agent_id.cmp(&b.agent_id));
+        agents
+    }
+
+    /// Ids of the actions of every registered agent.
+    pub fn list_actions(&self) -> Vec<String> {
+        self.describe_agents()
+            .iter()
+            .flat_map(|agent| agent.action_ids())
+            .collect()
+    }
+
+    pub fn get_logger(&self) -> &Logger {
+        &self.logging
+    }
+
     pub fn get_agent<T: Agent + 'static>(&self, agent_id: &str) -> Option<&T> {
         if let Some(agent) = self.agents.get(agent_id) {
             let agent = agent.as_any().downcast_ref::<T>();
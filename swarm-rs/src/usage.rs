@@ -0,0 +1,413 @@
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    path::Path,
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    context::ActionContext,
+    error::{AgentError, ErrorCode},
+    llm_agent::llm_response::LLMUsage,
+    utils::{json_io, time::current_time_iso},
+};
+
+/// Price of a model, per million tokens.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelPrice {
+    pub prompt: f64,
+    pub completion: f64,
+}
+
+impl ModelPrice {
+    pub fn new(prompt: f64, completion: f64) -> Self {
+        Self { prompt, completion }
+    }
+
+    pub fn cost(&self, usage: &LLMUsage) -> f64 {
+        (usage.prompt_tokens as f64 * self.prompt
+            + usage.completion_tokens as f64 * self.completion)
+            / 1_000_000.0
+    }
+}
+
+/// Prices by model name, the models without a price costing nothing.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PriceTable {
+    prices: HashMap<String, ModelPrice>,
+}
+
+impl PriceTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads a JSON object such as `{ "gpt-4o": { "prompt": 2.5, "completion": 10.0 } }`.
+    pub fn load<P: AsRef<Path>>(file_path: P) -> Result<Self, String> {
+        json_io::load(file_path)
+    }
+
+    pub fn with_price(mut self, model: &str, price: ModelPrice) -> Self {
+        self.prices.insert(model.to_string(), price);
+        self
+    }
+
+    pub fn get_price(&self, model: &str) -> Option<&ModelPrice> {
+        self.prices.get(model)
+    }
+
+    pub fn cost(&self, model: &str, usage: &LLMUsage) -> f64 {
+        self.get_price(model)
+            .map(|price| price.cost(usage))
+            .unwrap_or(0.0)
+    }
+}
+
+/// Token usage of a single LLM call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    pub action_id: String,
+    pub agent_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workflow_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    pub model: String,
+    pub usage: LLMUsage,
+    pub cost: f64,
+    pub created_at: String,
+}
+
+impl UsageRecord {
+    fn group_key(&self, group: UsageGroup) -> String {
+        let key = match group {
+            UsageGroup::Action => Some(&self.action_id),
+            UsageGroup::Agent => Some(&self.agent_id),
+            UsageGroup::Workflow => self.workflow_id.as_ref(),
+            UsageGroup::Run => self.run_id.as_ref(),
+            UsageGroup::User => self.user_id.as_ref(),
+            UsageGroup::Model => Some(&self.model),
+        };
+        key.map(|key| key.to_string()).unwrap_or_default()
+    }
+}
+
+/// Selects the usage records, an empty filter matching all of them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageFilter {
+    pub action_id: Option<String>,
+    pub agent_id: Option<String>,
+    pub workflow_id: Option<String>,
+    pub run_id: Option<String>,
+    pub user_id: Option<String>,
+    pub model: Option<String>,
+}
+
+impl UsageFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn for_user(user_id: &str) -> Self {
+        Self {
+            user_id: Some(user_id.to_string()),
+            ..Self::default()
+        }
+    }
+
+    pub fn for_run(run_id: &str) -> Self {
+        Self {
+            run_id: Some(run_id.to_string()),
+            ..Self::default()
+        }
+    }
+
+    pub fn matches(&self, record: &UsageRecord) -> bool {
+        fn check(expected: &Option<String>, value: Option<&String>) -> bool {
+            expected.is_none() || expected.as_ref() == value
+        }
+        check(&self.action_id, Some(&record.action_id))
+            && check(&self.agent_id, Some(&record.agent_id))
+            && check(&self.workflow_id, record.workflow_id.as_ref())
+            && check(&self.run_id, record.run_id.as_ref())
+            && check(&self.user_id, record.user_id.as_ref())
+            && check(&self.model, Some(&record.model))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageGroup {
+    Action,
+    Agent,
+    Workflow,
+    Run,
+    User,
+    Model,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageTotals {
+    pub calls: usize,
+    pub prompt_tokens: usize,
+    pub completion_tokens: usize,
+    pub total_tokens: usize,
+    pub cost: f64,
+}
+
+impl UsageTotals {
+    fn add(&mut self, record: &UsageRecord) {
+        self.calls += 1;
+        self.prompt_tokens += record.usage.prompt_tokens;
+        self.completion_tokens += record.usage.completion_tokens;
+        self.total_tokens += record.usage.total_tokens;
+        self.cost += record.cost;
+    }
+}
+
+/// Totals of the matching records, with a breakdown when grouped.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageReport {
+    pub totals: UsageTotals,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub groups: BTreeMap<String, UsageTotals>,
+}
+
+/// Limits of the LLM calls, reached as soon as one of them is.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Budget {
+    pub max_tokens: Option<usize>,
+    pub max_cost: Option<f64>,
+}
+
+impl Budget {
+    pub fn tokens(max_tokens: usize) -> Self {
+        Self {
+            max_tokens: Some(max_tokens),
+            max_cost: None,
+        }
+    }
+
+    pub fn cost(max_cost: f64) -> Self {
+        Self {
+            max_tokens: None,
+            max_cost: Some(max_cost),
+        }
+    }
+
+    pub fn is_exceeded(&self, totals: &UsageTotals) -> bool {
+        self.max_tokens
+            .is_some_and(|max_tokens| totals.total_tokens >= max_tokens)
+            || self.max_cost.is_some_and(|max_cost| totals.cost >= max_cost)
+    }
+}
+
+/// Number of records, and of run totals, kept by default.
+const DEFAULT_MAX_RECORDS: usize = 10_000;
+
+/// Usage of a user since the start of its budget period.
+struct UserUsage {
+    period_start: Instant,
+    totals: UsageTotals,
+}
+
+#[derive(Default)]
+struct UsageState {
+    records: VecDeque<UsageRecord>,
+    users: HashMap<String, UserUsage>,
+    runs: HashMap<String, UsageTotals>,
+    run_ids: VecDeque<String>,
+}
+
+/// Token usage of the LLM calls made through a [`Swarm`](crate::swarm::Swarm),
+/// attributed to the action, agent, workflow run and user they were made for.
+///
+/// User budgets cap the usage of a user over the budget period, starting with
+/// its first call once the previous period ended, or over the lifetime of the
+/// tracker when no period is set. Workflow budgets cap the usage of each run of
+/// the workflow. Budgets are checked against running totals, while the records,
+/// and so the reports, only cover the last `max_records` calls.
+pub struct UsageTracker {
+    prices: PriceTable,
+    user_budgets: HashMap<String, Budget>,
+    workflow_budgets: HashMap<String, Budget>,
+    budget_period: Option<Duration>,
+    max_records: usize,
+    state: Mutex<UsageState>,
+}
+
+impl Default for UsageTracker {
+    fn default() -> Self {
+        Self {
+            prices: PriceTable::default(),
+            user_budgets: HashMap::new(),
+            workflow_budgets: HashMap::new(),
+            budget_period: None,
+            max_records: DEFAULT_MAX_RECORDS,
+            state: Mutex::new(UsageState::default()),
+        }
+    }
+}
+
+impl UsageTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Period after which the usage of each user starts over.
+    pub fn with_budget_period(mut self, period: Duration) -> Self {
+        self.budget_period = Some(period);
+        self
+    }
+
+    /// Number of records kept, the oldest ones being evicted first. The totals
+    /// of the same number of runs are kept for their budgets.
+    pub fn with_max_records(mut self, max_records: usize) -> Self {
+        self.max_records = max_records;
+        self
+    }
+
+    pub fn with_prices(mut self, prices: PriceTable) -> Self {
+        self.prices = prices;
+        self
+    }
+
+    pub fn with_user_budget(mut self, user_id: &str, budget: Budget) -> Self {
+        self.user_budgets.insert(user_id.to_string(), budget);
+        self
+    }
+
+    pub fn with_workflow_budget(mut self, workflow_id: &str, budget: Budget) -> Self {
+        self.workflow_budgets.insert(workflow_id.to_string(), budget);
+        self
+    }
+
+    pub fn get_prices(&self) -> &PriceTable {
+        &self.prices
+    }
+
+    /// Records the usage of a call made from the action executing in `context`.
+    pub fn record(&self, context: &ActionContext, model: &str, usage: &LLMUsage) -> UsageRecord {
+        let action_id = context.get_action_id();
+        let agent_id = action_id.split_once('.').map_or(action_id, |(agent_id, _)| agent_id);
+        let record = UsageRecord {
+            action_id: action_id.to_string(),
+            agent_id: agent_id.to_string(),
+            workflow_id: context.get_workflow_id().map(str::to_string),
+            run_id: context.get_run_id().map(str::to_string),
+            user_id: context.get_user_id().map(str::to_string),
+            model: model.to_string(),
+            usage: usage.clone(),
+            cost: self.prices.cost(model, usage),
+            created_at: current_time_iso(),
+        };
+        let mut state = self.lock_state();
+        if let Some(user_id) = &record.user_id {
+            let usage = state
+                .users
+                .entry(user_id.to_string())
+                .or_insert_with(|| UserUsage {
+                    period_start: Instant::now(),
+                    totals: UsageTotals::default(),
+                });
+            if self.is_period_over(usage) {
+                usage.period_start = Instant::now();
+                usage.totals = UsageTotals::default();
+            }
+            usage.totals.add(&record);
+        }
+        if let Some(run_id) = &record.run_id {
+            if !state.runs.contains_key(run_id) {
+                state.run_ids.push_back(run_id.to_string());
+                if state.run_ids.len() > self.max_records {
+                    let evicted = state.run_ids.pop_front().unwrap_or_default();
+                    state.runs.remove(&evicted);
+                }
+            }
+            state.runs.entry(run_id.to_string()).or_default().add(&record);
+        }
+        state.records.push_back(record.clone());
+        if state.records.len() > self.max_records {
+            state.records.pop_front();
+        }
+        record
+    }
+
+    pub fn get_records(&self, filter: &UsageFilter) -> Vec<UsageRecord> {
+        self.lock_state()
+            .records
+            .iter()
+            .filter(|record| filter.matches(record))
+            .cloned()
+            .collect()
+    }
+
+    pub fn totals(&self, filter: &UsageFilter) -> UsageTotals {
+        self.report(filter, None).totals
+    }
+
+    pub fn report(&self, filter: &UsageFilter, group_by: Option<UsageGroup>) -> UsageReport {
+        let mut report = UsageReport::default();
+        for record in self.get_records(filter) {
+            report.totals.add(&record);
+            if let Some(group) = group_by {
+                report
+                    .groups
+                    .entry(record.group_key(group))
+                    .or_default()
+                    .add(&record);
+            }
+        }
+        report
+    }
+
+    /// Fails when the user or the workflow run of the context exhausted its budget.
+    pub fn check_budget(&self, context: &ActionContext) -> Result<(), AgentError> {
+        let state = self.lock_state();
+        let none = UsageTotals::default();
+        if let Some(user_id) = context.get_user_id() {
+            if let Some(budget) = self.user_budgets.get(user_id) {
+                let totals = state
+                    .users
+                    .get(user_id)
+                    .filter(|usage| !self.is_period_over(usage))
+                    .map_or(&none, |usage| &usage.totals);
+                if budget.is_exceeded(totals) {
+                    return Err(AgentError::new(
+                        ErrorCode::BudgetExceeded,
+                        &format!("Budget of user {} exceeded", user_id),
+                    ));
+                }
+            }
+        }
+        if let (Some(workflow_id), Some(run_id)) = (context.get_workflow_id(), context.get_run_id())
+        {
+            if let Some(budget) = self.workflow_budgets.get(workflow_id) {
+                if budget.is_exceeded(state.runs.get(run_id).unwrap_or(&none)) {
+                    return Err(AgentError::new(
+                        ErrorCode::BudgetExceeded,
+                        &format!("Budget of workflow {} exceeded by run {}", workflow_id, run_id),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn is_period_over(&self, usage: &UserUsage) -> bool {
+        self.budget_period
+            .is_some_and(|period| usage.period_start.elapsed() >= period)
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, UsageState> {
+        self.state.lock().expect("Failed to lock usage records")
+    }
+}
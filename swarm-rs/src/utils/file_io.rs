@@ -21,10 +21,10 @@ pub fn create_parent_dirs<P: AsRef<Path>>(path: P) {
 }
 
 pub fn read_text_file<P: AsRef<Path>>(path: P) -> String {
-    let mut file = File::open(&path).expect(&format!("unable to read file {:?}", path.as_ref()));
+    let mut file = File::open(&path).unwrap_or_else(|_| panic!("unable to read file {:?}", path.as_ref()));
     let mut string_content = String::new();
     file.read_to_string(&mut string_content)
-        .expect(&format!("unable to read file content{:?}", path.as_ref()));
+        .unwrap_or_else(|_| panic!("unable to read file content{:?}", path.as_ref()));
     string_content
 }
 
@@ -34,12 +34,12 @@ pub fn write_text_file<P: AsRef<Path>>(path: P, content: &str) {
     write!(&mut f, "{}", content).expect("");
 }
 
-pub fn write_binary_file<P: AsRef<Path>>(path: P, buffer: &Vec<u8>) {
+pub fn write_binary_file<P: AsRef<Path>>(path: P, buffer: &[u8]) {
     create_parent_dirs(&path);
     let pathbuf = PathBuf::from(path.as_ref());
     let mut f = File::create(path).expect("No Error");
     f.write_all(buffer)
-        .expect(&format!("unable to read file content{:?}", pathbuf));
+        .unwrap_or_else(|_| panic!("unable to read file content{:?}", pathbuf));
 }
 
 pub fn read_binary_file<P: AsRef<Path>>(path: P) -> Result<Vec<u8>, String> {
@@ -61,21 +61,17 @@ pub fn remove_dir<P: AsRef<Path>>(path: P) {
 
 pub fn get_absolute_path<P: AsRef<Path>>(relative_path: P) -> Result<PathBuf, std::io::Error> {
     let path = PathBuf::from(relative_path.as_ref());
-    match fs::canonicalize(&path) {
-        Ok(path) => Ok(path),
-        Err(error) => Err(error),
-    }
+    fs::canonicalize(&path)
 }
 
 pub fn format_absolute_path_str(path: &PathBuf) -> Result<String, std::io::Error> {
-    match fs::canonicalize(&path) {
+    match fs::canonicalize(path) {
         Ok(path) => {
             let path = path.to_str().unwrap();
             let url = if path.starts_with("\\\\?\\") {
-                let formatted_url = path.replacen("\\\\?\\", "", 1);
-                formatted_url
+                path.replacen("\\\\?\\", "", 1)
             } else {
-                format!("{}", path)
+                path.to_string()
             };
             Ok(url)
         }
@@ -89,10 +85,9 @@ pub fn get_absolute_path_str(relative_path: &str) -> Result<String, std::io::Err
         Ok(path) => {
             let path = path.to_str().unwrap();
             let url = if path.starts_with("\\\\?\\") {
-                let formatted_url = path.replacen("\\\\?\\", "", 1);
-                formatted_url
+                path.replacen("\\\\?\\", "", 1)
             } else {
-                format!("{}", path)
+                path.to_string()
             };
             Ok(url)
         }
@@ -102,8 +97,7 @@ pub fn get_absolute_path_str(relative_path: &str) -> Result<String, std::io::Err
 
 pub fn format_os_path(path: &str) -> String {
     let url = if path.starts_with("\\\\?\\") {
-        let formatted_url = path.replacen("\\\\?\\", "", 1);
-        formatted_url
+        path.replacen("\\\\?\\", "", 1)
     } else {
         format!("file://{}", path)
     };
@@ -163,7 +157,7 @@ pub fn list_folders<P: AsRef<Path>>(root: P, max_depth: Option<usize>) -> Vec<Pa
     let files: Vec<PathBuf> = wd
         .min_depth(1)
         .into_iter()
-        .filter_entry(|e| is_not_hidden(e))
+        .filter_entry(is_not_hidden)
         .filter_map(|entry| entry.ok())
         // .filter_map(|entry| entry.metadata().ok())
         .filter(|entry| match entry.metadata().ok() {
@@ -193,7 +187,7 @@ pub fn list_files<P: AsRef<Path>>(root: P, max_depth: Option<usize>) -> Vec<Path
         .min_depth(1)
         // .max_depth(3)
         .into_iter()
-        .filter_entry(|e| is_not_hidden(e))
+        .filter_entry(is_not_hidden)
         .filter_map(|entry| entry.ok())
         // .filter_map(|entry| entry.metadata().ok())
         .filter(|entry| match entry.metadata().ok() {
@@ -212,7 +206,7 @@ pub fn list_files<P: AsRef<Path>>(root: P, max_depth: Option<usize>) -> Vec<Path
     files
 }
 
-pub fn get_created_date(path: &PathBuf) -> Option<String> {
+pub fn get_created_date(path: &Path) -> Option<String> {
     let metadata = path.metadata().unwrap();
     let time = metadata.created();
     match time {
@@ -221,7 +215,7 @@ pub fn get_created_date(path: &PathBuf) -> Option<String> {
     }
 }
 
-pub fn get_modified_date(path: &PathBuf) -> String {
+pub fn get_modified_date(path: &Path) -> String {
     let metadata = path.metadata().unwrap();
     let time = metadata.modified();
     // system_time_to_iso(time)
@@ -245,13 +239,13 @@ pub fn get_metadata<P: AsRef<Path>>(path: P) -> HashMap<String, String> {
     metadata
 }
 
-pub fn get_accessed_date(path: &PathBuf) -> String {
+pub fn get_accessed_date(path: &Path) -> String {
     let metadata = path.metadata().unwrap();
     let time = metadata.accessed().unwrap();
     time::system_time_to_iso(time)
 }
 
-pub fn get_size(path: &PathBuf) -> u64 {
+pub fn get_size(path: &Path) -> u64 {
     if let Ok(metadata) = path.metadata() {
         metadata.len()
     } else {
@@ -0,0 +1,81 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::error::{AgentError, ErrorCode};
+
+use super::json_io;
+
+/// Directory of JSON records named after their id.
+pub(crate) struct JsonDirectory {
+    base_dir: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl JsonDirectory {
+    pub fn new<P: AsRef<Path>>(base_dir: P) -> Self {
+        Self {
+            base_dir: base_dir.as_ref().to_path_buf(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn record_path(&self, id: &str) -> Result<PathBuf, AgentError> {
+        if id.is_empty() || id.contains(['/', '\\', '.']) {
+            return Err(AgentError::new(
+                ErrorCode::InvalidPayload,
+                &format!("Invalid id {}", id),
+            ));
+        }
+        Ok(self.base_dir.join(format!("{}.json", id)))
+    }
+
+    pub fn save<T: Serialize>(&self, id: &str, record: &T) -> Result<(), AgentError> {
+        let path = self.record_path(id)?;
+        let _guard = self.lock.lock().expect("Failed to lock store");
+        json_io::write(path, record)
+            .map_err(|_| AgentError::new(ErrorCode::Internal, &format!("Unable to save {}", id)))
+    }
+
+    pub fn load<T: DeserializeOwned>(&self, id: &str) -> Result<Option<T>, AgentError> {
+        let path = self.record_path(id)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        let _guard = self.lock.lock().expect("Failed to lock store");
+        json_io::load(path)
+            .map(Some)
+            .map_err(|message| AgentError::new(ErrorCode::Internal, &message))
+    }
+
+    pub fn remove(&self, id: &str) -> Result<(), AgentError> {
+        let path = self.record_path(id)?;
+        let _guard = self.lock.lock().expect("Failed to lock store");
+        match fs::remove_file(path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(AgentError::new(ErrorCode::Internal, &e.to_string())),
+        }
+    }
+
+    pub fn list<T: DeserializeOwned>(&self) -> Vec<T> {
+        let entries = match fs::read_dir(&self.base_dir) {
+            Ok(entries) => entries,
+            Err(_) => return vec![],
+        };
+        let _guard = self.lock.lock().expect("Failed to lock store");
+        entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "json")
+            })
+            .filter_map(|path| json_io::load(path).ok())
+            .collect()
+    }
+}
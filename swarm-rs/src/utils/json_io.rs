@@ -21,6 +21,7 @@ pub fn load<P: AsRef<Path>, T: DeserializeOwned>(file_path: P) -> Result<T, Stri
     Err(format!("unable to read file {:?}", file_path.as_ref()))
 }
 
+#[allow(clippy::result_unit_err)]
 pub fn write<P: AsRef<Path>, T: Serialize>(file: P, data: &T) -> Result<(), ()> {
     let res = serde_json::to_string_pretty(data);
     if let Ok(json_string) = res {
@@ -29,7 +30,7 @@ pub fn write<P: AsRef<Path>, T: Serialize>(file: P, data: &T) -> Result<(), ()>
             fs::create_dir_all(parent).expect("Unable to create db directory");
         }
         let mut json_file: File = File::create(file).expect("No Error");
-        if let Ok(_) = write!(&mut json_file, "{}", json_string) {
+        if write!(&mut json_file, "{}", json_string).is_ok() {
             Ok(())
         } else {
             Err(())
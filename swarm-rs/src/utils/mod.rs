@@ -1,3 +1,5 @@
 pub mod file_io;
+pub(crate) mod json_directory;
 pub mod json_io;
+pub mod pattern;
 pub mod time;
\ No newline at end of file
@@ -0,0 +1,35 @@
+/// Matches `value` against a pattern where `*` stands for any sequence of characters.
+pub fn wildcard_match(pattern: &str, value: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let value: Vec<char> = value.chars().collect();
+
+    let (mut p, mut v) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while v < value.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, v));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == value[v] {
+            p += 1;
+            v += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            v = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[test]
+pub fn test_wildcard_match() {
+    assert!(wildcard_match("*", "llm-summarizer"));
+    assert!(wildcard_match("llm-*", "llm-summarizer"));
+    assert!(wildcard_match("*-summarizer", "llm-summarizer"));
+    assert!(wildcard_match("l*m*r", "llm-summarizer"));
+    assert!(wildcard_match("searx_ng", "searx_ng"));
+    assert!(!wildcard_match("llm-*", "searx_ng"));
+    assert!(!wildcard_match("searx", "searx_ng"));
+}
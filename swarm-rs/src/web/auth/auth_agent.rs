@@ -31,14 +31,12 @@ impl AuthAgent {
 
     pub fn find_user_from_login(&self, login: &str) -> Option<UserInfo> {
         let users: Vec<UserInfo> = self.load_users();
-        let existing_user = users.into_iter().find(|u| &u.login == login);
-        existing_user
+        users.into_iter().find(|u| u.login == login)
     }
 
     pub fn find_user_from_id(&self, id: &str) -> Option<UserInfo> {
         let users: Vec<UserInfo> = self.load_users();
-        let existing_user = users.into_iter().find(|u| u.id == id);
-        existing_user
+        users.into_iter().find(|u| u.id == id)
     }
 
     fn load_users(&self) -> Vec<UserInfo> {
@@ -89,21 +87,18 @@ impl AuthAgent {
     pub async fn login(&self, credentials: UserCredentials) -> Result<UserAuth, String> {
         let existing_user = self.find_user_from_login(&credentials.login);
 
-        match existing_user {
-            Some(user) => {
-                if user.is_password_valid(&credentials.password) {
-                    let secret = &self.server_secret;
-                    let validity_in_hours = self.token_validity_in_days * 24;
-                    let token = user.new_token(secret, validity_in_hours);
-                    return Ok(UserAuth {
-                        user_id: user.id.to_string(),
-                        full_name: user.full_name.to_string(),
-                        roles: user.roles.clone(),
-                        token,
-                    });
-                }
+        if let Some(user) = existing_user {
+            if user.is_password_valid(&credentials.password) {
+                let secret = &self.server_secret;
+                let validity_in_hours = self.token_validity_in_days * 24;
+                let token = user.new_token(secret, validity_in_hours);
+                return Ok(UserAuth {
+                    user_id: user.id.to_string(),
+                    full_name: user.full_name.to_string(),
+                    roles: user.roles.clone(),
+                    token,
+                });
             }
-            _ => {}
         }
         Err("Auth error".to_string())
     }
@@ -112,19 +107,16 @@ impl AuthAgent {
     pub async fn refresh_token(&self, token: UserToken) -> Result<UserAuth, String> {
         if let Ok(auth) = token.check_token(&self.server_secret) {
             let existing_user = self.find_user_from_id(&auth.user_id);
-            match existing_user {
-                Some(user) => {
-                    let secret = &self.server_secret;
-                    let validity_in_hours = self.token_validity_in_days * 24;
-                    let token = user.new_token(secret, validity_in_hours);
-                    return Ok(UserAuth {
-                        user_id: user.id.to_string(),
-                        full_name: user.full_name.to_string(),
-                        roles: user.roles.clone(),
-                        token,
-                    });
-                }
-                _ => {}
+            if let Some(user) = existing_user {
+                let secret = &self.server_secret;
+                let validity_in_hours = self.token_validity_in_days * 24;
+                let token = user.new_token(secret, validity_in_hours);
+                return Ok(UserAuth {
+                    user_id: user.id.to_string(),
+                    full_name: user.full_name.to_string(),
+                    roles: user.roles.clone(),
+                    token,
+                });
             }
         }
         Err("Auth error".to_string())
@@ -135,42 +127,56 @@ impl AuthAgent {
         let token = UserToken::new(&update.token);
         if let Ok(auth) = token.check_token(&self.server_secret) {
             let existing_user = self.find_user_from_id(&auth.user_id);
-            match existing_user {
-                Some(mut user) => {
-                    user.password = hash_password(&update.new_password);
-                    self.save_user(&user);
-                    let secret = &self.server_secret;
-                    let validity_in_hours = self.token_validity_in_days * 24;
-                    let token = user.new_token(secret, validity_in_hours);
-                    return Ok(UserAuth {
-                        user_id: user.id.to_string(),
-                        full_name: user.full_name.to_string(),
-                        roles: user.roles.clone(),
-                        token,
-                    });
-                }
-                _ => {}
+            if let Some(mut user) = existing_user {
+                user.password = hash_password(&update.new_password);
+                self.save_user(&user);
+                let secret = &self.server_secret;
+                let validity_in_hours = self.token_validity_in_days * 24;
+                let token = user.new_token(secret, validity_in_hours);
+                return Ok(UserAuth {
+                    user_id: user.id.to_string(),
+                    full_name: user.full_name.to_string(),
+                    roles: user.roles.clone(),
+                    token,
+                });
             }
         }
         Err("Auth error".to_string())
     }
     
 
+    pub fn get_auth_info(&self, token: &str) -> Option<AuthInfo> {
+        UserToken::new(token).check_token(&self.server_secret).ok()
+    }
+
+    /// Roles allowed to execute the action, any user being allowed when empty.
+    pub fn get_required_roles(&self, action: &str) -> Vec<String> {
+        self.protected_actions
+            .get(action)
+            .cloned()
+            .unwrap_or_default()
+    }
+
     pub fn is_accessible(&self, token: &str, action: &str) -> bool {
         if let Some(roles) = self.protected_actions.get(action) {
-            let token = UserToken::new(token);
-            if let Ok(auth_info) = token.check_token(&self.server_secret) {
-                for role in roles {
-                    if auth_info.roles.contains(role) {
-                        return true;
-                    }
-                }
-            }
-            false
+            self.has_any_role(token, roles)
         } else {
             true
         }
     }
+
+    /// Tells whether the token is valid and grants one of the roles.
+    pub fn has_any_role(&self, token: &str, roles: &[String]) -> bool {
+        let token = UserToken::new(token);
+        if let Ok(auth_info) = token.check_token(&self.server_secret) {
+            for role in roles {
+                if auth_info.roles.contains(role) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -186,6 +192,7 @@ impl UserToken {
         }
     }
 
+    #[allow(clippy::result_unit_err)]
     pub fn check_token(&self, server_secret: &str) -> Result<AuthInfo, ()> {
         match verify_token(&self.token, server_secret) {
             Ok(claims) => Ok(AuthInfo {
@@ -228,7 +235,7 @@ pub struct NewUser {
 
 impl NewUser {
     pub fn new(login: &str, password: &str, full_name: &str, email: &str, roles: Vec<&str>) -> Self {
-        let email = if email.len() > 0 {
+        let email = if !email.is_empty() {
             Some(email.to_string())
         }else {
             None
@@ -9,7 +9,7 @@ pub struct TokenClaims {
 }
 
 
-pub fn create_token(user_id: &str, roles: &Vec<String>, secret: &str, validity_in_hours: i64) -> String {
+pub fn create_token(user_id: &str, roles: &[String], secret: &str, validity_in_hours: i64) -> String {
 
     let validity_duration = chrono::Duration::hours(validity_in_hours);
     // let validity_duration = chrono::Duration::seconds(10);
@@ -20,7 +20,7 @@ pub fn create_token(user_id: &str, roles: &Vec<String>, secret: &str, validity_i
 
     let claims = TokenClaims {
         user_id: user_id.to_owned(),
-        roles: roles.clone(),
+        roles: roles.to_vec(),
         exp: expiration as usize,
     };
 
@@ -2,4 +2,5 @@ mod spa_services;
 mod auth;
 pub mod web_swarm;
 mod request_headers;
+pub mod openapi;
 pub use auth::*;
\ No newline at end of file
@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    agent::Output,
+    error::AgentError,
+    schema::{ActionDescriptor, SchemaProbe, WithSchema},
+    swarm::Swarm,
+};
+
+use super::auth::AuthAgent;
+
+/// OpenAPI 3.1 document of the web API, generated from the action descriptors.
+///
+/// `POST /api/action` takes one of the actions with its payload. When the typed
+/// routes are mounted, each action also gets its `POST /api/agents/<agent>/<action>`
+/// path, taking the payload as body and answering its `Ok` value or an [`AgentError`].
+/// Actions protected by the [`AuthAgent`], or declaring their roles, list them in `x-roles`.
+pub fn openapi_document(swarm: &Swarm, typed_routes: bool) -> Value {
+    let mut components = Components::default();
+    let output_schema = components.register((&SchemaProbe::<Output>::new()).json_schema());
+    components.schemas.insert("Output".to_string(), output_schema);
+    let error_schema = components.register((&SchemaProbe::<AgentError>::new()).json_schema());
+    components.schemas.insert("AgentError".to_string(), error_schema);
+
+    let auth_agent = swarm.get_agent::<AuthAgent>("Auth");
+    let mut paths = Map::new();
+    let mut actions = vec![];
+    for agent in swarm.describe_agents() {
+        for descriptor in &agent.actions {
+            let action_id = format!("{}.{}", agent.agent_id, descriptor.name);
+            let mut roles = auth_agent
+                .map(|auth_agent| auth_agent.get_required_roles(&action_id))
+                .unwrap_or_default();
+            if auth_agent.is_some() {
+                for role in &descriptor.roles {
+                    if !roles.contains(role) {
+                        roles.push(role.to_string());
+                    }
+                }
+            }
+            let input_schema = components.register(descriptor.input_schema.clone());
+            actions.push(json!({
+                "type": "object",
+                "title": action_id,
+                "properties": {
+                    "id": { "const": action_id },
+                    "payload": input_schema,
+                    "timeout_ms": { "type": "integer", "minimum": 0 }
+                },
+                "required": ["id", "payload"]
+            }));
+            if typed_routes {
+                let output_schema = components.register(descriptor.output_schema.clone());
+                let path = format!("/api/agents/{}/{}", agent.agent_id, descriptor.name);
+                let operation =
+                    typed_operation(&action_id, descriptor, input_schema, output_schema, &roles);
+                paths.insert(path, json!({ "post": operation }));
+            }
+        }
+    }
+
+    paths.insert(
+        "/api/action".to_string(),
+        json!({
+            "post": {
+                "operationId": "execute_action",
+                "summary": "Executes an action",
+                "security": [{}, { "token": [] }],
+                "requestBody": {
+                    "required": true,
+                    "content": { "application/json": { "schema": { "oneOf": actions } } }
+                },
+                "responses": {
+                    "200": json_response("Output of the action", json!({ "$ref": "#/components/schemas/Output" })),
+                    "403": { "description": "Missing role" }
+                }
+            }
+        }),
+    );
+
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "swarm-rs",
+            "version": env!("CARGO_PKG_VERSION")
+        },
+        "paths": paths,
+        "components": {
+            "schemas": components.schemas,
+            "securitySchemes": {
+                "token": { "type": "apiKey", "in": "header", "name": "Token" }
+            }
+        }
+    })
+}
+
+fn typed_operation(
+    action_id: &str,
+    descriptor: &ActionDescriptor,
+    input_schema: Value,
+    output_schema: Value,
+    roles: &[String],
+) -> Value {
+    let mut operation = json!({
+        "operationId": action_id.replace(['.', '-'], "_"),
+        "summary": action_id,
+        "tags": [action_id.split('.').next().unwrap_or_default()],
+        "x-action-kind": descriptor.kind,
+        "requestBody": {
+            "required": true,
+            "content": { "application/json": { "schema": input_schema } }
+        },
+        "responses": {
+            "200": json_response("Output payload", output_schema),
+            "403": { "description": "Missing role" },
+            "default": json_response("Error of the action", json!({ "$ref": "#/components/schemas/AgentError" }))
+        }
+    });
+    if let Some(description) = &descriptor.description {
+        operation["description"] = json!(description);
+    }
+    if descriptor.deprecated {
+        operation["deprecated"] = json!(true);
+    }
+    if !roles.is_empty() {
+        operation["security"] = json!([{ "token": [] }]);
+        operation["x-roles"] = json!(roles);
+    }
+    operation
+}
+
+fn json_response(description: &str, schema: Value) -> Value {
+    json!({
+        "description": description,
+        "content": { "application/json": { "schema": schema } }
+    })
+}
+
+/// Schemas of the document components.
+#[derive(Default)]
+struct Components {
+    schemas: Map<String, Value>,
+    /// Source `$defs` entry of each component, telling apart the types sharing a name.
+    definitions: HashMap<String, Value>,
+}
+
+impl Components {
+    /// Moves the `$defs` of the schema to the components, any value being allowed without schema.
+    ///
+    /// A definition named like another component, such as `Output`, is renamed with a
+    /// numeric suffix unless it is the same definition.
+    fn register(&mut self, schema: Option<Value>) -> Value {
+        let mut schema = match schema {
+            Some(schema) => schema,
+            None => return json!({}),
+        };
+        let definitions = match schema
+            .as_object_mut()
+            .and_then(|schema| schema.remove("$defs"))
+        {
+            Some(Value::Object(definitions)) => definitions,
+            _ => Map::new(),
+        };
+
+        let mut names = HashMap::new();
+        let mut added = vec![];
+        for (name, definition) in definitions {
+            let mut component_name = name.to_string();
+            let mut suffix = 1;
+            loop {
+                match self.definitions.get(&component_name) {
+                    Some(existing) if *existing == definition => break,
+                    None if !self.schemas.contains_key(&component_name) => {
+                        self.schemas.insert(component_name.to_string(), Value::Null);
+                        self.definitions
+                            .insert(component_name.to_string(), definition.clone());
+                        added.push((component_name.to_string(), definition));
+                        break;
+                    }
+                    _ => {
+                        suffix += 1;
+                        component_name = format!("{}{}", name, suffix);
+                    }
+                }
+            }
+            names.insert(name, component_name);
+        }
+        for (component_name, mut definition) in added {
+            rewrite_refs(&mut definition, &names);
+            self.schemas.insert(component_name, definition);
+        }
+        rewrite_refs(&mut schema, &names);
+        schema
+    }
+}
+
+fn rewrite_refs(schema: &mut Value, names: &HashMap<String, String>) {
+    match schema {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                match value {
+                    Value::String(reference) if key == "$ref" => {
+                        if let Some(name) = reference.strip_prefix("#/$defs/") {
+                            let name = names.get(name).map(String::as_str).unwrap_or(name);
+                            *reference = format!("#/components/schemas/{}", name);
+                        }
+                    }
+                    _ => rewrite_refs(value, names),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| rewrite_refs(item, names)),
+        _ => {}
+    }
+}
@@ -6,6 +6,7 @@ use crate::logger::Logger;
 
 use super::request_headers::RequestHeaders;
 
+#[allow(clippy::upper_case_acronyms)]
 pub struct SPA {
     ui_dir: String,
     resources_dir: String
@@ -1,33 +1,87 @@
+use futures::{stream::BoxStream, StreamExt};
 use rocket::{
-    catch, catchers, fairing::AdHoc, http::Status, post, routes, serde::json::Json, Build, Rocket,
-    State,
+    catch, catchers,
+    fairing::AdHoc,
+    get,
+    http::Status,
+    post,
+    response::{
+        status,
+        stream::{Event, EventStream},
+    },
+    routes,
+    serde::json::Json,
+    Build, FromForm, Rocket, State,
 };
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use crate::{
-    agent::{Action, Output}, logger::Logger, prelude::Swarm
+    agent::{Action, Output}, error::{AgentError, ErrorCode}, logger::Logger, prelude::Swarm, swarm::ActionEvent,
+    usage::{UsageFilter, UsageGroup, UsageReport},
+    workflow::{Approval, ApprovalDecision, ApprovalOutcome, RunSummary, WorkflowRun},
 };
 
 use super::{
-    auth::{AuthAgent, AuthHeaders}, request_headers::RequestHeaders, spa_services::{self, SPA}
+    auth::{AuthAgent, AuthHeaders}, openapi::openapi_document, request_headers::RequestHeaders,
+    spa_services::{self, SPA},
 };
 
+#[derive(Debug, Clone, Default)]
+pub struct WebOptions {
+    typed_routes: bool,
+}
+
+impl WebOptions {
+    /// Mounts `POST /api/agents/<agent>/<action>`, taking the action payload as body.
+    pub fn with_typed_routes(mut self) -> Self {
+        self.typed_routes = true;
+        self
+    }
+}
+
 pub struct WebSwarm {}
 
 impl WebSwarm {
     pub fn serve(swarm: Swarm) -> Rocket<Build> {
+        Self::serve_with_options(swarm, WebOptions::default())
+    }
+
+    pub fn serve_with_options(swarm: Swarm, options: WebOptions) -> Rocket<Build> {
         let figment = rocket::Config::figment().merge(("address", "0.0.0.0"));
         let spa_settings = SPA::default();
         let logger = Logger::new("logs", "webswarm");
+        let typed_routes = if options.typed_routes {
+            routes![execute_typed_action]
+        } else {
+            routes![]
+        };
         rocket::custom(figment)
             .manage(spa_settings)
             .manage(swarm)
             .manage(logger)
+            .manage(options)
             .mount(
                 "/",
                 routes![spa_services::app_index, spa_services::app_resources],
             )
             .register("/", catchers![forbidded_catcher])
-            .mount("/api", routes![execute_action])
+            .mount(
+                "/api",
+                routes![
+                    execute_action,
+                    stream_action,
+                    list_runs,
+                    get_run,
+                    resume_run,
+                    list_approvals,
+                    get_approval,
+                    decide_approval,
+                    get_usage,
+                    get_openapi
+                ],
+            )
+            .mount("/api", typed_routes)
             .attach(AdHoc::on_shutdown("Shutdown Printer", |_| {
                 Box::pin(async move {
                     println!("...shutdown has commenced!");
@@ -47,13 +101,8 @@ pub async fn execute_action(
 ) -> Result<Json<Output>, Status> {
     logger.info("ACTION", &headers);
 
-    let accessible = if let Some(auth_agent) = agents_swarm.get_agent::<AuthAgent>("Auth") {
-        let action_id = action.get_id();
-        auth_agent.is_accessible(&auth_headers.token, action_id)
-    }else {
-        true
-    };
-    if accessible {
+    if is_accessible(agents_swarm, &auth_headers.token, action.get_id()) {
+        let action = with_user(agents_swarm, &auth_headers.token, action.into_inner());
         let output = agents_swarm.execute_action(&action).await;
         Ok(Json(output))
     }else {
@@ -62,6 +111,320 @@ pub async fn execute_action(
     
 }
 
+/// Executes the action with the body as payload, answering its payload or its
+/// error with the HTTP status matching the error code.
+#[post("/agents/<agent>/<action>", data = "<payload>")]
+pub async fn execute_typed_action(
+    agent: &str,
+    action: &str,
+    auth_headers: AuthHeaders,
+    payload: Json<Value>,
+    agents_swarm: &State<Swarm>,
+    headers: RequestHeaders,
+    logger: &State<Logger>,
+) -> Result<Result<Json<Value>, status::Custom<Json<AgentError>>>, Status> {
+    logger.info("ACTION", &headers);
+
+    let action_id = format!("{}.{}", agent, action);
+    if !is_accessible(agents_swarm, &auth_headers.token, &action_id) {
+        return Err(Status::Forbidden);
+    }
+    let action = with_user(
+        agents_swarm,
+        &auth_headers.token,
+        Action::new(&action_id, payload.into_inner()),
+    );
+    let output = agents_swarm.execute_action(&action).await;
+    match output.get_error() {
+        None => Ok(Ok(Json(output.get_value().clone()))),
+        Some(error) => Ok(Err(status::Custom(
+            error_status(&error.code),
+            Json(error.clone()),
+        ))),
+    }
+}
+
+fn error_status(code: &ErrorCode) -> Status {
+    match code {
+        ErrorCode::InvalidPayload => Status::BadRequest,
+        ErrorCode::AgentNotFound | ErrorCode::UnknownAction => Status::NotFound,
+        ErrorCode::Unauthorized => Status::Unauthorized,
+        ErrorCode::Forbidden => Status::Forbidden,
+        ErrorCode::BudgetExceeded | ErrorCode::RateLimited => Status::TooManyRequests,
+        ErrorCode::CircuitOpen => Status::ServiceUnavailable,
+        ErrorCode::Timeout => Status::GatewayTimeout,
+        _ => Status::InternalServerError,
+    }
+}
+
+/// OpenAPI document of the API, listing the typed routes when mounted.
+#[get("/openapi.json")]
+pub async fn get_openapi(agents_swarm: &State<Swarm>, options: &State<WebOptions>) -> Json<Value> {
+    Json(openapi_document(agents_swarm, options.typed_routes))
+}
+
+/// Executes the action and sends its `progress` events, then its `output`, as Server-Sent Events.
+#[post("/action/stream", data = "<action>")]
+pub async fn stream_action<'r>(
+    auth_headers: AuthHeaders,
+    action: Json<Action>,
+    agents_swarm: &'r State<Swarm>,
+    headers: RequestHeaders,
+    logger: &State<Logger>,
+) -> Result<EventStream<BoxStream<'r, Event>>, Status> {
+    logger.info("STREAM", &headers);
+
+    if is_accessible(agents_swarm, &auth_headers.token, action.get_id()) {
+        let action = with_user(agents_swarm, &auth_headers.token, action.into_inner());
+        let events = agents_swarm
+            .execute_stream(&action)
+            .map(|event| {
+                let name = match &event {
+                    ActionEvent::Progress(_) => "progress",
+                    ActionEvent::Output(_) => "output",
+                };
+                Event::json(&event).event(name)
+            })
+            .boxed();
+        Ok(EventStream::from(events))
+    } else {
+        Err(Status::Forbidden)
+    }
+}
+
+/// Runs of the workflows whose `run` action is accessible.
+#[get("/runs")]
+pub async fn list_runs(
+    auth_headers: AuthHeaders,
+    agents_swarm: &State<Swarm>,
+) -> Result<Json<Vec<RunSummary>>, Status> {
+    let store = agents_swarm.get_checkpoint_store().ok_or(Status::NotFound)?;
+    let runs = store.list().map_err(|_| Status::InternalServerError)?;
+    Ok(Json(
+        runs.iter()
+            .filter(|run| {
+                let action_id = format!("{}.run", run.workflow_id);
+                is_accessible(agents_swarm, &auth_headers.token, &action_id)
+            })
+            .map(|run| run.summary())
+            .collect(),
+    ))
+}
+
+#[get("/runs/<run_id>")]
+pub async fn get_run(
+    run_id: &str,
+    auth_headers: AuthHeaders,
+    agents_swarm: &State<Swarm>,
+) -> Result<Json<WorkflowRun>, Status> {
+    let run = load_run(agents_swarm, run_id)?;
+    let action_id = format!("{}.run", run.workflow_id);
+    if is_accessible(agents_swarm, &auth_headers.token, &action_id) {
+        Ok(Json(run))
+    } else {
+        Err(Status::Forbidden)
+    }
+}
+
+#[post("/runs/<run_id>/resume")]
+pub async fn resume_run(
+    run_id: &str,
+    auth_headers: AuthHeaders,
+    agents_swarm: &State<Swarm>,
+    headers: RequestHeaders,
+    logger: &State<Logger>,
+) -> Result<Json<Output>, Status> {
+    logger.info("RESUME", &headers);
+
+    let run = load_run(agents_swarm, run_id)?;
+    let action_id = format!("{}.resume", run.workflow_id);
+    if is_accessible(agents_swarm, &auth_headers.token, &action_id) {
+        let output = agents_swarm.execute(&action_id, &run.run_id).await;
+        Ok(Json(output))
+    } else {
+        Err(Status::Forbidden)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ApprovalResponse {
+    pub outcome: ApprovalOutcome,
+    pub comment: Option<String>,
+}
+
+/// Pending approvals the user is allowed to decide.
+#[get("/approvals")]
+pub async fn list_approvals(
+    auth_headers: AuthHeaders,
+    agents_swarm: &State<Swarm>,
+) -> Result<Json<Vec<Approval>>, Status> {
+    let approvals = agents_swarm.get_approvals().ok_or(Status::NotFound)?;
+    let pending = approvals
+        .list_pending()
+        .map_err(|_| Status::InternalServerError)?;
+    Ok(Json(
+        pending
+            .into_iter()
+            .filter(|approval| check_approver(agents_swarm, &auth_headers.token, approval).is_ok())
+            .collect(),
+    ))
+}
+
+#[get("/approvals/<approval_id>")]
+pub async fn get_approval(
+    approval_id: &str,
+    auth_headers: AuthHeaders,
+    agents_swarm: &State<Swarm>,
+) -> Result<Json<Approval>, Status> {
+    let approval = load_approval(agents_swarm, approval_id)?;
+    check_approver(agents_swarm, &auth_headers.token, &approval)?;
+    Ok(Json(approval))
+}
+
+/// Records the decision, resuming the workflow waiting for it. The workflows
+/// interrupted meanwhile get the decision once resumed through `/runs/<run_id>/resume`.
+#[post("/approvals/<approval_id>", data = "<response>")]
+pub async fn decide_approval(
+    approval_id: &str,
+    response: Json<ApprovalResponse>,
+    auth_headers: AuthHeaders,
+    agents_swarm: &State<Swarm>,
+    headers: RequestHeaders,
+    logger: &State<Logger>,
+) -> Result<Json<Approval>, Status> {
+    logger.info("APPROVAL", &headers);
+
+    let approval = load_approval(agents_swarm, approval_id)?;
+    let approver = check_approver(agents_swarm, &auth_headers.token, &approval)?;
+    let mut decision = ApprovalDecision::new(response.outcome);
+    decision.approver = approver;
+    decision.comment = response.comment.clone();
+    let approvals = agents_swarm.get_approvals().ok_or(Status::NotFound)?;
+    approvals
+        .decide(approval_id, decision)
+        .map(Json)
+        .map_err(|_| Status::Conflict)
+}
+
+#[derive(FromForm)]
+pub struct UsageQuery {
+    pub action_id: Option<String>,
+    pub agent_id: Option<String>,
+    pub workflow_id: Option<String>,
+    pub run_id: Option<String>,
+    pub user_id: Option<String>,
+    pub model: Option<String>,
+    /// One of `action`, `agent`, `workflow`, `run`, `user` or `model`.
+    pub group_by: Option<String>,
+}
+
+/// Token usage and cost totals of the LLM calls. Authenticated users only
+/// get the usage of their own calls.
+#[get("/usage?<query..>")]
+pub async fn get_usage(
+    query: UsageQuery,
+    auth_headers: AuthHeaders,
+    agents_swarm: &State<Swarm>,
+) -> Result<Json<UsageReport>, Status> {
+    let group_by = match &query.group_by {
+        Some(group_by) => Some(
+            serde_json::from_value::<UsageGroup>(serde_json::Value::String(group_by.to_string()))
+                .map_err(|_| Status::BadRequest)?,
+        ),
+        None => None,
+    };
+    let mut filter = UsageFilter {
+        action_id: query.action_id,
+        agent_id: query.agent_id,
+        workflow_id: query.workflow_id,
+        run_id: query.run_id,
+        user_id: query.user_id,
+        model: query.model,
+    };
+    if let Some(auth_agent) = agents_swarm.get_agent::<AuthAgent>("Auth") {
+        let auth_info = auth_agent
+            .get_auth_info(&auth_headers.token)
+            .ok_or(Status::Forbidden)?;
+        filter.user_id = Some(auth_info.user_id);
+    }
+    Ok(Json(
+        agents_swarm.get_usage_tracker().report(&filter, group_by),
+    ))
+}
+
+/// Attributes the action to the authenticated user and their roles, if any.
+fn with_user(swarm: &Swarm, token: &str, action: Action) -> Action {
+    let auth_info = swarm
+        .get_agent::<AuthAgent>("Auth")
+        .and_then(|auth_agent| auth_agent.get_auth_info(token));
+    match auth_info {
+        Some(auth_info) => action
+            .with_user(&auth_info.user_id)
+            .with_roles(&auth_info.roles),
+        None => action,
+    }
+}
+
+fn load_approval(swarm: &Swarm, approval_id: &str) -> Result<Approval, Status> {
+    let approvals = swarm.get_approvals().ok_or(Status::NotFound)?;
+    match approvals.get(approval_id) {
+        Ok(Some(approval)) => Ok(approval),
+        Ok(None) => Err(Status::NotFound),
+        Err(_) => Err(Status::BadRequest),
+    }
+}
+
+/// Id of the user allowed to decide the approval, who must be authenticated once an
+/// `AuthAgent` is registered.
+fn check_approver(
+    swarm: &Swarm,
+    token: &str,
+    approval: &Approval,
+) -> Result<Option<String>, Status> {
+    let auth_agent = match swarm.get_agent::<AuthAgent>("Auth") {
+        Some(auth_agent) => auth_agent,
+        None => return Ok(None),
+    };
+    match auth_agent.get_auth_info(token) {
+        Some(auth_info)
+            if approval.roles.is_empty()
+                || auth_info.roles.iter().any(|role| approval.roles.contains(role)) =>
+        {
+            Ok(Some(auth_info.user_id))
+        }
+        _ => Err(Status::Forbidden),
+    }
+}
+
+fn load_run(swarm: &Swarm, run_id: &str) -> Result<WorkflowRun, Status> {
+    let store = swarm.get_checkpoint_store().ok_or(Status::NotFound)?;
+    match store.load(run_id) {
+        Ok(Some(run)) => Ok(run),
+        Ok(None) => Err(Status::NotFound),
+        Err(_) => Err(Status::BadRequest),
+    }
+}
+
+/// Checks the roles declared by `#[agent_action(roles = [...])]` as the swarm
+/// does when dispatching, then the roles of `AuthAgent.protected_actions`.
+/// Aliases are checked as the action they name, so they do not get around its protection.
+fn is_accessible(swarm: &Swarm, token: &str, action_id: &str) -> bool {
+    let action = with_user(swarm, token, Action::new(action_id, Value::Null));
+    if swarm.check_roles(&action).is_err() {
+        return false;
+    }
+    if let Some(auth_agent) = swarm.get_agent::<AuthAgent>("Auth") {
+        let canonical_id = match (swarm.describe_action(action_id), action_id.split_once('.')) {
+            (Some(descriptor), Some((agent_id, _))) => format!("{}.{}", agent_id, descriptor.name),
+            _ => action_id.to_string(),
+        };
+        auth_agent.is_accessible(token, action_id)
+            && auth_agent.is_accessible(token, &canonical_id)
+    } else {
+        true
+    }
+}
+
 #[catch(403)]
 pub fn forbidded_catcher(req: &rocket::Request) -> String {
     format!("Forbidden access: {}", req.uri())
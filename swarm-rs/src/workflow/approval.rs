@@ -0,0 +1,393 @@
+use std::{collections::HashMap, path::Path, sync::Mutex, time::Duration};
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::oneshot;
+
+use crate::{
+    context::ActionContext,
+    error::{AgentError, ErrorCode},
+    utils::{
+        json_directory::JsonDirectory,
+        time::{current_time_iso, today_with_format},
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalOutcome {
+    Approved,
+    Rejected,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ApprovalStatus {
+    Pending,
+    Approved,
+    Rejected,
+    /// Nobody decided in time, the default outcome was applied.
+    TimedOut,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalDecision {
+    pub outcome: ApprovalOutcome,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approver: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    #[serde(default)]
+    pub decided_at: String,
+}
+
+impl ApprovalDecision {
+    pub fn new(outcome: ApprovalOutcome) -> Self {
+        Self {
+            outcome,
+            approver: None,
+            comment: None,
+            decided_at: current_time_iso(),
+        }
+    }
+
+    pub fn approved() -> Self {
+        Self::new(ApprovalOutcome::Approved)
+    }
+
+    pub fn rejected() -> Self {
+        Self::new(ApprovalOutcome::Rejected)
+    }
+
+    pub fn with_approver(mut self, approver: &str) -> Self {
+        self.approver = Some(approver.to_string());
+        self
+    }
+
+    pub fn with_comment(mut self, comment: &str) -> Self {
+        self.comment = Some(comment.to_string());
+        self
+    }
+
+    pub fn is_approved(&self) -> bool {
+        self.outcome == ApprovalOutcome::Approved
+    }
+}
+
+/// Persisted approval request, pending until someone decides or it expires.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Approval {
+    pub approval_id: String,
+    pub title: String,
+    #[serde(default)]
+    pub details: Value,
+    /// Roles allowed to decide, anybody when empty.
+    #[serde(default)]
+    pub roles: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workflow_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    pub status: ApprovalStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_outcome: Option<ApprovalOutcome>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decision: Option<ApprovalDecision>,
+    pub created_at: String,
+}
+
+impl Approval {
+    pub fn new(title: &str) -> Self {
+        let suffix: u32 = rand::thread_rng().gen();
+        Self {
+            approval_id: format!(
+                "approval-{}-{:08x}",
+                today_with_format("%Y%m%d%H%M%S%3f"),
+                suffix
+            ),
+            title: title.to_string(),
+            details: Value::Null,
+            roles: vec![],
+            workflow_id: None,
+            run_id: None,
+            status: ApprovalStatus::Pending,
+            timeout_ms: None,
+            default_outcome: None,
+            expires_at: None,
+            decision: None,
+            created_at: current_time_iso(),
+        }
+    }
+
+    /// Stable id, so that a resumed workflow finds the approval it was waiting for.
+    pub fn with_id(mut self, approval_id: &str) -> Self {
+        self.approval_id = approval_id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self
+    }
+
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = details;
+        self
+    }
+
+    pub fn with_roles(mut self, roles: &[String]) -> Self {
+        self.roles = roles.to_vec();
+        self
+    }
+
+    pub fn with_run(mut self, workflow_id: &str, run_id: Option<&str>) -> Self {
+        self.workflow_id = Some(workflow_id.to_string());
+        self.run_id = run_id.map(|run_id| run_id.to_string());
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout_ms = Some(timeout.as_millis() as u64);
+        self
+    }
+
+    pub fn with_default_outcome(mut self, outcome: ApprovalOutcome) -> Self {
+        self.default_outcome = Some(outcome);
+        self
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.status == ApprovalStatus::Pending
+    }
+
+    fn remaining_time(&self) -> Option<Duration> {
+        let expires_at = DateTime::parse_from_rfc3339(self.expires_at.as_ref()?).ok()?;
+        let remaining = expires_at.with_timezone(&Utc) - Utc::now();
+        Some(remaining.to_std().unwrap_or_default())
+    }
+
+    fn apply(&mut self, decision: ApprovalDecision, timed_out: bool) {
+        self.status = match (timed_out, decision.outcome) {
+            (true, _) => ApprovalStatus::TimedOut,
+            (false, ApprovalOutcome::Approved) => ApprovalStatus::Approved,
+            (false, ApprovalOutcome::Rejected) => ApprovalStatus::Rejected,
+        };
+        self.decision = Some(decision);
+    }
+}
+
+pub trait ApprovalStore: Send + Sync {
+    fn save(&self, approval: &Approval) -> Result<(), AgentError>;
+
+    fn load(&self, approval_id: &str) -> Result<Option<Approval>, AgentError>;
+
+    /// Approvals sorted by creation date.
+    fn list(&self) -> Result<Vec<Approval>, AgentError>;
+}
+
+/// Stores each approval as a JSON file named after its id.
+pub struct FileApprovalStore {
+    directory: JsonDirectory,
+}
+
+impl FileApprovalStore {
+    pub fn new<P: AsRef<Path>>(base_dir: P) -> Self {
+        Self {
+            directory: JsonDirectory::new(base_dir),
+        }
+    }
+}
+
+impl ApprovalStore for FileApprovalStore {
+    fn save(&self, approval: &Approval) -> Result<(), AgentError> {
+        self.directory.save(&approval.approval_id, approval)
+    }
+
+    fn load(&self, approval_id: &str) -> Result<Option<Approval>, AgentError> {
+        self.directory.load(approval_id)
+    }
+
+    fn list(&self) -> Result<Vec<Approval>, AgentError> {
+        let mut approvals: Vec<Approval> = self.directory.list();
+        approvals.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        Ok(approvals)
+    }
+}
+
+/// Pending approvals of a swarm and the actions awaiting them.
+///
+/// The decisions are persisted first, so a workflow interrupted while waiting
+/// finds its decision when it is resumed.
+pub struct Approvals {
+    store: Box<dyn ApprovalStore>,
+    default_timeout: Option<Duration>,
+    default_outcome: ApprovalOutcome,
+    waiters: Mutex<HashMap<String, oneshot::Sender<ApprovalDecision>>>,
+}
+
+impl Approvals {
+    pub fn new<T: ApprovalStore + 'static>(store: T) -> Self {
+        Self {
+            store: Box::new(store),
+            default_timeout: None,
+            default_outcome: ApprovalOutcome::Rejected,
+            waiters: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Timeout of the approvals that do not define their own one.
+    pub fn with_default_timeout(mut self, timeout: Duration) -> Self {
+        self.default_timeout = Some(timeout);
+        self
+    }
+
+    /// Outcome of the timed out approvals that do not define their own one, rejected by default.
+    pub fn with_default_outcome(mut self, outcome: ApprovalOutcome) -> Self {
+        self.default_outcome = outcome;
+        self
+    }
+
+    pub fn get(&self, approval_id: &str) -> Result<Option<Approval>, AgentError> {
+        self.store.load(approval_id)
+    }
+
+    pub fn list(&self) -> Result<Vec<Approval>, AgentError> {
+        self.store.list()
+    }
+
+    pub fn list_pending(&self) -> Result<Vec<Approval>, AgentError> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .filter(|approval| approval.is_pending())
+            .collect())
+    }
+
+    /// Persists the approval as pending and waits for its decision, the
+    /// timeout or the cancellation of the current action.
+    ///
+    /// An approval already known by its id is not created again, and its
+    /// decision is returned right away when it has one. An approval can only
+    /// be awaited by one action at a time.
+    pub async fn request(&self, approval: Approval) -> Result<ApprovalDecision, AgentError> {
+        // Registered under the lock taken by `decide`, so that no decision gets lost
+        let (approval, receiver) = {
+            let mut waiters = self.waiters.lock().expect("Failed to lock approvals");
+            let approval = match self.store.load(&approval.approval_id)? {
+                Some(existing) => existing,
+                None => {
+                    let mut approval = approval;
+                    let timeout_ms = approval.timeout_ms.or(self
+                        .default_timeout
+                        .map(|timeout| timeout.as_millis() as u64));
+                    approval.expires_at = timeout_ms.map(|timeout_ms| {
+                        (Utc::now() + chrono::Duration::milliseconds(timeout_ms as i64))
+                            .to_rfc3339()
+                    });
+                    self.store.save(&approval)?;
+                    approval
+                }
+            };
+            if let Some(decision) = &approval.decision {
+                return Ok(decision.clone());
+            }
+            if waiters
+                .get(&approval.approval_id)
+                .is_some_and(|waiter| !waiter.is_closed())
+            {
+                return Err(AgentError::new(
+                    ErrorCode::InvalidPayload,
+                    &format!("Approval {} is already awaited", approval.approval_id),
+                ));
+            }
+            let (sender, receiver) = oneshot::channel();
+            waiters.insert(approval.approval_id.to_string(), sender);
+            (approval, receiver)
+        };
+
+        let cancellation = ActionContext::current()
+            .map(|context| context.cancellation_token().clone())
+            .unwrap_or_default();
+        let expiration = async {
+            match approval.remaining_time() {
+                Some(remaining) => tokio::time::sleep(remaining).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            biased;
+            _ = cancellation.cancelled() => {
+                self.remove_waiter(&approval.approval_id);
+                Err(AgentError::new(ErrorCode::Cancelled, "Approval cancelled"))
+            }
+            decision = receiver => decision.map_err(|_| {
+                AgentError::new(ErrorCode::Internal, "Approval waiter dropped")
+            }),
+            _ = expiration => {
+                let outcome = approval.default_outcome.unwrap_or(self.default_outcome);
+                let decision = ApprovalDecision::new(outcome).with_comment("Timed out");
+                match self.complete(&approval.approval_id, decision.clone(), true) {
+                    Ok(approval) => Ok(approval.decision.unwrap_or(decision)),
+                    // Decided meanwhile
+                    Err(_) => self
+                        .get(&approval.approval_id)?
+                        .and_then(|approval| approval.decision)
+                        .ok_or_else(|| AgentError::new(ErrorCode::Internal, "Approval lost")),
+                }
+            }
+        }
+    }
+
+    /// Records the decision of a pending approval and wakes up the action awaiting it.
+    pub fn decide(
+        &self,
+        approval_id: &str,
+        decision: ApprovalDecision,
+    ) -> Result<Approval, AgentError> {
+        self.complete(approval_id, decision, false)
+    }
+
+    fn complete(
+        &self,
+        approval_id: &str,
+        decision: ApprovalDecision,
+        timed_out: bool,
+    ) -> Result<Approval, AgentError> {
+        let mut waiters = self.waiters.lock().expect("Failed to lock approvals");
+        let mut approval = self.store.load(approval_id)?.ok_or_else(|| {
+            AgentError::new(
+                ErrorCode::InvalidPayload,
+                &format!("Unknown approval {}", approval_id),
+            )
+        })?;
+        if !approval.is_pending() {
+            return Err(AgentError::new(
+                ErrorCode::InvalidPayload,
+                &format!("Approval {} is already decided", approval_id),
+            ));
+        }
+        approval.apply(decision.clone(), timed_out);
+        self.store.save(&approval)?;
+        if let Some(waiter) = waiters.remove(approval_id) {
+            let _ = waiter.send(decision);
+        }
+        Ok(approval)
+    }
+
+    fn remove_waiter(&self, approval_id: &str) -> Option<oneshot::Sender<ApprovalDecision>> {
+        self.waiters
+            .lock()
+            .expect("Failed to lock approvals")
+            .remove(approval_id)
+    }
+}
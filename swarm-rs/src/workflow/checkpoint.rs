@@ -0,0 +1,158 @@
+use std::path::Path;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    agent::Output,
+    error::AgentError,
+    utils::{
+        json_directory::JsonDirectory,
+        time::{current_time_iso, today_with_format},
+    },
+};
+
+/// Unique id of a new run of the workflow.
+pub(crate) fn new_run_id(workflow_id: &str) -> String {
+    let suffix: u32 = rand::thread_rng().gen();
+    format!(
+        "{}-{}-{:08x}",
+        workflow_id,
+        today_with_format("%Y%m%d%H%M%S%3f"),
+        suffix
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RunStatus {
+    /// Started and not finished, either still running or interrupted.
+    Running,
+    Completed,
+    Failed,
+}
+
+/// Completed action step of a workflow run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Position of the step, with the iteration index inside loops, e.g. `pages[2].summarize`.
+    pub step_path: String,
+    pub action: String,
+    pub payload: Value,
+    pub output: Output,
+    /// Workflow state once the step completed.
+    pub state: Value,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowRun {
+    pub run_id: String,
+    pub workflow_id: String,
+    pub status: RunStatus,
+    pub input: Value,
+    #[serde(default)]
+    pub checkpoints: Vec<Checkpoint>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<AgentError>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl WorkflowRun {
+    pub fn new(workflow_id: &str, input: Value) -> Self {
+        let run_id = new_run_id(workflow_id);
+        let now = current_time_iso();
+        Self {
+            run_id,
+            workflow_id: workflow_id.to_string(),
+            status: RunStatus::Running,
+            input,
+            checkpoints: vec![],
+            output: None,
+            error: None,
+            created_at: now.to_string(),
+            updated_at: now,
+        }
+    }
+
+    /// Successful checkpoint recorded for a step, reused when the run resumes.
+    pub fn get_checkpoint(&self, step_path: &str) -> Option<&Checkpoint> {
+        self.checkpoints
+            .iter()
+            .rev()
+            .find(|checkpoint| checkpoint.step_path == step_path && checkpoint.output.is_success())
+    }
+
+    pub fn last_checkpoint(&self) -> Option<&Checkpoint> {
+        self.checkpoints.last()
+    }
+
+    pub fn summary(&self) -> RunSummary {
+        RunSummary {
+            run_id: self.run_id.to_string(),
+            workflow_id: self.workflow_id.to_string(),
+            status: self.status,
+            checkpoints: self.checkpoints.len(),
+            last_step: self
+                .last_checkpoint()
+                .map(|checkpoint| checkpoint.step_path.to_string()),
+            created_at: self.created_at.to_string(),
+            updated_at: self.updated_at.to_string(),
+        }
+    }
+}
+
+/// Run without its checkpoints, as listed by the web layer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunSummary {
+    pub run_id: String,
+    pub workflow_id: String,
+    pub status: RunStatus,
+    pub checkpoints: usize,
+    pub last_step: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Persistence of the workflow runs, shared by all the workflows of a [`crate::swarm::Swarm`].
+pub trait CheckpointStore: Send + Sync {
+    fn save(&self, run: &WorkflowRun) -> Result<(), AgentError>;
+
+    fn load(&self, run_id: &str) -> Result<Option<WorkflowRun>, AgentError>;
+
+    /// Runs sorted by creation date.
+    fn list(&self) -> Result<Vec<WorkflowRun>, AgentError>;
+}
+
+/// Stores each run as a JSON file named after its run id.
+pub struct FileCheckpointStore {
+    directory: JsonDirectory,
+}
+
+impl FileCheckpointStore {
+    pub fn new<P: AsRef<Path>>(base_dir: P) -> Self {
+        Self {
+            directory: JsonDirectory::new(base_dir),
+        }
+    }
+}
+
+impl CheckpointStore for FileCheckpointStore {
+    fn save(&self, run: &WorkflowRun) -> Result<(), AgentError> {
+        self.directory.save(&run.run_id, run)
+    }
+
+    fn load(&self, run_id: &str) -> Result<Option<WorkflowRun>, AgentError> {
+        self.directory.load(run_id)
+    }
+
+    fn list(&self) -> Result<Vec<WorkflowRun>, AgentError> {
+        let mut runs: Vec<WorkflowRun> = self.directory.list();
+        runs.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        Ok(runs)
+    }
+}
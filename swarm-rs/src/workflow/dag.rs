@@ -0,0 +1,293 @@
+use std::{
+    any::Any,
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    path::Path,
+    time::Instant,
+};
+
+use async_trait::async_trait;
+use futures::{stream::FuturesUnordered, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    agent::{Action, Agent, Output},
+    context::{emit_progress, scope_workflow_run, ActionContext},
+    error::{AgentError, ErrorCode},
+    schema::{ActionDescriptor, ActionKind, SchemaProbe, WithSchema},
+    swarm::Swarm,
+    utils::time::current_time_iso,
+};
+
+use super::{
+    expression::{assign, resolve, select},
+    workflow_definition::load_definition,
+};
+
+/// Graph of actions where independent branches run concurrently.
+///
+/// Node inputs are mappings resolved against `{ "input": <payload>, "nodes": { <node id>: <output> } }`,
+/// and edges copy an upstream output, or part of it, into a downstream input field.
+/// A node runs once all its upstream nodes succeeded, and is skipped otherwise.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dag {
+    pub id: String,
+    pub nodes: Vec<DagNode>,
+    #[serde(default)]
+    pub edges: Vec<DagEdge>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrency: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DagNode {
+    pub id: String,
+    pub action: String,
+    #[serde(default)]
+    pub input: Value,
+    /// Ordering only dependencies, in addition to the edges.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DagEdge {
+    pub from: String,
+    pub to: String,
+    /// JSONPath applied to the upstream output, the whole output by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// Dot separated field of the downstream input, the whole input by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DagNodeReport {
+    pub action: String,
+    pub output: Output,
+    pub started_at: String,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DagReport {
+    pub dag_id: String,
+    pub run_id: String,
+    pub success: bool,
+    pub nodes: BTreeMap<String, DagNodeReport>,
+    pub skipped: Vec<String>,
+    pub started_at: String,
+    pub duration_ms: u64,
+}
+
+impl DagReport {
+    pub fn get_output(&self, node_id: &str) -> Option<&Output> {
+        self.nodes.get(node_id).map(|report| &report.output)
+    }
+}
+
+impl Dag {
+    pub fn load<P: AsRef<Path>>(file_path: P) -> Result<Self, String> {
+        load_definition(file_path)
+    }
+
+    pub fn get_id(&self) -> String {
+        self.id.to_string()
+    }
+
+    /// Nodes the node depends on, each one listed once.
+    fn upstream_nodes<'a>(&'a self, node: &'a DagNode) -> Vec<&'a str> {
+        let edges = self.edges.iter().filter(|edge| edge.to == node.id);
+        let mut upstream: Vec<&str> = vec![];
+        for id in node.depends_on.iter().chain(edges.map(|edge| &edge.from)) {
+            if !upstream.contains(&id.as_str()) {
+                upstream.push(id);
+            }
+        }
+        upstream
+    }
+
+    /// Checks that the edges reference known nodes and do not form a cycle.
+    pub fn validate(&self) -> Result<(), AgentError> {
+        let ids: HashSet<&str> = self.nodes.iter().map(|node| node.id.as_str()).collect();
+        if ids.len() != self.nodes.len() {
+            return Err(invalid_dag("Duplicated node id"));
+        }
+        let mut pending: HashMap<&str, usize> = HashMap::new();
+        for node in &self.nodes {
+            let upstream = self.upstream_nodes(node);
+            if let Some(unknown) = upstream.iter().find(|id| !ids.contains(*id)) {
+                return Err(invalid_dag(&format!("Unknown node {}", unknown)));
+            }
+            pending.insert(&node.id, upstream.len());
+        }
+
+        let mut ready: VecDeque<&str> = pending
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(id, _)| *id)
+            .collect();
+        let mut visited = 0;
+        while let Some(id) = ready.pop_front() {
+            visited += 1;
+            for node in &self.nodes {
+                if self.upstream_nodes(node).contains(&id) {
+                    let count = pending.get_mut(node.id.as_str()).unwrap();
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.push_back(&node.id);
+                    }
+                }
+            }
+        }
+        if visited == self.nodes.len() {
+            Ok(())
+        } else {
+            Err(invalid_dag("The graph has a cycle"))
+        }
+    }
+
+    fn build_payload(&self, node: &DagNode, state: &Value) -> Value {
+        let mut payload = resolve(&node.input, state);
+        for edge in self.edges.iter().filter(|edge| edge.to == node.id) {
+            let upstream_output = &state["nodes"][edge.from.as_str()];
+            let value = match &edge.output {
+                Some(path) => select(upstream_output, path).unwrap_or(Value::Null),
+                None => upstream_output.clone(),
+            };
+            assign(&mut payload, edge.input.as_deref().unwrap_or(""), value);
+        }
+        payload
+    }
+
+    /// Executes the nodes as a new run of the DAG, which their actions are attributed to.
+    pub async fn execute(&self, input: Value, swarm: &Swarm) -> Result<DagReport, AgentError> {
+        self.validate()?;
+        scope_workflow_run(&self.id, self.execute_run(input, swarm)).await
+    }
+
+    async fn execute_run(&self, input: Value, swarm: &Swarm) -> Result<DagReport, AgentError> {
+        let run_id = ActionContext::current()
+            .and_then(|context| context.get_run_id().map(str::to_string))
+            .unwrap_or_default();
+        let started_at = current_time_iso();
+        let start = Instant::now();
+        let max_concurrency = self.max_concurrency.unwrap_or(self.nodes.len()).max(1);
+
+        let mut state = json!({ "input": input, "nodes": {} });
+        let mut pending: HashMap<&str, Vec<&str>> = self
+            .nodes
+            .iter()
+            .map(|node| (node.id.as_str(), self.upstream_nodes(node)))
+            .collect();
+        let mut succeeded: HashSet<&str> = HashSet::new();
+        let mut nodes: BTreeMap<String, DagNodeReport> = BTreeMap::new();
+        let mut skipped: Vec<String> = vec![];
+        let mut ready: VecDeque<&DagNode> = VecDeque::new();
+        let mut running = FuturesUnordered::new();
+
+        loop {
+            // Schedule the nodes whose upstream nodes all completed
+            let completed: Vec<&str> = self
+                .nodes
+                .iter()
+                .map(|node| node.id.as_str())
+                .filter(|id| nodes.contains_key(*id) || skipped.iter().any(|s| s == id))
+                .collect();
+            for node in &self.nodes {
+                let upstream = match pending.get(node.id.as_str()) {
+                    Some(upstream) => upstream,
+                    None => continue,
+                };
+                if upstream.iter().all(|id| completed.contains(id)) {
+                    if upstream.iter().all(|id| succeeded.contains(id)) {
+                        ready.push_back(node);
+                    } else {
+                        skipped.push(node.id.to_string());
+                    }
+                    pending.remove(node.id.as_str());
+                }
+            }
+            if ready.is_empty() && running.is_empty() && !pending.is_empty() {
+                // Skipping made more nodes complete, iterate again
+                continue;
+            }
+
+            while running.len() < max_concurrency {
+                let node = if let Some(node) = ready.pop_front() {
+                    node
+                } else {
+                    break;
+                };
+                let action = Action::new(&node.action, self.build_payload(node, &state));
+                running.push(async move {
+                    let started_at = current_time_iso();
+                    let start = Instant::now();
+                    emit_progress("node_started", json!({ "node": node.id }));
+                    let output = swarm.execute_action(&action).await;
+                    let status = if output.is_success() {
+                        "completed"
+                    } else {
+                        "failed"
+                    };
+                    emit_progress(&format!("node_{}", status), json!({ "node": node.id }));
+                    let report = DagNodeReport {
+                        action: node.action.to_string(),
+                        output,
+                        started_at,
+                        duration_ms: start.elapsed().as_millis() as u64,
+                    };
+                    (node, report)
+                });
+            }
+
+            if let Some((node, report)) = running.next().await {
+                if report.output.is_success() {
+                    succeeded.insert(&node.id);
+                    state["nodes"][node.id.as_str()] = report.output.get_value().clone();
+                }
+                nodes.insert(node.id.to_string(), report);
+            } else if ready.is_empty() && pending.is_empty() {
+                break;
+            }
+        }
+
+        Ok(DagReport {
+            dag_id: self.get_id(),
+            run_id,
+            success: succeeded.len() == self.nodes.len(),
+            nodes,
+            skipped,
+            started_at,
+            duration_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+}
+
+fn invalid_dag(message: &str) -> AgentError {
+    AgentError::new(ErrorCode::InvalidPayload, message)
+}
+
+#[async_trait]
+impl Agent for Dag {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn describe(&self) -> Vec<ActionDescriptor> {
+        vec![ActionDescriptor::new("run")
+            .with_kind(ActionKind::Workflow)
+            .with_error_schema(SchemaProbe::<AgentError>::new().json_schema())]
+    }
+
+    async fn execute(&self, action: &Action, swarm: &Swarm) -> Output {
+        match action.get_name() {
+            "run" | "default" => match action.get_payload::<Value>() {
+                Ok(input) => Output::from_result(self.execute(input, swarm).await),
+                Err(error) => Output::from_error(error),
+            },
+            _ => Output::from_error(AgentError::new(ErrorCode::UnknownAction, "Unknown action")),
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use serde_json::{Map, Value};
+
+/// Resolves a mapping against the workflow state.
+///
+/// Strings starting with `$` are JSONPath expressions replaced by the value
+/// they select, strings containing `{{ path }}` placeholders are templates
+/// rendered as strings, and objects and arrays are resolved recursively.
+pub fn resolve(mapping: &Value, state: &Value) -> Value {
+    match mapping {
+        Value::String(expression) if expression.starts_with('$') => {
+            select(state, expression).unwrap_or(Value::Null)
+        }
+        Value::String(template) if template.contains("{{") => {
+            Value::String(render_template(template, state))
+        }
+        Value::Array(items) => {
+            Value::Array(items.iter().map(|item| resolve(item, state)).collect())
+        }
+        Value::Object(fields) => {
+            let fields: Map<String, Value> = fields
+                .iter()
+                .map(|(key, value)| (key.to_string(), resolve(value, state)))
+                .collect();
+            Value::Object(fields)
+        }
+        _ => mapping.clone(),
+    }
+}
+
+/// Replaces every `{{ path }}` placeholder with the selected value.
+/// Strings are inserted as is, other values as JSON.
+pub fn render_template(template: &str, state: &Value) -> String {
+    let mut output = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        if let Some(end) = after.find("}}") {
+            let path = after[..end].trim();
+            let path = if path.starts_with('$') {
+                path.to_string()
+            } else {
+                format!("$.{}", path)
+            };
+            match select(state, &path) {
+                Some(Value::String(text)) => output.push_str(&text),
+                Some(Value::Null) | None => {}
+                Some(value) => output.push_str(&value.to_string()),
+            }
+            rest = &after[end + 2..];
+        } else {
+            output.push_str(&rest[start..]);
+            rest = "";
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Evaluates a JSONPath subset: `$`, `.field`, `['field']`, `[index]` and `[*]`.
+/// Negative indexes count from the end of arrays.
+pub fn select(root: &Value, path: &str) -> Option<Value> {
+    let segments = parse_path(path)?;
+    let mut current = vec![root.clone()];
+    let mut is_collection = false;
+    for segment in segments {
+        let mut next = vec![];
+        for value in current {
+            match &segment {
+                Segment::Field(name) => {
+                    if let Some(field) = value.get(name) {
+                        next.push(field.clone());
+                    }
+                }
+                Segment::Index(index) => {
+                    if let Some(items) = value.as_array() {
+                        let index = if *index < 0 {
+                            items.len() as i64 + index
+                        } else {
+                            *index
+                        };
+                        if let Some(item) = usize::try_from(index).ok().and_then(|i| items.get(i)) {
+                            next.push(item.clone());
+                        }
+                    }
+                }
+                Segment::Wildcard => {
+                    is_collection = true;
+                    match value {
+                        Value::Array(items) => next.extend(items),
+                        Value::Object(fields) => next.extend(fields.into_iter().map(|(_, v)| v)),
+                        _ => {}
+                    }
+                }
+            }
+        }
+        current = next;
+    }
+    if is_collection {
+        Some(Value::Array(current))
+    } else {
+        current.into_iter().next()
+    }
+}
+
+/// Sets `value` at a dot separated field path of `target`, creating the
+/// intermediate objects. Numeric fields index into existing arrays.
+/// An empty path replaces the whole target.
+pub fn assign(target: &mut Value, field_path: &str, value: Value) {
+    let mut current = target;
+    for field in field_path.split('.').filter(|field| !field.is_empty()) {
+        match (current.is_array(), field.parse::<usize>()) {
+            (true, Ok(index)) => {
+                let items = current.as_array_mut().unwrap();
+                if items.len() <= index {
+                    items.resize(index + 1, Value::Null);
+                }
+                current = &mut items[index];
+            }
+            _ => {
+                if !current.is_object() {
+                    *current = Value::Object(Map::new());
+                }
+                current = &mut current[field];
+            }
+        }
+    }
+    *current = value;
+}
+
+enum Segment {
+    Field(String),
+    Index(i64),
+    Wildcard,
+}
+
+fn parse_path(path: &str) -> Option<Vec<Segment>> {
+    let path = path.trim();
+    let mut chars = path.strip_prefix('$')?.chars().peekable();
+    let mut segments = vec![];
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => {
+                let mut name = String::new();
+                while let Some(c) = chars.peek() {
+                    if *c == '.' || *c == '[' {
+                        break;
+                    }
+                    name.push(*c);
+                    chars.next();
+                }
+                if name == "*" {
+                    segments.push(Segment::Wildcard);
+                } else if !name.is_empty() {
+                    segments.push(Segment::Field(name));
+                } else {
+                    return None;
+                }
+            }
+            '[' => {
+                let mut content = String::new();
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                    content.push(c);
+                }
+                let content = content.trim();
+                if content == "*" {
+                    segments.push(Segment::Wildcard);
+                } else if let Ok(index) = content.parse::<i64>() {
+                    segments.push(Segment::Index(index));
+                } else {
+                    let name = content.trim_matches(|c| c == '\'' || c == '"');
+                    segments.push(Segment::Field(name.to_string()));
+                }
+            }
+            _ => return None,
+        }
+    }
+    Some(segments)
+}
+
+#[test]
+pub fn test_select() {
+    let state = serde_json::json!({
+        "input": { "text": "agentic ai" },
+        "steps": {
+            "search": { "results": [{ "url": "a" }, { "url": "b" }] }
+        }
+    });
+    assert_eq!(
+        select(&state, "$.input.text"),
+        Some(Value::from("agentic ai"))
+    );
+    assert_eq!(
+        select(&state, "$['input']['text']"),
+        Some(Value::from("agentic ai"))
+    );
+    assert_eq!(
+        select(&state, "$.steps.search.results[1].url"),
+        Some(Value::from("b"))
+    );
+    assert_eq!(
+        select(&state, "$.steps.search.results[-1].url"),
+        Some(Value::from("b"))
+    );
+    assert_eq!(
+        select(&state, "$.steps.search.results[*].url"),
+        Some(serde_json::json!(["a", "b"]))
+    );
+    assert_eq!(select(&state, "$.steps.missing"), None);
+    assert_eq!(select(&state, "input.text"), None);
+}
+
+#[test]
+pub fn test_assign() {
+    let mut payload = serde_json::json!({ "lang": "en" });
+    assign(&mut payload, "query.terms", Value::from("agentic ai"));
+    assert_eq!(
+        payload,
+        serde_json::json!({ "lang": "en", "query": { "terms": "agentic ai" } })
+    );
+    let mut pair = serde_json::json!([0, 0]);
+    assign(&mut pair, "1", Value::from(5));
+    assert_eq!(pair, serde_json::json!([0, 5]));
+    assign(&mut payload, "", Value::from(42));
+    assert_eq!(payload, Value::from(42));
+}
+
+#[test]
+pub fn test_resolve() {
+    let state = serde_json::json!({ "input": { "text": "agentic ai", "page": 2 } });
+    let mapping = serde_json::json!({
+        "terms": "$.input.text",
+        "page": "$.input.page",
+        "title": "Results for {{input.text}} (page {{ $.input.page }})",
+        "lang": "en"
+    });
+    assert_eq!(
+        resolve(&mapping, &state),
+        serde_json::json!({
+            "terms": "agentic ai",
+            "page": 2,
+            "title": "Results for agentic ai (page 2)",
+            "lang": "en"
+        })
+    );
+}
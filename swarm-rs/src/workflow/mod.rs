@@ -0,0 +1,15 @@
+mod approval;
+mod checkpoint;
+mod dag;
+pub mod expression;
+mod workflow_agent;
+mod workflow_definition;
+pub use approval::{
+    Approval, ApprovalDecision, ApprovalOutcome, ApprovalStatus, ApprovalStore, Approvals,
+    FileApprovalStore,
+};
+pub(crate) use checkpoint::new_run_id;
+pub use checkpoint::{Checkpoint, CheckpointStore, FileCheckpointStore, RunStatus, RunSummary, WorkflowRun};
+pub use dag::{Dag, DagEdge, DagNode, DagNodeReport, DagReport};
+pub use workflow_agent::WorkflowAgent;
+pub use workflow_definition::{Condition, ConditionOp, WorkflowDefinition, WorkflowStep};
@@ -0,0 +1,334 @@
+use std::{any::Any, path::Path, sync::Arc};
+
+use async_trait::async_trait;
+use futures::{future::BoxFuture, FutureExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    agent::{Action, Agent, Output},
+    context::{emit_progress, ActionContext},
+    error::{AgentError, ErrorCode},
+    schema::{ActionDescriptor, ActionKind, SchemaProbe, WithSchema},
+    swarm::Swarm,
+    utils::time::current_time_iso,
+};
+
+use super::{
+    approval::Approval,
+    checkpoint::{Checkpoint, CheckpointStore, RunStatus, WorkflowRun},
+    expression::{render_template, resolve, select},
+    workflow_definition::{WorkflowDefinition, WorkflowStep},
+};
+
+/// Agent running a [`WorkflowDefinition`] through its `run` action.
+///
+/// The steps read and write a JSON state shaped as
+/// `{ "input": <payload>, "steps": { <step id>: <step output> } }`,
+/// extended with the current item of the enclosing loops.
+///
+/// When the swarm has a [`CheckpointStore`], every action step is checkpointed
+/// and an interrupted run can be resumed by its run id: the steps already
+/// completed are replayed from their checkpoints instead of being executed again.
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct WorkflowAgent {
+    definition: WorkflowDefinition,
+}
+
+impl WorkflowAgent {
+    pub fn new(definition: WorkflowDefinition) -> Self {
+        Self { definition }
+    }
+
+    pub fn load<P: AsRef<Path>>(file_path: P) -> Result<Self, String> {
+        WorkflowDefinition::load(file_path).map(Self::new)
+    }
+
+    pub fn get_id(&self) -> String {
+        self.definition.id.to_string()
+    }
+
+    pub fn get_definition(&self) -> &WorkflowDefinition {
+        &self.definition
+    }
+
+    pub async fn run(&self, input: Value, swarm: &Swarm) -> Result<Value, AgentError> {
+        let run = WorkflowRun::new(&self.get_id(), input.clone());
+        let run_id = run.run_id.to_string();
+        let mut recorder = swarm
+            .get_checkpoint_store()
+            .map(|store| Recorder { store, run });
+        self.execute_run(&run_id, input, swarm, &mut recorder).await
+    }
+
+    /// Resumes a run from its last checkpoint, or returns its output if it already completed.
+    pub async fn resume(&self, run_id: &str, swarm: &Swarm) -> Result<Value, AgentError> {
+        let store = swarm.get_checkpoint_store().ok_or_else(|| {
+            AgentError::new(ErrorCode::Internal, "No checkpoint store configured")
+        })?;
+        let run = store
+            .load(run_id)?
+            .filter(|run| run.workflow_id == self.get_id())
+            .ok_or_else(|| {
+                AgentError::new(
+                    ErrorCode::InvalidPayload,
+                    &format!("Unknown run {}", run_id),
+                )
+            })?;
+        if let (RunStatus::Completed, Some(output)) = (run.status, &run.output) {
+            return Ok(output.clone());
+        }
+        let input = run.input.clone();
+        let run_id = run.run_id.to_string();
+        let mut recorder = Some(Recorder { store, run });
+        self.execute_run(&run_id, input, swarm, &mut recorder).await
+    }
+
+    async fn execute_run(
+        &self,
+        run_id: &str,
+        input: Value,
+        swarm: &Swarm,
+        recorder: &mut Option<Recorder>,
+    ) -> Result<Value, AgentError> {
+        if let Some(recorder) = recorder {
+            recorder.run.status = RunStatus::Running;
+            recorder.run.error = None;
+            recorder.save()?;
+        }
+        let mut state = json!({ "input": input, "steps": {} });
+        // The nested actions are attributed to the run
+        let mut context = ActionContext::current().unwrap_or_default();
+        context.set_run(&self.get_id(), run_id);
+        let result = context
+            .scope(self.run_steps(&self.definition.steps, "", &mut state, swarm, recorder))
+            .await
+            .map(|_| match &self.definition.output {
+                Some(output) => resolve(output, &state),
+                None => state["steps"].clone(),
+            });
+        if let Some(recorder) = recorder {
+            match &result {
+                Ok(output) => {
+                    recorder.run.status = RunStatus::Completed;
+                    recorder.run.output = Some(output.clone());
+                }
+                Err(error) => {
+                    recorder.run.status = RunStatus::Failed;
+                    recorder.run.error = Some(error.clone());
+                }
+            }
+            recorder.save()?;
+        }
+        result
+    }
+
+    /// Runs the steps in order and returns the output of the last one.
+    fn run_steps<'a>(
+        &'a self,
+        steps: &'a [WorkflowStep],
+        scope: &'a str,
+        state: &'a mut Value,
+        swarm: &'a Swarm,
+        recorder: &'a mut Option<Recorder>,
+    ) -> BoxFuture<'a, Result<Value, AgentError>> {
+        async move {
+            let mut last_output = Value::Null;
+            for step in steps {
+                let step_path = format!("{}{}", scope, step.get_id());
+                emit_progress("step_started", json!({ "step": step_path }));
+                let result = self.run_step(step, scope, state, swarm, recorder).await;
+                let status = if result.is_ok() {
+                    "completed"
+                } else {
+                    "failed"
+                };
+                emit_progress(&format!("step_{}", status), json!({ "step": step_path }));
+                last_output = result?;
+            }
+            Ok(last_output)
+        }
+        .boxed()
+    }
+
+    async fn run_step(
+        &self,
+        step: &WorkflowStep,
+        scope: &str,
+        state: &mut Value,
+        swarm: &Swarm,
+        recorder: &mut Option<Recorder>,
+    ) -> Result<Value, AgentError> {
+        let output = match step {
+            WorkflowStep::Action { id, action, input } => {
+                let step_path = format!("{}{}", scope, id);
+                let replayed = recorder
+                    .as_ref()
+                    .and_then(|recorder| recorder.run.get_checkpoint(&step_path))
+                    .map(|checkpoint| checkpoint.output.clone());
+                let payload = resolve(input, state);
+                let output = match replayed {
+                    Some(output) => output,
+                    None => swarm.execute(action, &payload).await,
+                };
+                let result = output.get_payload::<Value>().map_err(|error| {
+                    AgentError::action_failed(&format!("Step {} failed", id)).with_cause(error)
+                });
+                if let Ok(value) = &result {
+                    state["steps"][id.as_str()] = value.clone();
+                }
+                if let Some(recorder) = recorder {
+                    if recorder.run.get_checkpoint(&step_path).is_none() {
+                        recorder.run.checkpoints.push(Checkpoint {
+                            step_path,
+                            action: action.to_string(),
+                            payload,
+                            output,
+                            state: state.clone(),
+                            created_at: current_time_iso(),
+                        });
+                        recorder.save()?;
+                    }
+                }
+                result?
+            }
+            WorkflowStep::If {
+                condition,
+                then,
+                otherwise,
+                ..
+            } => {
+                let branch = if condition.evaluate(state) {
+                    then
+                } else {
+                    otherwise
+                };
+                self.run_steps(branch, scope, state, swarm, recorder)
+                    .await?
+            }
+            WorkflowStep::ForEach {
+                id,
+                items,
+                item_name,
+                steps,
+                output,
+            } => {
+                let items = match select(state, items) {
+                    Some(Value::Array(items)) => items,
+                    Some(Value::Null) | None => vec![],
+                    Some(_) => {
+                        return Err(AgentError::new(
+                            ErrorCode::InvalidPayload,
+                            &format!("Step {} items is not an array", id),
+                        ))
+                    }
+                };
+                let mut results = vec![];
+                for (index, item) in items.into_iter().enumerate() {
+                    // Iterations work on their own copy of the state
+                    let mut iteration_state = state.clone();
+                    iteration_state[item_name.as_str()] = item;
+                    let iteration_scope = format!("{}{}[{}].", scope, id, index);
+                    let last_output = self
+                        .run_steps(
+                            steps,
+                            &iteration_scope,
+                            &mut iteration_state,
+                            swarm,
+                            recorder,
+                        )
+                        .await?;
+                    results.push(match output {
+                        Some(output) => resolve(output, &iteration_state),
+                        None => last_output,
+                    });
+                }
+                Value::Array(results)
+            }
+            WorkflowStep::Approval {
+                id,
+                title,
+                details,
+                roles,
+                timeout_ms,
+                default_outcome,
+            } => {
+                let approvals = swarm.get_approvals().ok_or_else(|| {
+                    AgentError::new(ErrorCode::Internal, "No approvals configured")
+                })?;
+                let run_id = recorder
+                    .as_ref()
+                    .map(|recorder| recorder.run.run_id.as_str());
+                let mut approval = Approval::new(&render_template(title, state))
+                    .with_details(resolve(details, state))
+                    .with_roles(roles)
+                    .with_run(&self.get_id(), run_id);
+                if let Some(run_id) = run_id {
+                    approval = approval.with_id(&format!("{}-{}{}", run_id, scope, id));
+                }
+                approval.timeout_ms = *timeout_ms;
+                approval.default_outcome = *default_outcome;
+
+                let decision = approvals.request(approval).await?;
+                if !decision.is_approved() {
+                    let reason = decision.comment.as_deref().unwrap_or("Rejected");
+                    return Err(AgentError::action_failed(&format!("Step {} rejected", id))
+                        .with_cause(AgentError::action_failed(reason)));
+                }
+                serde_json::to_value(decision).unwrap_or_default()
+            }
+        };
+        state["steps"][step.get_id()] = output.clone();
+        Ok(output)
+    }
+}
+
+#[async_trait]
+impl Agent for WorkflowAgent {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn describe(&self) -> Vec<ActionDescriptor> {
+        let error_schema = SchemaProbe::<AgentError>::new().json_schema();
+        let mut run = ActionDescriptor::new("run")
+            .with_kind(ActionKind::Workflow)
+            .with_error_schema(error_schema.clone());
+        if !self.definition.description.is_empty() {
+            run = run.with_description(&self.definition.description);
+        }
+        let resume = ActionDescriptor::new("resume")
+            .with_kind(ActionKind::Workflow)
+            .with_description("Resumes a run from its last checkpoint.")
+            .with_input_schema(SchemaProbe::<String>::new().json_schema())
+            .with_error_schema(error_schema);
+        vec![run, resume]
+    }
+
+    async fn execute(&self, action: &Action, swarm: &Swarm) -> Output {
+        match action.get_name() {
+            "run" | "default" => match action.get_payload::<Value>() {
+                Ok(input) => Output::from_result(self.run(input, swarm).await),
+                Err(error) => Output::from_error(error),
+            },
+            "resume" => match action.get_payload::<String>() {
+                Ok(run_id) => Output::from_result(self.resume(&run_id, swarm).await),
+                Err(error) => Output::from_error(error),
+            },
+            _ => Output::from_error(AgentError::new(ErrorCode::UnknownAction, "Unknown action")),
+        }
+    }
+}
+
+struct Recorder {
+    store: Arc<dyn CheckpointStore>,
+    run: WorkflowRun,
+}
+
+impl Recorder {
+    fn save(&mut self) -> Result<(), AgentError> {
+        self.run.updated_at = current_time_iso();
+        self.store.save(&self.run)
+    }
+}
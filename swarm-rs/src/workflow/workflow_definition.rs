@@ -0,0 +1,201 @@
+use std::path::Path;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::utils::{file_io, json_io};
+
+use super::{
+    approval::ApprovalOutcome,
+    expression::{resolve, select},
+};
+
+/// Declarative pipeline executed by a [`WorkflowAgent`](super::WorkflowAgent).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowDefinition {
+    pub id: String,
+    #[serde(default)]
+    pub description: String,
+    pub steps: Vec<WorkflowStep>,
+    /// Mapping of the workflow result, defaults to the steps outputs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<Value>,
+}
+
+impl WorkflowDefinition {
+    /// Loads a definition from a JSON file, or a YAML one for `.yaml` and `.yml` files
+    /// when the `yaml` feature is enabled.
+    pub fn load<P: AsRef<Path>>(file_path: P) -> Result<Self, String> {
+        load_definition(file_path)
+    }
+}
+
+/// Loads a JSON definition file, or a YAML one for `.yaml` and `.yml` files.
+pub(crate) fn load_definition<P: AsRef<Path>, T: DeserializeOwned>(
+    file_path: P,
+) -> Result<T, String> {
+    let extension = file_io::get_file_extension(&file_path).unwrap_or_default();
+    if extension == "yaml" || extension == "yml" {
+        load_yaml(file_path)
+    } else {
+        json_io::load(file_path)
+    }
+}
+
+#[cfg(feature = "yaml")]
+fn load_yaml<P: AsRef<Path>, T: DeserializeOwned>(file_path: P) -> Result<T, String> {
+    let content = std::fs::read_to_string(&file_path)
+        .map_err(|_| format!("unable to read file {:?}", file_path.as_ref()))?;
+    serde_yaml::from_str(&content)
+        .map_err(|e| format!("Malformed yaml: {:?} {}", file_path.as_ref(), e))
+}
+
+#[cfg(not(feature = "yaml"))]
+fn load_yaml<P: AsRef<Path>, T: DeserializeOwned>(file_path: P) -> Result<T, String> {
+    Err(format!(
+        "unable to load {:?}, YAML definitions need the `yaml` feature",
+        file_path.as_ref()
+    ))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WorkflowStep {
+    /// Executes a swarm action with a payload mapped from the workflow state.
+    Action {
+        id: String,
+        action: String,
+        #[serde(default)]
+        input: Value,
+    },
+    /// Runs `then` or `else` depending on the condition.
+    If {
+        id: String,
+        condition: Condition,
+        #[serde(default)]
+        then: Vec<WorkflowStep>,
+        #[serde(default, rename = "else")]
+        otherwise: Vec<WorkflowStep>,
+    },
+    /// Runs the steps once per item of the selected array.
+    /// Its output is the array of the iterations `output`, or of their last step output.
+    ForEach {
+        id: String,
+        items: String,
+        #[serde(default = "default_item_name", rename = "as")]
+        item_name: String,
+        steps: Vec<WorkflowStep>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        output: Option<Value>,
+    },
+    /// Waits for a person with one of the `roles` to approve, and fails when rejected.
+    /// Its output is the decision.
+    Approval {
+        id: String,
+        /// Template rendered with the workflow state.
+        title: String,
+        #[serde(default)]
+        details: Value,
+        #[serde(default)]
+        roles: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout_ms: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        default_outcome: Option<ApprovalOutcome>,
+    },
+}
+
+fn default_item_name() -> String {
+    "item".to_string()
+}
+
+impl WorkflowStep {
+    pub fn get_id(&self) -> &str {
+        match self {
+            WorkflowStep::Action { id, .. } => id,
+            WorkflowStep::If { id, .. } => id,
+            WorkflowStep::ForEach { id, .. } => id,
+            WorkflowStep::Approval { id, .. } => id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConditionOp {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Contains,
+    Exists,
+    NotExists,
+    Empty,
+    NotEmpty,
+}
+
+/// Compares the value selected by `path` with `value`, which may itself be a mapping.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Condition {
+    pub path: String,
+    pub op: ConditionOp,
+    #[serde(default)]
+    pub value: Value,
+}
+
+impl Condition {
+    pub fn evaluate(&self, state: &Value) -> bool {
+        let selected = select(state, &self.path);
+        let expected = resolve(&self.value, state);
+        match self.op {
+            ConditionOp::Exists => selected.is_some_and(|value| !value.is_null()),
+            ConditionOp::NotExists => selected.is_none_or(|value| value.is_null()),
+            ConditionOp::Empty => selected.is_none_or(|value| is_empty(&value)),
+            ConditionOp::NotEmpty => selected.is_some_and(|value| !is_empty(&value)),
+            ConditionOp::Eq => selected.unwrap_or(Value::Null) == expected,
+            ConditionOp::Ne => selected.unwrap_or(Value::Null) != expected,
+            ConditionOp::Contains => match selected {
+                Some(Value::Array(items)) => items.contains(&expected),
+                Some(Value::String(text)) => expected.as_str().is_some_and(|e| text.contains(e)),
+                Some(Value::Object(fields)) => {
+                    expected.as_str().is_some_and(|e| fields.contains_key(e))
+                }
+                _ => false,
+            },
+            ConditionOp::Gt | ConditionOp::Gte | ConditionOp::Lt | ConditionOp::Lte => {
+                let ordering = selected
+                    .as_ref()
+                    .and_then(|value| compare(value, &expected));
+                match ordering {
+                    Some(ordering) => match self.op {
+                        ConditionOp::Gt => ordering.is_gt(),
+                        ConditionOp::Gte => ordering.is_ge(),
+                        ConditionOp::Lt => ordering.is_lt(),
+                        _ => ordering.is_le(),
+                    },
+                    None => false,
+                }
+            }
+        }
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(text) => text.is_empty(),
+        Value::Array(items) => items.is_empty(),
+        Value::Object(fields) => fields.is_empty(),
+        _ => false,
+    }
+}
+
+fn compare(left: &Value, right: &Value) -> Option<std::cmp::Ordering> {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => left.as_f64()?.partial_cmp(&right.as_f64()?),
+        (Value::String(left), Value::String(right)) => Some(left.cmp(right)),
+        _ => None,
+    }
+}
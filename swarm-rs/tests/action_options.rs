@@ -0,0 +1,220 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use rocket::{
+    http::{ContentType, Header, Status},
+    local::asynchronous::Client,
+};
+use serde_json::{json, Value};
+use swarm_rs::{
+    prelude::*,
+    utils::file_io,
+    web::{
+        web_swarm::{WebOptions, WebSwarm},
+        AuthAgent, NewUser, UserAuth,
+    },
+};
+
+#[derive(Default)]
+pub struct ReportAgent {
+    attempts: AtomicUsize,
+}
+
+#[agent]
+impl ReportAgent {
+    /// Doc comment overridden by the description option.
+    #[agent_action(name = "summarize", aliases = ["sum", "abstract"], description = "Summarizes the text.")]
+    pub async fn summarize_text(&self, text: String) -> Result<String, String> {
+        Ok(text.split_whitespace().take(2).collect::<Vec<_>>().join(" "))
+    }
+
+    #[agent_action(timeout_ms = 50)]
+    pub async fn render(&self, delay_ms: u64) -> Result<String, String> {
+        tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+        Ok("rendered".to_string())
+    }
+
+    #[agent_action(retry(max_attempts = 3, initial_backoff_ms = 1, max_backoff_ms = 5))]
+    pub async fn fetch(&self, failures: usize) -> Result<usize, String> {
+        let attempt = self.attempts.fetch_add(1, Ordering::Relaxed) + 1;
+        if attempt <= failures {
+            return Err("Source unavailable".to_string());
+        }
+        Ok(attempt)
+    }
+
+    #[agent_action(roles = ["Publisher"], deprecated)]
+    pub async fn publish(&self, title: String) -> Result<String, String> {
+        Ok(format!("{} published", title))
+    }
+}
+
+fn new_swarm() -> Swarm {
+    let mut swarm = Swarm::default();
+    swarm.register_agent("report", ReportAgent::default());
+    swarm
+}
+
+#[test]
+pub fn describe_options() {
+    let actions = ReportAgent::default().describe();
+    let summarize = &actions[0];
+    assert_eq!(summarize.name, "summarize");
+    assert_eq!(summarize.aliases, vec!["sum", "abstract"]);
+    assert_eq!(summarize.description.as_deref(), Some("Summarizes the text."));
+    assert_eq!(actions[1].get_timeout(), Some(Duration::from_millis(50)));
+    assert_eq!(
+        actions[2].retry,
+        Some(
+            RetrySettings::new(3)
+                .with_initial_backoff_ms(1)
+                .with_max_backoff_ms(5)
+        )
+    );
+    let publish = serde_json::to_value(&actions[3]).unwrap();
+    assert_eq!(publish["roles"], json!(["Publisher"]));
+    assert_eq!(publish["deprecated"], true);
+
+    let swarm = new_swarm();
+    assert_eq!(swarm.describe_action("report.sum").unwrap().name, "summarize");
+    assert!(swarm.describe_action("report.summarize_text").is_none());
+}
+
+#[tokio::test]
+pub async fn enforce_options() {
+    let swarm = new_swarm();
+    let text = "Agents exchange actions through the swarm";
+    for action_id in ["report.summarize", "report.sum", "report.abstract"] {
+        let output = swarm.execute(action_id, &text).await;
+        assert_eq!(output.get_payload::<String>().unwrap(), "Agents exchange");
+    }
+    let output = swarm.execute("report.summarize_text", &text).await;
+    assert_eq!(output.get_error().unwrap().code, ErrorCode::UnknownAction);
+
+    // The declared timeout applies unless the action sets its own
+    let output = swarm.execute("report.render", &200).await;
+    assert_eq!(output.get_status(), OutputStatus::Timeout);
+    let action = Action::new("report.render", 100).with_timeout(Duration::from_millis(500));
+    assert!(swarm.execute_action(&action).await.is_success());
+
+    let output = swarm.execute("report.fetch", &2).await;
+    assert_eq!(output.get_payload::<usize>().unwrap(), 3);
+
+    // Typed clients use the public names
+    let client = ReportAgentClient::new(&swarm, "report");
+    assert_eq!(client.summarize_text(text.to_string()).await.unwrap(), "Agents exchange");
+    #[allow(deprecated)]
+    let error = client.publish("Changelog".to_string()).await.unwrap_err();
+    assert_eq!(error.code, ErrorCode::Unauthorized);
+}
+
+pub struct NewsroomAgent {}
+
+#[agent]
+impl NewsroomAgent {
+    #[agent_action]
+    pub async fn release(&self, title: String, swarm: &Swarm) -> Result<String, AgentError> {
+        swarm.execute("report.publish", &title).await.get_payload()
+    }
+}
+
+#[tokio::test]
+pub async fn nested_roles() {
+    let mut swarm = new_swarm();
+    swarm.register_agent("newsroom", NewsroomAgent {});
+
+    // Nested actions run for the user of the action executing them
+    let output = swarm.execute("newsroom.release", &"Changelog").await;
+    assert_eq!(output.get_error().unwrap().code, ErrorCode::Unauthorized);
+    let reader = ["Reader".to_string()];
+    let action = Action::new("newsroom.release", "Changelog")
+        .with_user("reader")
+        .with_roles(&reader);
+    let output = swarm.execute_action(&action).await;
+    let error = output.get_error().unwrap();
+    assert_eq!(error.code, ErrorCode::Forbidden);
+    assert_eq!(error.message, "report.publish requires one of the roles Publisher");
+    let publisher = ["Publisher".to_string()];
+    let action = Action::new("newsroom.release", "Changelog")
+        .with_user("publisher")
+        .with_roles(&publisher);
+    let output = swarm.execute_action(&action).await;
+    assert_eq!(output.get_payload::<String>().unwrap(), "Changelog published");
+}
+
+#[tokio::test]
+pub async fn web_roles() {
+    let mut swarm = new_swarm();
+    let users_db = "test-data/out/action-options-users.json";
+    file_io::remove_file(users_db);
+    let auth = AuthAgent::new(users_db, "action options secret", 1);
+    let publisher = NewUser::new("publisher", "p4ssw0rd", "Publisher", "", vec!["Publisher"]);
+    let publisher: UserAuth = auth.register_user(publisher).await.unwrap();
+    let reader = NewUser::new("reader", "p4ssw0rd", "Reader", "", vec!["Reader"]);
+    let reader: UserAuth = auth.register_user(reader).await.unwrap();
+    swarm.register_agent("Auth", auth);
+
+    let client = Client::tracked(WebSwarm::serve(swarm)).await.unwrap();
+    let action = Action::new("report.publish", "Changelog");
+    for (user, status) in [(&reader, Status::Forbidden), (&publisher, Status::Ok)] {
+        let response = client
+            .post("/api/action")
+            .header(ContentType::JSON)
+            .header(Header::new("Token", user.token.to_string()))
+            .body(serde_json::to_string(&action).unwrap())
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), status);
+    }
+
+    let response = client.get("/api/openapi.json").dispatch().await;
+    let document: Value = response.into_json().await.unwrap();
+    let actions = &document["paths"]["/api/action"]["post"]["requestBody"]["content"]
+        ["application/json"]["schema"]["oneOf"];
+    assert!(actions
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|action| action["title"] == "report.summarize"));
+}
+
+#[tokio::test]
+pub async fn protected_aliases() {
+    let mut swarm = new_swarm();
+    let users_db = "test-data/out/action-aliases-users.json";
+    file_io::remove_file(users_db);
+    let auth: AuthAgent = serde_json::from_value(json!({
+        "db_path": users_db,
+        "server_secret": "action aliases secret",
+        "token_validity_in_days": 1,
+        "protected_actions": { "report.summarize": ["Publisher"] }
+    }))
+    .unwrap();
+    let reader = NewUser::new("reader", "p4ssw0rd", "Reader", "", vec!["Reader"]);
+    let reader: UserAuth = auth.register_user(reader).await.unwrap();
+    swarm.register_agent("Auth", auth);
+
+    let rocket = WebSwarm::serve_with_options(swarm, WebOptions::default().with_typed_routes());
+    let client = Client::tracked(rocket).await.unwrap();
+    for action_id in ["report.summarize", "report.sum", "report.abstract"] {
+        let action = Action::new(action_id, "Aliases share the protection");
+        let response = client
+            .post("/api/action")
+            .header(ContentType::JSON)
+            .header(Header::new("Token", reader.token.to_string()))
+            .body(serde_json::to_string(&action).unwrap())
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Forbidden, "{}", action_id);
+    }
+    let response = client
+        .post("/api/agents/report/sum")
+        .header(ContentType::JSON)
+        .header(Header::new("Token", reader.token.to_string()))
+        .body("\"Aliases share the protection\"")
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Forbidden);
+}
@@ -0,0 +1,160 @@
+mod common;
+
+use common::{MockResponse, MockServer};
+use futures::StreamExt;
+use rocket::{http::ContentType, local::asynchronous::Client};
+use serde_json::{json, Value};
+use swarm_rs::{prelude::*, web::web_swarm::WebSwarm};
+
+#[derive(Default)]
+pub struct CrawlerAgent {}
+
+#[agent]
+impl CrawlerAgent {
+    #[agent_action]
+    pub async fn crawl(&self, pages: usize) -> Result<usize, String> {
+        for page in 1..=pages {
+            emit_progress("page", page);
+        }
+        Ok(pages)
+    }
+}
+
+fn new_swarm() -> Swarm {
+    let mut swarm = Swarm::default();
+    swarm.register_agent("crawler", CrawlerAgent::default());
+    let definition: WorkflowDefinition = serde_json::from_value(json!({
+        "id": "indexing",
+        "steps": [
+            { "type": "action", "id": "crawl", "action": "crawler.crawl", "input": "$.input" }
+        ],
+        "output": "$.steps.crawl"
+    }))
+    .unwrap();
+    swarm.register_agent("indexing", WorkflowAgent::new(definition));
+    swarm
+}
+
+#[tokio::test]
+pub async fn stream_progress() {
+    let swarm = new_swarm();
+    let action = Action::new("indexing.run", 2);
+    let events: Vec<ActionEvent> = swarm.execute_stream(&action).collect().await;
+
+    let progress: Vec<(String, String, Value)> = events
+        .iter()
+        .filter_map(|event| match event {
+            ActionEvent::Progress(progress) => Some((
+                progress.action_id.to_string(),
+                progress.event.to_string(),
+                progress.data.clone(),
+            )),
+            ActionEvent::Output(_) => None,
+        })
+        .collect();
+    assert_eq!(
+        progress,
+        vec![
+            (
+                "indexing.run".to_string(),
+                "step_started".to_string(),
+                json!({ "step": "crawl" })
+            ),
+            ("crawler.crawl".to_string(), "page".to_string(), json!(1)),
+            ("crawler.crawl".to_string(), "page".to_string(), json!(2)),
+            (
+                "indexing.run".to_string(),
+                "step_completed".to_string(),
+                json!({ "step": "crawl" })
+            ),
+        ]
+    );
+    match events.last().unwrap() {
+        ActionEvent::Output(output) => assert_eq!(output.get_payload::<usize>().unwrap(), 2),
+        event => panic!("Unexpected last event {:?}", event),
+    }
+
+    // Without listener the events are dropped
+    let output = swarm.execute("crawler.crawl", &3).await;
+    assert_eq!(output.get_payload::<usize>().unwrap(), 3);
+}
+
+#[tokio::test]
+pub async fn stream_llm_tokens() {
+    let chunks: Vec<String> = ["Agents", " at", " work"]
+        .iter()
+        .map(|content| json!({ "choices": [{ "delta": { "content": content } }] }).to_string())
+        .collect();
+    let chunks: Vec<&str> = chunks.iter().map(|chunk| chunk.as_str()).collect();
+    let server = MockServer::start(vec![MockResponse::sse(&chunks)]).await;
+    let llm_agent: LLMAgent = serde_json::from_value(json!({
+        "id": "writer",
+        "client": { "endpoint": server.url, "model": "mock-model" },
+        "role": "You are a writer.",
+        "goal": "Write a title about {topic}",
+        "output_rules": "Three words."
+    }))
+    .unwrap();
+    let mut swarm = Swarm::default();
+    swarm.register_agent(&llm_agent.get_id(), llm_agent);
+
+    let mut prompt = LLMPrompt::new();
+    prompt.add_content("topic", "agents");
+    let action = Action::new("writer.execute_streaming", prompt);
+    let events: Vec<ActionEvent> = swarm.execute_stream(&action).collect().await;
+    let tokens: Vec<Value> = events
+        .iter()
+        .filter_map(|event| match event {
+            ActionEvent::Progress(progress) if progress.event == "token" => {
+                Some(progress.data.clone())
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(tokens, vec![json!("Agents"), json!(" at"), json!(" work")]);
+    match events.last().unwrap() {
+        ActionEvent::Output(output) => {
+            let completion: Value = output.get_payload().unwrap();
+            assert_eq!(completion["content"], "Agents at work");
+        }
+        event => panic!("Unexpected last event {:?}", event),
+    }
+}
+
+#[tokio::test]
+pub async fn web_stream() {
+    let client = Client::tracked(WebSwarm::serve(new_swarm())).await.unwrap();
+    let action = Action::new("indexing.run", 1);
+    let response = client
+        .post("/api/action/stream")
+        .header(ContentType::JSON)
+        .body(serde_json::to_string(&action).unwrap())
+        .dispatch()
+        .await;
+    assert_eq!(response.content_type(), Some(ContentType::EventStream));
+
+    let body = response.into_string().await.unwrap();
+    let events: Vec<(&str, ActionEvent)> = body
+        .split("\n\n")
+        .filter(|event| !event.trim().is_empty())
+        .map(|event| {
+            let name = event
+                .lines()
+                .find_map(|line| line.strip_prefix("event:"))
+                .unwrap();
+            let data = event
+                .lines()
+                .find_map(|line| line.strip_prefix("data:"))
+                .unwrap();
+            (name, serde_json::from_str(data).unwrap())
+        })
+        .collect();
+    assert_eq!(events.len(), 4);
+    assert_eq!(events[1].0, "progress");
+    match &events[3] {
+        ("output", ActionEvent::Output(output)) => {
+            assert_eq!(output.get_payload::<usize>().unwrap(), 1)
+        }
+        event => panic!("Unexpected last event {:?}", event),
+    }
+}
@@ -0,0 +1,103 @@
+use std::time::{Duration, Instant};
+
+use swarm_rs::prelude::*;
+
+#[derive(Default)]
+pub struct SlowAgent {}
+
+#[agent]
+impl SlowAgent {
+    #[agent_action]
+    pub async fn sleep(&self, millis: u64) -> Result<u64, String> {
+        tokio::time::sleep(Duration::from_millis(millis)).await;
+        Ok(millis)
+    }
+
+    #[agent_workflow]
+    pub async fn nested_sleep(&self, millis: u64, swarm: &Swarm) -> Result<bool, String> {
+        let output = swarm.execute("slow.sleep", &millis).await;
+        Ok(output.is_cancelled())
+    }
+
+    #[agent_workflow]
+    pub async fn abort_nested(
+        &self,
+        millis: u64,
+        swarm: &Swarm,
+        cancellation: &CancellationToken,
+    ) -> Result<bool, String> {
+        let nested_cancellation = cancellation.child_token();
+        let nested =
+            Action::new("slow.sleep", millis).with_cancellation(nested_cancellation.clone());
+        let abort = async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            nested_cancellation.cancel();
+        };
+        let (output, _) = tokio::join!(swarm.execute_action(&nested), abort);
+        Ok(output.is_cancelled())
+    }
+}
+
+fn new_swarm() -> Swarm {
+    let mut swarm = Swarm::default();
+    swarm.register_agent("slow", SlowAgent::default());
+    swarm
+}
+
+#[tokio::test]
+pub async fn action_timeout() {
+    let swarm = new_swarm();
+
+    let action = Action::new("slow.sleep", 1000).with_timeout(Duration::from_millis(50));
+    let start = Instant::now();
+    let output = swarm.execute_action(&action).await;
+    assert!(start.elapsed() < Duration::from_millis(500));
+    assert!(output.is_timeout());
+    assert_eq!(output.get_status(), OutputStatus::Timeout);
+    assert!(output.get_error().unwrap().retryable);
+
+    let action = Action::new("slow.sleep", 10).with_timeout(Duration::from_millis(500));
+    let output = swarm.execute_action(&action).await;
+    assert!(output.is_success());
+}
+
+#[tokio::test]
+pub async fn swarm_default_timeout() {
+    let mut swarm = new_swarm();
+    swarm.set_default_timeout(Some(Duration::from_millis(50)));
+
+    let output = swarm.execute("slow.sleep", &1000).await;
+    assert!(output.is_timeout());
+
+    // The action deadline takes precedence over the swarm default
+    let action = Action::new("slow.sleep", 100).with_timeout(Duration::from_millis(500));
+    let output = swarm.execute_action(&action).await;
+    assert!(output.is_success());
+}
+
+#[tokio::test]
+pub async fn cancel_action() {
+    let swarm = new_swarm();
+    let cancellation = CancellationToken::new();
+
+    let action = Action::new("slow.nested_sleep", 1000).with_cancellation(cancellation.clone());
+    let abort = async {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        cancellation.cancel();
+    };
+    let start = Instant::now();
+    let (output, _) = tokio::join!(swarm.execute_action(&action), abort);
+    assert!(start.elapsed() < Duration::from_millis(500));
+    assert!(output.is_cancelled());
+}
+
+#[tokio::test]
+pub async fn workflow_aborts_nested_calls() {
+    let swarm = new_swarm();
+
+    let start = Instant::now();
+    let output = swarm.execute("slow.abort_nested", &1000).await;
+    assert!(start.elapsed() < Duration::from_millis(500));
+    assert!(output.is_success());
+    assert!(output.get_payload::<bool>().unwrap());
+}
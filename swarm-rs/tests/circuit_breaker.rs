@@ -0,0 +1,205 @@
+use std::{
+    sync::atomic::{AtomicBool, AtomicU32, Ordering},
+    time::Duration,
+};
+
+use swarm_rs::prelude::*;
+
+/// Stands for a backing service that can go down
+#[derive(Default)]
+pub struct ServiceAgent {
+    down: AtomicBool,
+    calls: AtomicU32,
+}
+
+#[agent]
+impl ServiceAgent {
+    #[agent_action]
+    pub async fn call(&self, value: u32) -> Result<u32, String> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        if self.down.load(Ordering::SeqCst) {
+            Err("Service down".to_string())
+        } else {
+            Ok(value)
+        }
+    }
+
+    #[agent_action]
+    pub async fn wait(&self, delay_ms: u64) -> Result<u64, String> {
+        tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+        Ok(delay_ms)
+    }
+}
+
+fn service(swarm: &Swarm) -> &ServiceAgent {
+    swarm.get_agent::<ServiceAgent>("service").unwrap()
+}
+
+#[tokio::test]
+pub async fn circuit_breaker() {
+    let mut swarm = Swarm::default();
+    let config = CircuitBreakerConfig::new(3, Duration::from_millis(100));
+    let options = AgentOptions::new().with_circuit_breaker(config);
+    swarm.register_agent_with_options("service", ServiceAgent::default(), options);
+    assert_eq!(
+        swarm.get_circuit_state("service"),
+        Some(CircuitState::Closed)
+    );
+
+    service(&swarm).down.store(true, Ordering::SeqCst);
+    for _ in 0..3 {
+        let output = swarm.execute("service.call", &1).await;
+        assert_eq!(output.get_error().unwrap().code, ErrorCode::ActionFailed);
+    }
+    assert_eq!(swarm.get_circuit_state("service"), Some(CircuitState::Open));
+
+    // Fails fast without reaching the service
+    let output = swarm.execute("service.call", &1).await;
+    assert!(output.is_circuit_open());
+    assert_eq!(output.get_status(), OutputStatus::CircuitOpen);
+    assert_eq!(service(&swarm).calls.load(Ordering::SeqCst), 3);
+
+    // Failed half-open trial opens the circuit again
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert_eq!(
+        swarm.get_circuit_state("service"),
+        Some(CircuitState::HalfOpen)
+    );
+    let output = swarm.execute("service.call", &1).await;
+    assert!(!output.is_circuit_open());
+    assert_eq!(swarm.get_circuit_state("service"), Some(CircuitState::Open));
+
+    // Successful half-open trial closes the circuit
+    service(&swarm).down.store(false, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    let output = swarm.execute("service.call", &1).await;
+    assert!(output.is_success());
+    assert_eq!(
+        swarm.get_circuit_state("service"),
+        Some(CircuitState::Closed)
+    );
+}
+
+#[tokio::test]
+pub async fn default_circuit_breaker() {
+    let mut swarm = Swarm::default();
+    swarm.register_agent("service", ServiceAgent::default());
+    assert_eq!(swarm.get_circuit_state("service"), None);
+
+    swarm.set_default_circuit_breaker(Some(CircuitBreakerConfig::new(2, Duration::from_secs(60))));
+    service(&swarm).down.store(true, Ordering::SeqCst);
+
+    // Invalid payloads are caller errors and do not open the circuit
+    for _ in 0..3 {
+        swarm.execute("service.call", &"not a number").await;
+    }
+    assert_eq!(
+        swarm.get_circuit_state("service"),
+        Some(CircuitState::Closed)
+    );
+
+    swarm.execute("service.call", &1).await;
+    swarm.execute("service.call", &1).await;
+    assert_eq!(swarm.get_circuit_state("service"), Some(CircuitState::Open));
+}
+
+#[tokio::test]
+pub async fn retries_stop_on_open_circuit() {
+    let mut swarm = Swarm::default();
+    let policy = RetryPolicy::new(10)
+        .with_backoff(Duration::from_millis(1), Duration::from_millis(1))
+        .retry_on(|error| error.code == ErrorCode::ActionFailed);
+    let options = AgentOptions::new()
+        .with_retry(policy)
+        .with_circuit_breaker(CircuitBreakerConfig::new(2, Duration::from_secs(60)));
+    swarm.register_agent_with_options("service", ServiceAgent::default(), options);
+    service(&swarm).down.store(true, Ordering::SeqCst);
+
+    let output = swarm.execute("service.call", &1).await;
+    assert!(output.is_circuit_open());
+    assert_eq!(service(&swarm).calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+pub async fn dropped_trial() {
+    let mut swarm = Swarm::default();
+    let config = CircuitBreakerConfig::new(1, Duration::from_millis(50));
+    let options = AgentOptions::new().with_circuit_breaker(config);
+    swarm.register_agent_with_options("service", ServiceAgent::default(), options);
+    service(&swarm).down.store(true, Ordering::SeqCst);
+    swarm.execute("service.call", &1).await;
+    assert_eq!(swarm.get_circuit_state("service"), Some(CircuitState::Open));
+
+    // The trial future is dropped before the agent answers
+    tokio::time::sleep(Duration::from_millis(80)).await;
+    let trial = swarm.execute("service.wait", &1000);
+    assert!(tokio::time::timeout(Duration::from_millis(20), trial)
+        .await
+        .is_err());
+    assert_eq!(
+        swarm.get_circuit_state("service"),
+        Some(CircuitState::HalfOpen)
+    );
+
+    // Another trial may go through
+    service(&swarm).down.store(false, Ordering::SeqCst);
+    let output = swarm.execute("service.call", &1).await;
+    assert!(output.is_success());
+    assert_eq!(
+        swarm.get_circuit_state("service"),
+        Some(CircuitState::Closed)
+    );
+}
+
+#[tokio::test]
+pub async fn stale_outcome() {
+    let mut swarm = Swarm::default();
+    let config = CircuitBreakerConfig::new(1, Duration::from_secs(60));
+    let options = AgentOptions::new().with_circuit_breaker(config);
+    swarm.register_agent_with_options("service", ServiceAgent::default(), options);
+
+    // A call let through before the circuit opened succeeds after it did
+    let stale = swarm.execute("service.wait", &100);
+    let failure = async {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        service(&swarm).down.store(true, Ordering::SeqCst);
+        swarm.execute("service.call", &1).await;
+    };
+    let (output, _) = tokio::join!(stale, failure);
+    assert!(output.is_success());
+    assert_eq!(swarm.get_circuit_state("service"), Some(CircuitState::Open));
+}
+
+#[tokio::test]
+pub async fn single_trial() {
+    let mut swarm = Swarm::default();
+    let config = CircuitBreakerConfig::new(1, Duration::from_millis(50));
+    let options = AgentOptions::new().with_circuit_breaker(config);
+    swarm.register_agent_with_options("service", ServiceAgent::default(), options);
+
+    // A call let through before the circuit opened completes during the trial
+    let stale = swarm.execute("service.wait", &150);
+    let trials = async {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        service(&swarm).down.store(true, Ordering::SeqCst);
+        swarm.execute("service.call", &1).await;
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        let trial = swarm.execute("service.wait", &200);
+        let second_trial = async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            assert_eq!(
+                swarm.get_circuit_state("service"),
+                Some(CircuitState::HalfOpen)
+            );
+            swarm.execute("service.wait", &1).await
+        };
+        tokio::join!(trial, second_trial)
+    };
+    let (_, (trial, second_trial)) = tokio::join!(stale, trials);
+    assert!(second_trial.is_circuit_open());
+    assert!(trial.is_success());
+    assert_eq!(
+        swarm.get_circuit_state("service"),
+        Some(CircuitState::Closed)
+    );
+}
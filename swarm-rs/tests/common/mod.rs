@@ -0,0 +1,154 @@
+#![allow(dead_code)]
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use serde_json::Value;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+/// Response returned by the [`MockServer`], its body written in several parts.
+pub struct MockResponse {
+    pub status: u16,
+    pub content_type: String,
+    pub headers: Vec<(String, String)>,
+    pub parts: Vec<String>,
+}
+
+impl MockResponse {
+    pub fn json(status: u16, body: &Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json".to_string(),
+            headers: vec![],
+            parts: vec![body.to_string()],
+        }
+    }
+
+    /// Server-Sent Events body made of the given `data:` payloads.
+    pub fn sse(data: &[&str]) -> Self {
+        Self {
+            status: 200,
+            content_type: "text/event-stream".to_string(),
+            headers: vec![],
+            parts: data
+                .iter()
+                .map(|data| format!("data: {}\n\n", data))
+                .collect(),
+        }
+    }
+
+    /// Plain text body.
+    pub fn text(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain".to_string(),
+            headers: vec![],
+            parts: vec![body.to_string()],
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// Local HTTP server answering the queued responses in order, and recording the requests.
+#[derive(Clone)]
+pub struct MockServer {
+    pub url: String,
+    responses: Arc<Mutex<VecDeque<MockResponse>>>,
+    requests: Arc<Mutex<Vec<Value>>>,
+    heads: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockServer {
+    pub async fn start(responses: Vec<MockResponse>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server = Self {
+            url: format!(
+                "http://{}/v1/chat/completions",
+                listener.local_addr().unwrap()
+            ),
+            responses: Arc::new(Mutex::new(responses.into())),
+            requests: Arc::new(Mutex::new(vec![])),
+            heads: Arc::new(Mutex::new(vec![])),
+        };
+        let handler = server.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let handler = handler.clone();
+                tokio::spawn(async move { handler.handle(stream).await });
+            }
+        });
+        server
+    }
+
+    pub fn get_requests(&self) -> Vec<Value> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Lowercased request lines and headers.
+    pub fn get_heads(&self) -> Vec<String> {
+        self.heads.lock().unwrap().clone()
+    }
+
+    async fn handle(&self, mut stream: TcpStream) {
+        let mut request = vec![];
+        let mut buffer = [0; 4096];
+        let body_start = loop {
+            let read = stream.read(&mut buffer).await.unwrap_or(0);
+            if read == 0 {
+                return;
+            }
+            request.extend_from_slice(&buffer[..read]);
+            if let Some(position) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                break position + 4;
+            }
+        };
+        let head = String::from_utf8_lossy(&request[..body_start]).to_lowercase();
+        let content_length: usize = head
+            .lines()
+            .find_map(|line| line.strip_prefix("content-length:"))
+            .and_then(|length| length.trim().parse().ok())
+            .unwrap_or(0);
+        while request.len() < body_start + content_length {
+            let read = stream.read(&mut buffer).await.unwrap_or(0);
+            if read == 0 {
+                break;
+            }
+            request.extend_from_slice(&buffer[..read]);
+        }
+        let body = serde_json::from_slice(&request[body_start..]).unwrap_or(Value::Null);
+        self.requests.lock().unwrap().push(body);
+        self.heads.lock().unwrap().push(head);
+
+        let response = self
+            .responses
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or_else(|| MockResponse::json(500, &Value::from("No mock response")));
+        let mut head = format!(
+            "HTTP/1.1 {} Mock\r\ncontent-type: {}\r\nconnection: close\r\n",
+            response.status, response.content_type
+        );
+        for (name, value) in &response.headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        head.push_str("\r\n");
+        let _ = stream.write_all(head.as_bytes()).await;
+        for part in response.parts {
+            let _ = stream.write_all(part.as_bytes()).await;
+            let _ = stream.flush().await;
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let _ = stream.shutdown().await;
+    }
+}
@@ -0,0 +1,108 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use swarm_rs::prelude::*;
+
+#[derive(Serialize, Deserialize)]
+pub struct Sleep {
+    millis: u64,
+    fail: bool,
+}
+
+impl Sleep {
+    pub fn new(millis: u64, fail: bool) -> Self {
+        Self { millis, fail }
+    }
+}
+
+#[derive(Default)]
+pub struct SleepAgent {}
+
+#[agent]
+impl SleepAgent {
+    #[agent_action]
+    pub async fn sleep(&self, sleep: Sleep) -> Result<u64, String> {
+        tokio::time::sleep(Duration::from_millis(sleep.millis)).await;
+        if sleep.fail {
+            Err(format!("Failed after {}ms", sleep.millis))
+        } else {
+            Ok(sleep.millis)
+        }
+    }
+}
+
+fn new_swarm() -> Swarm {
+    let mut swarm = Swarm::default();
+    swarm.register_agent("sleeper", SleepAgent::default());
+    swarm
+}
+
+#[tokio::test]
+pub async fn execute_all_runs_concurrently() {
+    let swarm = new_swarm();
+    let actions = vec![
+        Action::new("sleeper.sleep", Sleep::new(300, false)),
+        Action::new("sleeper.sleep", Sleep::new(100, false)),
+        Action::new("sleeper.sleep", Sleep::new(200, false)),
+    ];
+
+    let start = Instant::now();
+    let outputs = swarm.execute_all(&actions).await;
+    assert!(start.elapsed() < Duration::from_millis(550));
+
+    // Outputs follow the actions order, not the completion order
+    let durations: Vec<u64> = outputs.iter().map(|o| o.get_payload().unwrap()).collect();
+    assert_eq!(durations, vec![300, 100, 200]);
+}
+
+#[tokio::test]
+pub async fn execute_join_limits_concurrency() {
+    let swarm = new_swarm();
+    let actions: Vec<Action> = (0..4)
+        .map(|_| Action::new("sleeper.sleep", Sleep::new(100, false)))
+        .collect();
+
+    let start = Instant::now();
+    let options = JoinOptions::new().with_max_concurrency(2);
+    let outputs = swarm.execute_join(&actions, &options).await;
+    assert!(start.elapsed() >= Duration::from_millis(200));
+    assert!(outputs.iter().all(|o| o.is_success()));
+}
+
+#[tokio::test]
+pub async fn execute_join_fail_fast() {
+    let swarm = new_swarm();
+    let actions = vec![
+        Action::new("sleeper.sleep", Sleep::new(50, true)),
+        Action::new("sleeper.sleep", Sleep::new(500, false)),
+        Action::new("sleeper.sleep", Sleep::new(10, false)),
+    ];
+
+    let start = Instant::now();
+    let options = JoinOptions::new().with_max_concurrency(2).fail_fast();
+    let outputs = swarm.execute_join(&actions, &options).await;
+    assert!(start.elapsed() < Duration::from_millis(400));
+
+    assert_eq!(outputs.len(), 3);
+    assert_eq!(outputs[0].get_error_message(), "Failed after 50ms");
+    assert!(outputs[1].is_cancelled());
+    assert!(outputs[2].is_cancelled());
+}
+
+#[tokio::test]
+pub async fn execute_join_collect_all() {
+    let swarm = new_swarm();
+    let actions = vec![
+        Action::new("sleeper.sleep", Sleep::new(10, true)),
+        Action::new("sleeper.sleep", Sleep::new(50, false)),
+        Action::new("unknown.sleep", Sleep::new(10, false)),
+    ];
+
+    let outputs = swarm.execute_join(&actions, &JoinOptions::new()).await;
+    assert!(!outputs[0].is_success());
+    assert!(outputs[1].is_success());
+    assert_eq!(
+        outputs[2].get_error().unwrap().code,
+        ErrorCode::AgentNotFound
+    );
+}
@@ -0,0 +1,145 @@
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+use swarm_rs::prelude::*;
+
+#[derive(Default)]
+pub struct MathAgent {}
+
+#[agent]
+impl MathAgent {
+    #[agent_action]
+    pub async fn slow_double(&self, value: i64) -> Result<i64, String> {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        Ok(value * 2)
+    }
+
+    #[agent_action]
+    pub async fn sum(&self, values: Vec<i64>) -> Result<i64, String> {
+        Ok(values.iter().sum())
+    }
+
+    #[agent_action]
+    pub async fn fail(&self, _value: Value) -> Result<i64, String> {
+        Err("Always failing".to_string())
+    }
+}
+
+fn new_dag(nodes: Value, edges: Value) -> Dag {
+    serde_json::from_value(json!({ "id": "math_dag", "nodes": nodes, "edges": edges })).unwrap()
+}
+
+#[tokio::test]
+pub async fn parallel_branches() {
+    let mut swarm = Swarm::default();
+    swarm.register_agent("math", MathAgent::default());
+
+    let dag = new_dag(
+        json!([
+            { "id": "left", "action": "math.slow_double", "input": "$.input.a" },
+            { "id": "right", "action": "math.slow_double", "input": "$.input.b" },
+            { "id": "total", "action": "math.sum", "input": [0, 0] }
+        ]),
+        json!([
+            { "from": "left", "to": "total", "input": "0" },
+            { "from": "right", "to": "total", "input": "1" }
+        ]),
+    );
+
+    let start = Instant::now();
+    let report = dag
+        .execute(json!({ "a": 2, "b": 5 }), &swarm)
+        .await
+        .unwrap();
+    assert!(start.elapsed() < Duration::from_millis(390));
+
+    assert!(report.success);
+    assert!(report.skipped.is_empty());
+    assert_eq!(
+        report
+            .get_output("total")
+            .unwrap()
+            .get_payload::<i64>()
+            .unwrap(),
+        14
+    );
+    assert_eq!(report.nodes["left"].action, "math.slow_double");
+    assert!(report.nodes["left"].duration_ms >= 200);
+}
+
+#[tokio::test]
+pub async fn skipped_nodes() {
+    let mut swarm = Swarm::default();
+    swarm.register_agent("math", MathAgent::default());
+
+    let dag = new_dag(
+        json!([
+            { "id": "broken", "action": "math.fail", "input": null },
+            { "id": "double", "action": "math.slow_double", "input": 1 },
+            { "id": "after_broken", "action": "math.slow_double", "input": 0 },
+            { "id": "last", "action": "math.sum", "input": [], "depends_on": ["after_broken", "double"] }
+        ]),
+        json!([{ "from": "broken", "to": "after_broken" }]),
+    );
+    swarm.register_agent(&dag.get_id(), dag);
+
+    let output = swarm.execute("math_dag.run", &json!({})).await;
+    let report = output.get_payload::<DagReport>().unwrap();
+    assert!(!report.success);
+    assert_eq!(report.skipped, vec!["after_broken", "last"]);
+    assert_eq!(
+        report
+            .get_output("broken")
+            .unwrap()
+            .get_error()
+            .unwrap()
+            .message,
+        "Always failing"
+    );
+    assert_eq!(
+        report
+            .get_output("double")
+            .unwrap()
+            .get_payload::<i64>()
+            .unwrap(),
+        2
+    );
+}
+
+#[tokio::test]
+pub async fn invalid_graph() {
+    let swarm = Swarm::default();
+
+    let dag = new_dag(
+        json!([
+            { "id": "a", "action": "math.sum", "input": [] },
+            { "id": "b", "action": "math.sum", "input": [] }
+        ]),
+        json!([{ "from": "a", "to": "b" }, { "from": "b", "to": "a" }]),
+    );
+    let error = dag.execute(json!({}), &swarm).await.unwrap_err();
+    assert_eq!(error.message, "The graph has a cycle");
+
+    let dag = new_dag(
+        json!([{ "id": "a", "action": "math.sum", "input": [] }]),
+        json!([{ "from": "missing", "to": "a" }]),
+    );
+    let error = dag.execute(json!({}), &swarm).await.unwrap_err();
+    assert_eq!(error.message, "Unknown node missing");
+}
+
+#[tokio::test]
+pub async fn duplicated_dependencies() {
+    let mut swarm = Swarm::default();
+    swarm.register_agent("math", MathAgent::default());
+
+    let dag = new_dag(
+        json!([
+            { "id": "a", "action": "math.sum", "input": [1, 2] },
+            { "id": "b", "action": "math.sum", "input": [4], "depends_on": ["a", "a"] }
+        ]),
+        json!([]),
+    );
+    let report = dag.execute(json!({}), &swarm).await.unwrap();
+    assert_eq!(report.get_output("b").unwrap().get_payload::<i64>().unwrap(), 4);
+}
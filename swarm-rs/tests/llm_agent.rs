@@ -1,10 +1,14 @@
-use swarm_rs::prelude::*;
+use serde_json::Value;
+use swarm_rs::{llm_agent::llm_response::LLMResponse, prelude::*};
 
 #[tokio::test]
 pub async fn llm_agent() {
     let llm_agent: LLMAgent = json_io::load("test-data/agents/llm_summarizer.json").unwrap();
 
+    // Replays the recorded completion, `SWARM_FIXTURES=record` records it again
+    let fixture = SwarmFixture::from_env("test-data/fixtures/llm_agent.json").unwrap();
     let mut agent_swarm = Swarm::default();
+    agent_swarm.add_agent_middleware(&llm_agent.get_id(), fixture.clone());
     agent_swarm.register_agent(&llm_agent.get_id(), llm_agent);
 
     let mut prompt = LLMPrompt::new();
@@ -20,12 +24,10 @@ Context Awareness: Understanding and interpreting its environment to make inform
 Agentic AI is particularly valuable in scenarios requiring adaptability and complex decision-making, such as robotics, autonomous vehicles, personalized assistants, and strategic planning. However, its development raises important considerations around safety, ethical alignment, and accountability, as systems with high levels of autonomy may act in ways that are unpredictable or misaligned with human intentions.
 "#);
 
-    let action = Action::new("llm-summarizer.execute", prompt);
-    let output = agent_swarm.execute_action(&action).await;
-
-    if output.is_success() {
-        println!("SUCCESS : {}", output.get_value());
-    } else {
-        println!("ERROR : {}", output.get_error_message());
-    }
+    let summarizer = LLMAgentClient::new(&agent_swarm, "llm-summarizer");
+    let response: LLMResponse = summarizer.execute(prompt).await.unwrap();
+    assert!(fixture.get_misses().is_empty());
+    let summary: Value = response.get_output().unwrap();
+    assert!(summary["summary"].is_string());
+    assert!(summary["keywords"].is_string());
 }
@@ -0,0 +1,160 @@
+mod common;
+
+use common::{MockResponse, MockServer};
+use serde_json::{json, Value};
+use swarm_rs::{
+    llm_agent::conversation::{ConversationReply, FileConversationStore},
+    prelude::*,
+};
+
+fn reply(content: &str) -> MockResponse {
+    MockResponse::json(
+        200,
+        &json!({
+            "created": 1,
+            "model": "mock-model",
+            "object": "chat.completion",
+            "system_fingerprint": "mock",
+            "usage": { "completion_tokens": 3, "prompt_tokens": 20, "total_tokens": 23 },
+            "choices": [{
+                "index": 0,
+                "finish_reason": "stop",
+                "message": { "role": "assistant", "content": content }
+            }]
+        }),
+    )
+}
+
+fn new_agent(server: &MockServer, budget: Value) -> LLMAgent {
+    serde_json::from_value(json!({
+        "id": "tutor",
+        "client": { "endpoint": server.url, "model": "mock-model" },
+        "role": "You are a {subject} tutor.",
+        "goal": "Help the student.",
+        "output_rules": "Keep answers short.",
+        "conversation_budget": budget
+    }))
+    .unwrap()
+}
+
+fn turn(message: &str) -> Value {
+    json!({ "conversation_id": "student-1", "message": message, "values": { "subject": "rust" } })
+}
+
+fn roles_and_contents(request: &Value) -> Vec<(String, String)> {
+    request["messages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|message| {
+            (
+                message["role"].as_str().unwrap().to_string(),
+                message["content"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+pub async fn multi_turn_conversation() {
+    let server = MockServer::start(vec![
+        reply("A borrow is a reference."),
+        reply("Use &mut."),
+        reply("Yes."),
+    ])
+    .await;
+    let mut swarm = Swarm::default();
+    swarm.register_agent("tutor", new_agent(&server, json!({ "max_turns": 2 })));
+
+    let output = swarm
+        .execute("tutor.converse", &turn("What is a borrow?"))
+        .await;
+    let first: ConversationReply = output.get_payload().unwrap();
+    assert_eq!(first.message, "A borrow is a reference.");
+    assert_eq!(first.turns, 1);
+    swarm.execute("tutor.converse", &turn("And mutable?")).await;
+    let output = swarm.execute("tutor.converse", &turn("Is that all?")).await;
+    let last: ConversationReply = output.get_payload().unwrap();
+    assert_eq!(last.turns, 2);
+    assert_eq!(last.usage.total_tokens, 23);
+
+    let requests = server.get_requests();
+    let system = (
+        "system".to_string(),
+        "You are a rust tutor.\n\nHelp the student.\n\nKeep answers short.".to_string(),
+    );
+    assert_eq!(
+        roles_and_contents(&requests[1]),
+        vec![
+            system.clone(),
+            ("user".to_string(), "What is a borrow?".to_string()),
+            (
+                "assistant".to_string(),
+                "A borrow is a reference.".to_string()
+            ),
+            ("user".to_string(), "And mutable?".to_string()),
+        ]
+    );
+    // The oldest turn is trimmed
+    assert_eq!(
+        roles_and_contents(&requests[2]),
+        vec![
+            system,
+            ("user".to_string(), "And mutable?".to_string()),
+            ("assistant".to_string(), "Use &mut.".to_string()),
+            ("user".to_string(), "Is that all?".to_string()),
+        ]
+    );
+}
+
+#[tokio::test]
+pub async fn persisted_conversation() {
+    let store_dir = "test-data/out/conversations";
+    let _ = std::fs::remove_dir_all(store_dir);
+    let server = MockServer::start(vec![reply("Hello!"), reply("You said hi.")]).await;
+
+    let agent = new_agent(&server, json!({}))
+        .with_conversation_store(FileConversationStore::new(store_dir));
+    let mut swarm = Swarm::default();
+    swarm.register_agent("tutor", agent);
+    swarm.execute("tutor.converse", &turn("hi")).await;
+
+    // Another agent instance continues the stored conversation
+    let agent = new_agent(&server, json!({}))
+        .with_conversation_store(FileConversationStore::new(store_dir));
+    let mut swarm = Swarm::default();
+    swarm.register_agent("tutor", agent);
+    let output = swarm
+        .execute("tutor.converse", &turn("What did I say?"))
+        .await;
+    assert_eq!(output.get_payload::<ConversationReply>().unwrap().turns, 2);
+    let history = roles_and_contents(&server.get_requests()[1]);
+    assert_eq!(history[2], ("assistant".to_string(), "Hello!".to_string()));
+}
+
+#[tokio::test]
+pub async fn concurrent_turns() {
+    let server = MockServer::start(vec![reply("One."), reply("Two."), reply("Three.")]).await;
+    let mut swarm = Swarm::default();
+    swarm.register_agent("tutor", new_agent(&server, json!({})));
+
+    let first = Action::new("tutor.converse", turn("First?")).with_user("alice");
+    let second = Action::new("tutor.converse", turn("Second?")).with_user("alice");
+    let (first, second) = tokio::join!(swarm.execute_action(&first), swarm.execute_action(&second));
+    assert!(first.is_success() && second.is_success());
+
+    // Both turns are kept
+    let action = Action::new("tutor.converse", turn("Third?")).with_user("alice");
+    let output = swarm.execute_action(&action).await;
+    assert_eq!(output.get_payload::<ConversationReply>().unwrap().turns, 3);
+    assert_eq!(roles_and_contents(&server.get_requests()[2]).len(), 6);
+
+    // Other users can not continue the conversation
+    let action = Action::new("tutor.converse", turn("Hijack")).with_user("mallory");
+    let output = swarm.execute_action(&action).await;
+    let error = output.get_error().unwrap();
+    assert_eq!(error.code, ErrorCode::InvalidPayload);
+    assert_eq!(error.message, "Conversation student-1 belongs to another user");
+    assert!(!swarm.execute("tutor.converse", &turn("Anonymous")).await.is_success());
+    assert_eq!(server.get_requests().len(), 3);
+}
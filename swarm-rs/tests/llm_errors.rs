@@ -0,0 +1,157 @@
+mod common;
+
+use common::{MockResponse, MockServer};
+use serde::Deserialize;
+use std::time::Duration;
+
+use serde_json::json;
+use swarm_rs::{
+    llm_agent::{
+        llm_client::LLMClient,
+        llm_error::{LLMError, LLMErrorKind},
+        llm_message::{LLMMessage, LLMMessageBuilder},
+        llm_response::LLMResponse,
+    },
+    prelude::*,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct Keywords {
+    pub keywords: Vec<String>,
+}
+
+fn messages() -> Vec<LLMMessage> {
+    LLMMessageBuilder::new().add_user_message("Hello").build()
+}
+
+async fn complete(response: MockResponse) -> Result<LLMResponse, LLMError> {
+    let server = MockServer::start(vec![response]).await;
+    let client = LLMClient::new(&server.url, "mock-model", None);
+    client.autocomplete(&messages(), None).await
+}
+
+#[tokio::test]
+pub async fn lenient_decoding() {
+    let body = json!({
+        "choices": [{
+            "message": { "role": "assistant", "content": "{\"keywords\": [\"rust\"]}" },
+            "finish_reason": null
+        }]
+    });
+    let response = complete(MockResponse::json(200, &body)).await.unwrap();
+    assert_eq!(response.usage.total_tokens, 0);
+    assert_eq!(response.choices[0].finish_reason, "");
+    let raw: serde_json::Value = serde_json::from_str(response.raw.as_ref().unwrap()).unwrap();
+    assert_eq!(raw, body);
+    let output: Keywords = response.get_output().unwrap();
+    assert_eq!(output.keywords, vec!["rust"]);
+
+    let body = json!({
+        "choices": [{
+            "message": { "role": "assistant", "content": "{\"keywords\": [\"ru" },
+            "finish_reason": "length"
+        }]
+    });
+    let response = complete(MockResponse::json(200, &body)).await.unwrap();
+    let error = response.get_output::<Keywords>().unwrap_err();
+    assert!(error.starts_with("Invalid JSON output : EOF"));
+    assert!(error.ends_with("truncated by the token limit"));
+    let error = response.get_output::<Vec<u32>>().unwrap_err();
+    assert!(error.starts_with("Invalid JSON output"));
+}
+
+#[tokio::test]
+pub async fn provider_errors() {
+    let error = complete(
+        MockResponse::json(
+            429,
+            &json!({ "error": { "message": "Rate limit reached", "type": "requests", "code": "rate_limit_exceeded" } }),
+        )
+        .with_header("retry-after", "7"),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error.kind, LLMErrorKind::RateLimit);
+    assert_eq!(error.status, Some(429));
+    assert_eq!(error.retry_after, Some(7));
+    assert_eq!(error.message, "Rate limit reached");
+    assert!(error.body.unwrap().contains("rate_limit_exceeded"));
+
+    let error = complete(MockResponse::json(
+        400,
+        &json!({ "error": { "message": "This model's maximum context length is 4096 tokens", "type": "invalid_request_error", "code": "context_length_exceeded" } }),
+    ))
+    .await
+    .unwrap_err();
+    assert_eq!(error.kind, LLMErrorKind::ContextLength);
+
+    // Error reported with a success status
+    let error = complete(MockResponse::json(
+        200,
+        &json!({ "error": "model 'mock-model' not found" }),
+    ))
+    .await
+    .unwrap_err();
+    assert_eq!(error.kind, LLMErrorKind::Provider);
+    assert_eq!(error.message, "model 'mock-model' not found");
+
+    let error = complete(MockResponse::text(200, "<html>Gateway</html>"))
+        .await
+        .unwrap_err();
+    assert_eq!(error.kind, LLMErrorKind::Decode);
+    assert!(error.message.starts_with("Invalid JSON body"));
+    assert_eq!(error.body.unwrap(), "<html>Gateway</html>");
+
+    let error = complete(MockResponse::json(200, &json!({ "id": "1" })))
+        .await
+        .unwrap_err();
+    assert_eq!(error.kind, LLMErrorKind::Decode);
+    assert!(error.message.contains("missing field `choices`"));
+}
+
+#[tokio::test]
+pub async fn agent_errors() {
+    let server = MockServer::start(vec![
+        MockResponse::json(
+            401,
+            &json!({ "error": { "message": "Invalid API key", "code": "invalid_api_key" } }),
+        ),
+        MockResponse::json(503, &json!({ "error": { "message": "Overloaded" } })),
+        MockResponse::json(429, &json!({ "error": { "message": "Slow down", "code": "rate_limit_exceeded" } }))
+            .with_header("retry-after", "3"),
+    ])
+    .await;
+    let llm_agent: LLMAgent = serde_json::from_value(json!({
+        "id": "assistant",
+        "client": { "endpoint": server.url, "model": "mock-model" },
+        "role": "You are an assistant.",
+        "goal": "{question}",
+        "output_rules": "",
+        "output_format": null
+    }))
+    .unwrap();
+    let mut swarm = Swarm::default();
+    swarm.register_agent(&llm_agent.get_id(), llm_agent);
+
+    let mut prompt = LLMPrompt::new();
+    prompt.add_content("question", "Hello?");
+    let output = swarm.execute("assistant.execute", &prompt).await;
+    let error = output.get_error().unwrap();
+    assert_eq!(error.code, ErrorCode::ActionFailed);
+    assert_eq!(error.message, "LLM auth error (401): Invalid API key");
+    assert_eq!(error.details, Some(json!({ "kind": "auth", "status": 401, "retry_after": null })));
+    assert!(!error.retryable);
+
+    let output = swarm.execute("assistant.execute", &prompt).await;
+    let error = output.get_error().unwrap();
+    assert_eq!(error.message, "LLM provider error (503): Overloaded");
+    assert_eq!(error.details.as_ref().unwrap()["kind"], "provider");
+    assert!(error.retryable);
+
+    let output = swarm.execute("assistant.execute", &prompt).await;
+    let error = output.get_error().unwrap();
+    assert_eq!(error.code, ErrorCode::RateLimited);
+    assert_eq!(error.details, Some(json!({ "kind": "rate_limit", "status": 429, "retry_after": 3 })));
+    assert_eq!(error.get_retry_after(), Some(Duration::from_secs(3)));
+    assert!(error.retryable);
+}
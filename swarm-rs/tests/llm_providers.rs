@@ -0,0 +1,270 @@
+mod common;
+
+use common::{MockResponse, MockServer};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use swarm_rs::{
+    llm_agent::{
+        llm_client::LLMClient, llm_error::LLMErrorKind, llm_message::LLMMessageBuilder,
+        llm_provider::LLMProviderKind, llm_response::LLMResponse,
+    },
+    prelude::*,
+};
+
+#[derive(Deserialize)]
+pub struct Answer {
+    pub summary: String,
+}
+
+#[derive(Default)]
+pub struct ClockAgent {}
+
+#[agent]
+impl ClockAgent {
+    #[agent_action]
+    pub async fn time(&self, city: String) -> Result<String, String> {
+        Ok(format!("Noon in {}", city))
+    }
+}
+
+fn new_swarm(server: &MockServer, provider: &str, output_format: Option<Value>) -> Swarm {
+    let llm_agent: LLMAgent = serde_json::from_value(json!({
+        "id": "assistant",
+        "client": {
+            "provider": provider,
+            "endpoint": server.url,
+            "model": "mock-model",
+            "api_key": "secret"
+        },
+        "role": "You are a concise assistant.",
+        "goal": "{question}",
+        "output_rules": "Answer in one sentence.",
+        "output_format": output_format,
+        "tools": ["clock.time"]
+    }))
+    .unwrap();
+    let mut swarm = Swarm::default();
+    swarm.register_agent("clock", ClockAgent::default());
+    swarm.register_agent(&llm_agent.get_id(), llm_agent);
+    swarm
+}
+
+fn question(text: &str) -> LLMPrompt {
+    let mut prompt = LLMPrompt::new();
+    prompt.add_content("question", text);
+    prompt
+}
+
+fn answer_format() -> Value {
+    json!({
+        "name": "answer",
+        "strict": true,
+        "schema": {
+            "type": "object",
+            "properties": { "summary": { "type": "string" } },
+            "required": ["summary"]
+        }
+    })
+}
+
+fn anthropic_message(content: Value, stop_reason: &str) -> MockResponse {
+    MockResponse::json(
+        200,
+        &json!({
+            "id": "msg_1",
+            "type": "message",
+            "role": "assistant",
+            "model": "mock-model",
+            "content": content,
+            "stop_reason": stop_reason,
+            "usage": { "input_tokens": 12, "output_tokens": 5 }
+        }),
+    )
+}
+
+#[tokio::test]
+pub async fn anthropic_tool_calls_and_structured_output() {
+    let server = MockServer::start(vec![
+        anthropic_message(
+            json!([
+                { "type": "text", "text": "Let me check." },
+                { "type": "tool_use", "id": "toolu_1", "name": "clock__time", "input": { "input": "Paris" } }
+            ]),
+            "tool_use",
+        ),
+        anthropic_message(
+            json!([{ "type": "tool_use", "id": "toolu_2", "name": "answer", "input": { "summary": "It is noon." } }]),
+            "tool_use",
+        ),
+    ])
+    .await;
+    let swarm = new_swarm(&server, "anthropic", Some(answer_format()));
+
+    let output = swarm
+        .execute("assistant.execute", &question("What time is it in Paris?"))
+        .await;
+    let response: LLMResponse = output.get_payload().unwrap();
+    let answer: Answer = response.get_output().unwrap();
+    assert_eq!(answer.summary, "It is noon.");
+    assert_eq!(response.choices[0].finish_reason, "stop");
+    assert_eq!(response.usage.total_tokens, 17);
+
+    let heads = server.get_heads();
+    assert!(heads[0].contains("x-api-key: secret"));
+    assert!(heads[0].contains("anthropic-version: 2023-06-01"));
+    assert!(!heads[0].contains("authorization"));
+
+    let requests = server.get_requests();
+    assert_eq!(requests.len(), 2);
+    let first = &requests[0];
+    assert_eq!(first["system"], "You are a concise assistant.");
+    assert_eq!(first["max_tokens"], 4096);
+    assert_eq!(first["messages"].as_array().unwrap().len(), 1);
+    assert_eq!(first["messages"][0]["role"], "user");
+    assert!(first["messages"][0]["content"][0]["text"]
+        .as_str()
+        .unwrap()
+        .starts_with("What time is it in Paris?"));
+    assert_eq!(first["tools"][0]["name"], "clock__time");
+    assert_eq!(first["tools"][0]["input_schema"]["type"], "object");
+    assert_eq!(first["tools"][1]["name"], "answer");
+    assert_eq!(first["tools"][1]["input_schema"], answer_format()["schema"]);
+
+    let messages = requests[1]["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[0]["role"], "user");
+    assert_eq!(messages[1]["role"], "assistant");
+    assert_eq!(messages[1]["content"][0]["type"], "tool_use");
+    assert_eq!(
+        messages[1]["content"][0]["input"],
+        json!({ "input": "Paris" })
+    );
+    assert_eq!(messages[2]["role"], "user");
+    assert_eq!(messages[2]["content"][0]["type"], "tool_result");
+    assert_eq!(messages[2]["content"][0]["tool_use_id"], "toolu_1");
+    assert_eq!(messages[2]["content"][0]["content"], "\"Noon in Paris\"");
+}
+
+#[tokio::test]
+pub async fn anthropic_forces_output_tool() {
+    let server = MockServer::start(vec![anthropic_message(
+        json!([{ "type": "tool_use", "id": "toolu_1", "name": "answer", "input": { "summary": "Hi" } }]),
+        "tool_use",
+    )])
+    .await;
+    let client = LLMClient::new(&server.url, "mock-model", None)
+        .with_provider(LLMProviderKind::Anthropic)
+        .with_max_tokens(256);
+    let messages = LLMMessageBuilder::new()
+        .add_system_message("Be brief.")
+        .add_user_message("Say hi")
+        .build();
+
+    let response = client
+        .autocomplete(&messages, Some(answer_format().to_string()))
+        .await
+        .unwrap();
+    assert_eq!(response.get_message().unwrap(), "{\"summary\":\"Hi\"}");
+
+    let request = &server.get_requests()[0];
+    assert_eq!(request["max_tokens"], 256);
+    assert_eq!(request["system"], "Be brief.");
+    assert_eq!(
+        request["tool_choice"],
+        json!({ "type": "tool", "name": "answer" })
+    );
+
+    assert!(client.stream(&messages, None).await.is_err());
+}
+
+#[tokio::test]
+pub async fn ollama_chat() {
+    let server = MockServer::start(vec![
+        MockResponse::json(
+            200,
+            &json!({
+                "model": "mock-model",
+                "created_at": "2024-01-01T00:00:00Z",
+                "message": {
+                    "role": "assistant",
+                    "content": "",
+                    "tool_calls": [{ "function": { "name": "clock__time", "arguments": { "input": "Oslo" } } }]
+                },
+                "done": true,
+                "done_reason": "stop"
+            }),
+        ),
+        MockResponse::json(
+            200,
+            &json!({
+                "model": "mock-model",
+                "created_at": "2024-01-01T00:00:01Z",
+                "message": { "role": "assistant", "content": "{\"summary\":\"Noon in Oslo\"}" },
+                "done": true,
+                "done_reason": "stop",
+                "prompt_eval_count": 20,
+                "eval_count": 6
+            }),
+        ),
+    ])
+    .await;
+    let swarm = new_swarm(&server, "ollama", Some(answer_format()));
+
+    let output = swarm
+        .execute("assistant.execute", &question("What time is it in Oslo?"))
+        .await;
+    let response: LLMResponse = output.get_payload().unwrap();
+    let answer: Answer = response.get_output().unwrap();
+    assert_eq!(answer.summary, "Noon in Oslo");
+    assert_eq!(response.usage.prompt_tokens, 20);
+    assert_eq!(response.usage.completion_tokens, 6);
+
+    let requests = server.get_requests();
+    assert_eq!(requests[0]["stream"], false);
+    assert_eq!(requests[0]["format"], answer_format()["schema"]);
+    assert_eq!(requests[0]["messages"][0]["role"], "system");
+    assert_eq!(requests[0]["tools"][0]["function"]["name"], "clock__time");
+    assert!(server.get_heads()[0].contains("authorization: bearer secret"));
+
+    let messages = requests[1]["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 5);
+    assert_eq!(
+        messages[3]["tool_calls"][0]["function"]["arguments"],
+        json!({ "input": "Oslo" })
+    );
+    assert_eq!(messages[4]["role"], "tool");
+    assert_eq!(messages[4]["content"], "\"Noon in Oslo\"");
+}
+
+#[test]
+pub fn provider_defaults_to_openai() {
+    let client: LLMClient = serde_json::from_value(json!({
+        "endpoint": "http://localhost/v1/chat/completions",
+        "model": "mock-model"
+    }))
+    .unwrap();
+    assert_eq!(client.get_provider(), LLMProviderKind::OpenAI);
+    assert_eq!(serde_json::to_value(&client).unwrap()["provider"], "openai");
+
+    let client: LLMClient = serde_json::from_value(json!({
+        "provider": "anthropic",
+        "endpoint": "https://api.anthropic.com/v1/messages",
+        "model": "claude",
+        "api_key": "key"
+    }))
+    .unwrap();
+    assert_eq!(client.get_provider(), LLMProviderKind::Anthropic);
+}
+
+#[tokio::test]
+pub async fn malformed_api_key() {
+    let messages = LLMMessageBuilder::new().add_user_message("Say hi").build();
+    for provider in [LLMProviderKind::OpenAI, LLMProviderKind::Anthropic] {
+        let api_key = Some("key\nwith newline".to_string());
+        let client =
+            LLMClient::new("http://localhost:1/v1", "mock-model", api_key).with_provider(provider);
+        let error = client.autocomplete(&messages, None).await.unwrap_err();
+        assert_eq!(error.kind, LLMErrorKind::InvalidRequest);
+        assert!(!error.message.contains("newline"));
+    }
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use common::{MockResponse, MockServer};
+use futures::StreamExt;
+use serde_json::json;
+use swarm_rs::{
+    llm_agent::{
+        llm_client::LLMClient, llm_error::LLMErrorKind, llm_message::LLMMessageBuilder,
+        llm_response::LLMUsage, llm_stream::LLMStreamEvent,
+    },
+    prelude::*,
+};
+
+fn chunk(content: &str) -> String {
+    json!({ "choices": [{ "index": 0, "delta": { "content": content }, "finish_reason": null }] })
+        .to_string()
+}
+
+#[tokio::test]
+pub async fn stream_completion() {
+    let first = chunk("Agentic");
+    let second = chunk(" AI");
+    let finish =
+        json!({ "choices": [{ "index": 0, "delta": {}, "finish_reason": "stop" }] }).to_string();
+    let usage = json!({
+        "choices": [],
+        "usage": { "completion_tokens": 2, "prompt_tokens": 9, "total_tokens": 11 }
+    })
+    .to_string();
+    let server = MockServer::start(vec![MockResponse::sse(&[
+        &first, &second, &finish, &usage, "[DONE]",
+    ])])
+    .await;
+
+    let client = LLMClient::new(&server.url, "mock-model", Some("secret".to_string()));
+    let messages = LLMMessageBuilder::new()
+        .add_user_message("What is agentic AI?")
+        .build();
+    let events: Vec<LLMStreamEvent> = client
+        .stream(&messages, None)
+        .await
+        .unwrap()
+        .map(|event| event.unwrap())
+        .collect()
+        .await;
+
+    assert_eq!(
+        events,
+        vec![
+            LLMStreamEvent::Delta("Agentic".to_string()),
+            LLMStreamEvent::Delta(" AI".to_string()),
+            LLMStreamEvent::Done {
+                finish_reason: Some("stop".to_string()),
+                usage: Some(LLMUsage {
+                    completion_tokens: 2,
+                    prompt_tokens: 9,
+                    total_tokens: 11
+                }),
+            }
+        ]
+    );
+    let request = &server.get_requests()[0];
+    assert_eq!(request["stream"], true);
+    assert_eq!(request["model"], "mock-model");
+}
+
+#[tokio::test]
+pub async fn stream_from_agent() {
+    let server = MockServer::start(vec![
+        MockResponse::sse(&[&chunk("Short"), &chunk(" summary")]),
+        MockResponse::json(401, &json!({ "error": { "message": "Invalid API key" } })),
+    ])
+    .await;
+    let llm_agent: LLMAgent = serde_json::from_value(json!({
+        "id": "summarizer",
+        "client": { "endpoint": server.url, "model": "mock-model" },
+        "role": "You are a helpful assistant.",
+        "goal": "Summarize : {content}",
+        "output_rules": "Be concise."
+    }))
+    .unwrap();
+
+    let mut prompt = LLMPrompt::new();
+    prompt.add_content("content", "Agentic AI systems act autonomously.");
+    let mut stream = llm_agent.stream(&prompt).await.unwrap();
+    let mut text = String::new();
+    let mut finished = false;
+    while let Some(event) = stream.next().await {
+        match event.unwrap() {
+            LLMStreamEvent::Delta(delta) => text.push_str(&delta),
+            // Closed without [DONE]
+            LLMStreamEvent::Done { usage, .. } => {
+                assert!(usage.is_none());
+                finished = true;
+            }
+        }
+    }
+    assert_eq!(text, "Short summary");
+    assert!(finished);
+    let request = &server.get_requests()[0];
+    assert_eq!(
+        request["messages"][1]["content"],
+        "Summarize : Agentic AI systems act autonomously."
+    );
+
+    let error = llm_agent.stream(&prompt).await.err().unwrap();
+    assert_eq!(error.kind, LLMErrorKind::Auth);
+    assert_eq!(error.status, Some(401));
+    assert_eq!(error.message, "Invalid API key");
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use common::{MockResponse, MockServer};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use swarm_rs::{llm_agent::llm_response::LLMResponse, prelude::*};
+
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct Summary {
+    pub summary: String,
+    pub keywords: Vec<String>,
+}
+
+fn completion(content: &str) -> MockResponse {
+    MockResponse::json(
+        200,
+        &json!({
+            "model": "mock-model",
+            "usage": { "completion_tokens": 1, "prompt_tokens": 1, "total_tokens": 2 },
+            "choices": [{
+                "index": 0,
+                "finish_reason": "stop",
+                "message": { "role": "assistant", "content": content }
+            }]
+        }),
+    )
+}
+
+fn new_agent(server: &MockServer, max_repairs: usize) -> LLMAgent {
+    serde_json::from_value(json!({
+        "id": "summarizer",
+        "client": { "endpoint": server.url, "model": "mock-model" },
+        "role": "You are a helpful research assistant.",
+        "goal": "Summarize : {content}",
+        "output_rules": "Answer with a summary and keywords.",
+        "output_format": {
+            "name": "summary_schema",
+            "strict": true,
+            "schema": {
+                "type": "object",
+                "properties": {
+                    "summary": { "type": "string" },
+                    "keywords": { "type": "array", "items": { "type": "string" } }
+                },
+                "required": ["summary", "keywords"]
+            }
+        },
+        "max_repairs": max_repairs
+    }))
+    .unwrap()
+}
+
+fn content(text: &str) -> LLMPrompt {
+    let mut prompt = LLMPrompt::new();
+    prompt.add_content("content", text);
+    prompt
+}
+
+#[tokio::test]
+pub async fn output_repair() {
+    let server = MockServer::start(vec![
+        completion("Agentic AI acts on its own."),
+        completion(r#"{"summary": "Agentic AI acts on its own.", "keywords": "ai"}"#),
+        completion(r#"{"summary": "Agentic AI acts on its own.", "keywords": ["ai"]}"#),
+    ])
+    .await;
+    let mut swarm = Swarm::default();
+    swarm.register_agent("summarizer", new_agent(&server, 2));
+
+    let output = swarm
+        .execute("summarizer.execute", &content("Agentic AI"))
+        .await;
+    let response: LLMResponse = output.get_payload().unwrap();
+    assert!(response.output.is_some());
+    let summary: Summary = response.get_output().unwrap();
+    assert_eq!(
+        summary,
+        Summary {
+            summary: "Agentic AI acts on its own.".to_string(),
+            keywords: vec!["ai".to_string()]
+        }
+    );
+
+    let requests = server.get_requests();
+    assert_eq!(requests.len(), 3);
+    let messages = requests[2]["messages"].as_array().unwrap();
+    let repairs: Vec<&Value> = messages
+        .iter()
+        .filter(|message| message["role"] == "user")
+        .collect();
+    assert_eq!(repairs.len(), 2);
+    assert!(repairs[0]["content"]
+        .as_str()
+        .unwrap()
+        .contains("Invalid JSON output"));
+    assert!(repairs[1]["content"]
+        .as_str()
+        .unwrap()
+        .contains("/keywords : \"ai\" is not of type \"array\""));
+    assert_eq!(
+        messages[messages.len() - 2]["content"],
+        r#"{"summary": "Agentic AI acts on its own.", "keywords": "ai"}"#
+    );
+}
+
+#[tokio::test]
+pub async fn output_repair_exhausted() {
+    let server = MockServer::start(vec![
+        completion(r#"{"summary": "Agentic AI"}"#),
+        completion(r#"{"summary": "Agentic AI"}"#),
+    ])
+    .await;
+    let agent = new_agent(&server, 1);
+
+    let swarm = Swarm::default();
+    let error = agent
+        .execute_as::<Summary>(&content("Agentic AI"), &swarm)
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidOutput);
+    assert_eq!(
+        error.message,
+        "Output still invalid after 1 repairs : \"keywords\" is a required property"
+    );
+    assert_eq!(server.get_requests().len(), 2);
+}
@@ -0,0 +1,168 @@
+mod common;
+
+use common::{MockResponse, MockServer};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use swarm_rs::{llm_agent::llm_response::LLMResponse, prelude::*};
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct ForecastQuery {
+    /// City name
+    pub city: String,
+    pub days: Option<u32>,
+}
+
+#[derive(Default)]
+pub struct WeatherAgent {}
+
+#[agent]
+impl WeatherAgent {
+    #[agent_action]
+    pub async fn forecast(&self, query: ForecastQuery) -> Result<String, String> {
+        Ok(format!(
+            "Sunny in {} for {} days",
+            query.city,
+            query.days.unwrap_or(1)
+        ))
+    }
+
+    #[agent_action]
+    pub async fn alerts(&self, region: String) -> Result<Vec<String>, String> {
+        Err(format!("No alert service for {}", region))
+    }
+}
+
+fn completion(message: Value) -> MockResponse {
+    MockResponse::json(
+        200,
+        &json!({
+            "created": 1,
+            "model": "mock-model",
+            "object": "chat.completion",
+            "system_fingerprint": "mock",
+            "usage": { "completion_tokens": 1, "prompt_tokens": 1, "total_tokens": 2 },
+            "choices": [{ "index": 0, "finish_reason": "stop", "message": message }]
+        }),
+    )
+}
+
+fn tool_call(id: &str, name: &str, arguments: Value) -> MockResponse {
+    completion(json!({
+        "role": "assistant",
+        "content": null,
+        "tool_calls": [{
+            "id": id,
+            "type": "function",
+            "function": { "name": name, "arguments": arguments.to_string() }
+        }]
+    }))
+}
+
+fn new_swarm(server: &MockServer, max_iterations: usize) -> Swarm {
+    let llm_agent: LLMAgent = serde_json::from_value(json!({
+        "id": "assistant",
+        "client": { "endpoint": server.url, "model": "mock-model" },
+        "role": "You are a travel assistant.",
+        "goal": "{question}",
+        "output_rules": "Answer in one sentence.",
+        "tools": ["weather.forecast", "weather.alerts"],
+        "max_iterations": max_iterations
+    }))
+    .unwrap();
+    let mut swarm = Swarm::default();
+    swarm.register_agent("weather", WeatherAgent::default());
+    swarm.register_agent(&llm_agent.get_id(), llm_agent);
+    swarm
+}
+
+fn question(text: &str) -> LLMPrompt {
+    let mut prompt = LLMPrompt::new();
+    prompt.add_content("question", text);
+    prompt
+}
+
+#[tokio::test]
+pub async fn tool_calling_loop() {
+    let server = MockServer::start(vec![
+        tool_call(
+            "call_1",
+            "weather__forecast",
+            json!({ "city": "Paris", "days": 2 }),
+        ),
+        tool_call("call_2", "weather__alerts", json!({ "input": "Alps" })),
+        completion(json!({ "role": "assistant", "content": "Pack sunglasses." })),
+    ])
+    .await;
+    let swarm = new_swarm(&server, 5);
+
+    let output = swarm
+        .execute("assistant.execute", &question("Weather in Paris?"))
+        .await;
+    let response: LLMResponse = output.get_payload().unwrap();
+    assert_eq!(response.get_message().unwrap(), "Pack sunglasses.");
+
+    let requests = server.get_requests();
+    assert_eq!(requests.len(), 3);
+    let tools = &requests[0]["tools"];
+    assert_eq!(tools[0]["function"]["name"], "weather__forecast");
+    assert_eq!(tools[0]["function"]["description"], "weather.forecast");
+    let parameters = &tools[0]["function"]["parameters"];
+    assert_eq!(parameters["type"], "object");
+    assert_eq!(parameters["properties"]["city"]["type"], "string");
+    assert_eq!(parameters["required"], json!(["city"]));
+    // Non object payloads are wrapped
+    assert_eq!(
+        tools[1]["function"]["parameters"]["properties"]["input"]["type"],
+        "string"
+    );
+
+    let messages = requests[2]["messages"].as_array().unwrap();
+    let results: Vec<(&str, &str)> = messages
+        .iter()
+        .filter(|message| message["role"] == "tool")
+        .map(|message| {
+            (
+                message["tool_call_id"].as_str().unwrap(),
+                message["content"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        results,
+        vec![
+            ("call_1", "\"Sunny in Paris for 2 days\""),
+            ("call_2", "{\"error\":\"No alert service for Alps\"}")
+        ]
+    );
+    assert_eq!(messages[3]["tool_calls"][0]["id"], "call_1");
+}
+
+#[tokio::test]
+pub async fn max_iterations() {
+    let server = MockServer::start(vec![
+        tool_call("call_1", "weather__forecast", json!({ "city": "Oslo" })),
+        tool_call("call_2", "weather__unknown", json!({})),
+    ])
+    .await;
+    let swarm = new_swarm(&server, 2);
+
+    let output = swarm
+        .execute("assistant.execute", &question("Weather in Oslo?"))
+        .await;
+    assert_eq!(
+        output.get_error_message(),
+        "No final answer after 2 iterations"
+    );
+    let last_messages = server.get_requests()[1]["messages"].clone();
+    assert_eq!(
+        last_messages.as_array().unwrap().last().unwrap()["content"],
+        "\"Sunny in Oslo for 1 days\""
+    );
+
+    let descriptor = swarm.describe_action("weather.forecast").unwrap();
+    assert!(descriptor.input_schema.is_some());
+    // LLMPrompt has no JSON Schema
+    let descriptor = swarm.describe_action("assistant.execute").unwrap();
+    assert!(descriptor.input_schema.is_none());
+    assert_eq!(descriptor.kind, ActionKind::Action);
+}
@@ -0,0 +1,268 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{MockResponse, MockServer};
+use rocket::{
+    http::{ContentType, Header, Status},
+    local::asynchronous::Client,
+};
+use serde_json::{json, Value};
+use swarm_rs::{
+    prelude::*,
+    utils::{file_io, json_io},
+    web::{web_swarm::WebSwarm, AuthAgent, NewUser, UserAuth},
+};
+
+fn completion(prompt_tokens: usize, completion_tokens: usize) -> MockResponse {
+    MockResponse::json(
+        200,
+        &json!({
+            "model": "mock-model-2024",
+            "usage": {
+                "prompt_tokens": prompt_tokens,
+                "completion_tokens": completion_tokens,
+                "total_tokens": prompt_tokens + completion_tokens
+            },
+            "choices": [{
+                "index": 0,
+                "finish_reason": "stop",
+                "message": { "role": "assistant", "content": "Draft" }
+            }]
+        }),
+    )
+}
+
+fn new_swarm(server: &MockServer, tracker: UsageTracker) -> Swarm {
+    let llm_agent: LLMAgent = serde_json::from_value(json!({
+        "id": "writer",
+        "client": { "endpoint": server.url, "model": "mock-model" },
+        "role": "You are a technical writer.",
+        "goal": "Write about {topic}",
+        "output_rules": ""
+    }))
+    .unwrap();
+    let definition: WorkflowDefinition = serde_json::from_value(json!({
+        "id": "drafting",
+        "steps": [
+            { "type": "action", "id": "outline", "action": "writer.execute", "input": { "values": "$.input" } },
+            { "type": "action", "id": "draft", "action": "writer.execute", "input": { "values": "$.input" } }
+        ]
+    }))
+    .unwrap();
+
+    let mut swarm = Swarm::default();
+    swarm.register_agent("writer", llm_agent);
+    swarm.register_agent("drafting", WorkflowAgent::new(definition));
+    swarm.set_usage_tracker(tracker);
+    swarm
+}
+
+fn topic(topic: &str) -> Value {
+    json!({ "values": { "topic": topic } })
+}
+
+#[tokio::test]
+pub async fn usage_attribution() {
+    let server = MockServer::start(vec![
+        completion(1000, 500),
+        completion(2000, 1000),
+        completion(100, 50),
+    ])
+    .await;
+    let prices = PriceTable::new().with_price("mock-model", ModelPrice::new(2.0, 10.0));
+    let swarm = new_swarm(&server, UsageTracker::new().with_prices(prices));
+
+    let action = Action::new("drafting.run", json!({ "topic": "agents" })).with_user("alice");
+    assert!(swarm.execute_action(&action).await.is_success());
+    assert!(swarm.execute("writer.execute", &topic("swarms")).await.is_success());
+
+    let tracker = swarm.get_usage_tracker();
+    let records = tracker.get_records(&UsageFilter::new());
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0].action_id, "writer.execute");
+    assert_eq!(records[0].agent_id, "writer");
+    assert_eq!(records[0].workflow_id.as_deref(), Some("drafting"));
+    assert!(records[0].run_id.is_some());
+    assert_eq!(records[0].run_id, records[1].run_id);
+    assert_eq!(records[0].user_id.as_deref(), Some("alice"));
+    assert_eq!(records[0].model, "mock-model");
+    assert!((records[0].cost - 0.007).abs() < 1e-9);
+    assert_eq!(records[2].run_id, None);
+    assert_eq!(records[2].user_id, None);
+
+    let totals = tracker.totals(&UsageFilter::for_user("alice"));
+    assert_eq!(totals.calls, 2);
+    assert_eq!(totals.prompt_tokens, 3000);
+    assert_eq!(totals.completion_tokens, 1500);
+    assert_eq!(totals.total_tokens, 4500);
+    assert!((totals.cost - 0.021).abs() < 1e-9);
+
+    let report = tracker.report(&UsageFilter::new(), Some(UsageGroup::Workflow));
+    assert_eq!(report.totals.calls, 3);
+    assert_eq!(report.groups["drafting"].total_tokens, 4500);
+    assert_eq!(report.groups[""].total_tokens, 150);
+}
+
+#[tokio::test]
+pub async fn usage_budgets() {
+    let server = MockServer::start(vec![
+        completion(1000, 500),
+        completion(1000, 500),
+        completion(100, 50),
+        completion(100, 50),
+        completion(100, 50),
+    ])
+    .await;
+    let tracker = UsageTracker::new()
+        .with_user_budget("bob", Budget::tokens(1500))
+        .with_workflow_budget("drafting", Budget::tokens(1000));
+    let swarm = new_swarm(&server, tracker);
+
+    // The first step exhausts the budget of the run
+    let output = swarm.execute("drafting.run", &json!({ "topic": "agents" })).await;
+    let error = output.get_error().unwrap();
+    assert_eq!(error.message, "Step draft failed");
+    assert_eq!(error.root_cause().code, ErrorCode::BudgetExceeded);
+    assert!(error
+        .root_cause()
+        .message
+        .starts_with("Budget of workflow drafting exceeded by run drafting-"));
+
+    let action = Action::new("writer.execute", topic("agents")).with_user("bob");
+    assert!(swarm.execute_action(&action).await.is_success());
+    let output = swarm.execute_action(&action).await;
+    assert_eq!(output.get_error().unwrap().code, ErrorCode::BudgetExceeded);
+    assert_eq!(output.get_error_message(), "Budget of user bob exceeded");
+
+    // Other users and new runs have their own budget
+    let action = Action::new("writer.execute", topic("agents")).with_user("carol");
+    assert!(swarm.execute_action(&action).await.is_success());
+    let output = swarm.execute("drafting.run", &json!({ "topic": "agents" })).await;
+    assert!(output.is_success());
+    assert_eq!(server.get_requests().len(), 5);
+}
+
+pub struct EditorAgent {}
+
+#[agent]
+impl EditorAgent {
+    #[agent_workflow]
+    pub async fn review(&self, topic: String, swarm: &Swarm) -> Result<Vec<String>, AgentError> {
+        let mut drafts = vec![];
+        for _ in 0..2 {
+            let output = swarm.execute("writer.execute", &self::topic(&topic)).await;
+            drafts.push(output.get_payload::<Value>()?.to_string());
+        }
+        Ok(drafts)
+    }
+}
+
+#[tokio::test]
+pub async fn workflow_run_usage() {
+    let server = MockServer::start(vec![completion(1000, 500), completion(1000, 500)]).await;
+    let tracker = UsageTracker::new()
+        .with_workflow_budget("editor.review", Budget::tokens(1000))
+        .with_workflow_budget("pipeline", Budget::tokens(1000));
+    let mut swarm = new_swarm(&server, tracker);
+    swarm.register_agent("editor", EditorAgent {});
+    let dag: Dag = serde_json::from_value(json!({
+        "id": "pipeline",
+        "nodes": [
+            { "id": "outline", "action": "writer.execute", "input": topic("agents") },
+            { "id": "draft", "action": "writer.execute", "input": topic("agents"), "depends_on": ["outline"] }
+        ]
+    }))
+    .unwrap();
+    swarm.register_agent("pipeline", dag);
+
+    // The calls of workflow methods and DAG nodes are attributed to their run
+    let output = swarm.execute("editor.review", &"agents").await;
+    assert_eq!(output.get_error().unwrap().code, ErrorCode::BudgetExceeded);
+    let output = swarm.execute("pipeline.run", &json!({})).await;
+    let report: DagReport = output.get_payload().unwrap();
+    assert!(report.run_id.starts_with("pipeline-"));
+    let draft = report.get_output("draft").unwrap();
+    assert_eq!(draft.get_error().unwrap().code, ErrorCode::BudgetExceeded);
+
+    let records = swarm.get_usage_tracker().get_records(&UsageFilter::new());
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].workflow_id.as_deref(), Some("editor.review"));
+    assert_eq!(records[1].workflow_id.as_deref(), Some("pipeline"));
+    assert_eq!(records[1].run_id.as_ref(), Some(&report.run_id));
+    assert_ne!(records[0].run_id, records[1].run_id);
+    assert_eq!(server.get_requests().len(), 2);
+}
+
+#[tokio::test]
+pub async fn budget_period() {
+    let server = MockServer::start(vec![completion(1000, 500), completion(100, 50)]).await;
+    let tracker = UsageTracker::new()
+        .with_user_budget("dave", Budget::tokens(1000))
+        .with_budget_period(Duration::from_millis(200))
+        .with_max_records(1);
+    let swarm = new_swarm(&server, tracker);
+
+    let action = Action::new("writer.execute", topic("agents")).with_user("dave");
+    assert!(swarm.execute_action(&action).await.is_success());
+    let output = swarm.execute_action(&action).await;
+    assert_eq!(output.get_error().unwrap().code, ErrorCode::BudgetExceeded);
+
+    // The usage starts over with the next period, only the last records being kept
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert!(swarm.execute_action(&action).await.is_success());
+    let records = swarm.get_usage_tracker().get_records(&UsageFilter::new());
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].usage.total_tokens, 150);
+}
+
+#[tokio::test]
+pub async fn web_usage() {
+    let server = MockServer::start(vec![completion(1000, 500), completion(100, 50)]).await;
+    let mut swarm = new_swarm(&server, UsageTracker::new());
+    let users_db = "test-data/out/usage-web-users.json";
+    file_io::remove_file(users_db);
+    let mut auth_config: Value = json_io::load("test-data/agents/auth.json").unwrap();
+    auth_config["db_path"] = json!(users_db);
+    let auth: AuthAgent = serde_json::from_value(auth_config).unwrap();
+    let alice = NewUser::new("alice", "p4ssw0rd", "Alice", "", vec!["Writer"]);
+    let alice: UserAuth = auth.register_user(alice).await.unwrap();
+    swarm.register_agent("Auth", auth);
+
+    let client = Client::tracked(WebSwarm::serve(swarm)).await.unwrap();
+    let action = Action::new("writer.execute", topic("agents"));
+    let response = client
+        .post("/api/action")
+        .header(ContentType::JSON)
+        .header(Header::new("Token", alice.token.to_string()))
+        .body(serde_json::to_string(&action).unwrap())
+        .dispatch()
+        .await;
+    assert!(response.into_json::<Output>().await.unwrap().is_success());
+    let response = client
+        .post("/api/action")
+        .header(ContentType::JSON)
+        .body(serde_json::to_string(&action).unwrap())
+        .dispatch()
+        .await;
+    assert!(response.into_json::<Output>().await.unwrap().is_success());
+
+    let response = client
+        .get("/api/usage?group_by=user")
+        .header(Header::new("Token", alice.token.to_string()))
+        .dispatch()
+        .await;
+    let report: UsageReport = response.into_json().await.unwrap();
+    assert_eq!(report.totals.calls, 1);
+    assert_eq!(report.totals.total_tokens, 1500);
+    assert_eq!(report.groups["alice"].calls, 1);
+
+    let response = client.get("/api/usage").dispatch().await;
+    assert_eq!(response.status(), Status::Forbidden);
+    let response = client
+        .get("/api/usage?group_by=planet")
+        .header(Header::new("Token", alice.token.to_string()))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::BadRequest);
+}
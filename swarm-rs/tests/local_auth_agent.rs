@@ -25,7 +25,7 @@ pub async fn auth_agent() {
     println!("{}", output);
     assert!(output.is_success());
 
-    let user_auth: UserAuth = output.get_payload();
+    let user_auth: UserAuth = output.get_payload().unwrap();
 
     let payload = UserCredentials {
         login: "admin".to_string(),
@@ -1,7 +1,7 @@
 use swarm_rs::prelude::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Default, Serialize, Deserialize)]
 pub struct MacroAgent {}
 
 #[agent]
@@ -10,6 +10,9 @@ impl MacroAgent {
         Self {}
     }
 
+    /// Greets the user.
+    ///
+    /// Prints the message as well.
     #[agent_action]
     pub async fn print_hello(&self, name: String) -> Result<String, String> {
         let message = format!("Hello {}", name);
@@ -43,4 +46,102 @@ async fn execute_action<T: Serialize>(agent_swarm: &Swarm, action_id: &str, payl
     } else {
         println!("ERROR : {}", output.get_error_message());
     }
-}
\ No newline at end of file
+}
+
+#[test]
+pub fn describe_agent() {
+    let actions = MacroAgent::new().describe();
+    assert_eq!(actions.len(), 2);
+
+    let print_hello = &actions[0];
+    assert_eq!(print_hello.name, "print_hello");
+    assert_eq!(print_hello.kind, ActionKind::Action);
+    assert_eq!(
+        print_hello.description.as_deref(),
+        Some("Greets the user.\n\nPrints the message as well.")
+    );
+    assert_eq!(print_hello.input_schema.as_ref().unwrap()["type"], "string");
+    assert_eq!(print_hello.output_schema.as_ref().unwrap()["type"], "string");
+    assert_eq!(print_hello.error_schema.as_ref().unwrap()["type"], "string");
+
+    let test_workflow = &actions[1];
+    assert_eq!(test_workflow.kind, ActionKind::Workflow);
+    assert_eq!(test_workflow.description, None);
+}
+
+#[tokio::test]
+pub async fn typed_client() {
+    let mut agent_swarm = Swarm::default();
+    agent_swarm.register_agent("TestMacro", MacroAgent::new());
+
+    let client = MacroAgentClient::new(&agent_swarm, "TestMacro");
+    let message: String = client.print_hello("User".to_string()).await.unwrap();
+    assert_eq!(message, "Hello User");
+    let message = client.test_workflow("User".to_string()).await.unwrap();
+    assert_eq!(message, "Workflow invocation User");
+
+    // Errors of the dispatch are reported as agent errors
+    let client = MacroAgentClient::new(&agent_swarm, "Missing");
+    let error = client.print_hello("User".to_string()).await.err().unwrap();
+    assert_eq!(error.code, ErrorCode::AgentNotFound);
+}
+
+#[test]
+pub fn action_registry() {
+    let mut agent_swarm = Swarm::default();
+    agent_swarm.register_agent("TestMacro", MacroAgent::new());
+    let searx_agent: SearxAgent = json_io::load("test-data/agents/searxng.json").unwrap();
+    agent_swarm.register_agent(&searx_agent.get_id(), searx_agent);
+
+    assert_eq!(
+        agent_swarm.list_actions(),
+        vec![
+            "TestMacro.print_hello",
+            "TestMacro.test_workflow",
+            "searx_ng.search"
+        ]
+    );
+    let agents = agent_swarm.describe_agents();
+    let search = &agents[1].actions[0];
+    assert_eq!(search.input_schema.as_ref().unwrap()["required"][0], "terms");
+    // SearxResponse has no JSON Schema
+    assert!(search.output_schema.is_none());
+    assert_eq!(
+        serde_json::to_value(search).unwrap()["kind"],
+        serde_json::json!("action")
+    );
+}
+
+mod private_agent {
+    use super::*;
+
+    /// Payload only known to this module.
+    #[derive(Serialize, Deserialize)]
+    struct Note {
+        text: String,
+    }
+
+    #[derive(Default)]
+    struct NotesAgent {}
+
+    #[agent]
+    impl NotesAgent {
+        #[agent_action]
+        async fn echo(&self, note: Note) -> Result<String, String> {
+            Ok(note.text)
+        }
+    }
+
+    #[tokio::test]
+    pub async fn private_client() {
+        let mut agent_swarm = Swarm::default();
+        agent_swarm.register_agent("notes", NotesAgent::default());
+
+        // The client is as private as the agent actions
+        let client = NotesAgentClient::new(&agent_swarm, "notes");
+        let note = Note {
+            text: "Draft".to_string(),
+        };
+        assert_eq!(client.echo(note).await.unwrap(), "Draft");
+    }
+}
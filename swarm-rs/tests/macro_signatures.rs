@@ -0,0 +1,148 @@
+use std::{
+    fmt::Display,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use serde::Serialize;
+use serde_json::json;
+use swarm_rs::prelude::*;
+
+#[derive(Debug)]
+pub struct ParseError {
+    pub input: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unable to parse {}", self.input)
+    }
+}
+
+#[derive(Serialize)]
+pub struct QuotaError {
+    pub limit: usize,
+}
+
+#[derive(Default)]
+pub struct CounterAgent {
+    count: AtomicUsize,
+}
+
+#[agent]
+impl CounterAgent {
+    /// Increments the counter.
+    #[agent_action]
+    pub fn increment(&self) -> usize {
+        self.count.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    #[agent_action]
+    pub fn reset(&self) {
+        self.count.store(0, Ordering::Relaxed);
+    }
+
+    #[agent_action]
+    pub fn add(&self, amount: usize, times: Option<usize>) -> Result<usize, QuotaError> {
+        let total = amount * times.unwrap_or(1);
+        if total > 100 {
+            return Err(QuotaError { limit: 100 });
+        }
+        Ok(self.count.fetch_add(total, Ordering::Relaxed) + total)
+    }
+
+    #[agent_action]
+    pub async fn parse(&self, input: String) -> Result<usize, ParseError> {
+        input.parse().map_err(|_| ParseError { input })
+    }
+
+    /// The swarm is found by its type, wherever it is declared.
+    #[agent_workflow]
+    pub async fn add_all(
+        &self,
+        swarm: &Swarm,
+        amounts: Vec<usize>,
+        label: String,
+    ) -> Result<String, AgentError> {
+        for amount in amounts {
+            swarm
+                .execute("counter.add", &json!({ "amount": amount }))
+                .await
+                .get_payload::<usize>()?;
+        }
+        let count = swarm.execute("counter.increment", &()).await;
+        Ok(format!("{} {}", label, count.get_payload::<usize>()? - 1))
+    }
+}
+
+fn new_swarm() -> Swarm {
+    let mut swarm = Swarm::default();
+    swarm.register_agent("counter", CounterAgent::default());
+    swarm
+}
+
+#[tokio::test]
+pub async fn sync_and_zero_argument_actions() {
+    let swarm = new_swarm();
+    assert_eq!(swarm.execute("counter.increment", &()).await.get_payload::<usize>().unwrap(), 1);
+    // The payload of the actions without argument is ignored
+    assert_eq!(swarm.execute("counter.increment", &"ignored").await.get_payload::<usize>().unwrap(), 2);
+    let output = swarm.execute("counter.reset", &()).await;
+    assert!(output.is_success());
+    assert_eq!(output.get_value(), &json!(null));
+    assert_eq!(swarm.execute("counter.increment", &()).await.get_payload::<usize>().unwrap(), 1);
+}
+
+#[tokio::test]
+pub async fn named_arguments() {
+    let swarm = new_swarm();
+    let output = swarm.execute("counter.add", &json!({ "amount": 5, "times": 2 })).await;
+    assert_eq!(output.get_payload::<usize>().unwrap(), 10);
+    // Optional arguments may be omitted
+    let output = swarm.execute("counter.add", &json!({ "amount": 5 })).await;
+    assert_eq!(output.get_payload::<usize>().unwrap(), 15);
+
+    let output = swarm.execute("counter.add", &json!({ "times": 2 })).await;
+    let error = output.get_error().unwrap();
+    assert_eq!(error.code, ErrorCode::InvalidPayload);
+    assert_eq!(error.message, "Unable to get argument amount");
+
+    let output = swarm
+        .execute("counter.add_all", &json!({ "amounts": [1, 2, 3], "label": "Total" }))
+        .await;
+    assert_eq!(output.get_payload::<String>().unwrap(), "Total 21");
+
+    let descriptor = swarm.describe_action("counter.add").unwrap();
+    let schema = descriptor.input_schema.unwrap();
+    assert_eq!(schema["type"], "object");
+    assert_eq!(schema["properties"]["amount"]["type"], "integer");
+    assert_eq!(schema["required"], json!(["amount"]));
+    let descriptor = swarm.describe_action("counter.increment").unwrap();
+    assert_eq!(descriptor.input_schema.unwrap()["type"], "null");
+    assert_eq!(descriptor.output_schema.unwrap()["type"], "integer");
+}
+
+#[tokio::test]
+pub async fn custom_errors() {
+    let swarm = new_swarm();
+    let output = swarm.execute("counter.parse", &"forty-two").await;
+    let error = output.get_error().unwrap();
+    assert_eq!(error.code, ErrorCode::ActionFailed);
+    assert_eq!(error.message, "Unable to parse forty-two");
+
+    let output = swarm.execute("counter.add", &json!({ "amount": 60, "times": 2 })).await;
+    let error = output.get_error().unwrap();
+    assert_eq!(error.message, "{\"limit\":100}");
+    assert_eq!(error.details, Some(json!({ "limit": 100 })));
+}
+
+#[tokio::test]
+pub async fn typed_client() {
+    let swarm = new_swarm();
+    let counter = CounterAgentClient::new(&swarm, "counter");
+    assert_eq!(counter.increment().await.unwrap(), 1);
+    assert_eq!(counter.add(4, Some(2)).await.unwrap(), 9);
+    counter.reset().await.unwrap();
+    assert_eq!(counter.add_all(vec![2, 3], "Sum".to_string()).await.unwrap(), "Sum 5");
+    let error = counter.parse("x".to_string()).await.err().unwrap();
+    assert_eq!(error.message, "Unable to parse x");
+}
@@ -0,0 +1,138 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+use swarm_rs::prelude::*;
+
+#[derive(Default)]
+pub struct EchoAgent {}
+
+#[agent]
+impl EchoAgent {
+    #[agent_action]
+    pub async fn echo(&self, text: String) -> Result<String, String> {
+        Ok(text)
+    }
+}
+
+/// Rejects empty payloads before they reach the agent
+pub struct Validation {}
+
+#[async_trait]
+impl Middleware for Validation {
+    async fn before(&self, invocation: &Invocation<'_>, _swarm: &Swarm) -> Option<Output> {
+        match invocation.get_action().get_payload::<String>() {
+            Ok(text) if text.is_empty() => Some(Output::from_error(AgentError::new(
+                ErrorCode::InvalidPayload,
+                "Empty text",
+            ))),
+            _ => None,
+        }
+    }
+}
+
+/// Records the middleware hooks calls
+pub struct Audit {
+    name: String,
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl Middleware for Audit {
+    async fn before(&self, invocation: &Invocation<'_>, _swarm: &Swarm) -> Option<Output> {
+        let event = format!("{}:before:{}", self.name, invocation.get_action().get_id());
+        self.events.lock().unwrap().push(event);
+        None
+    }
+
+    async fn after(&self, invocation: &Invocation<'_>, output: Output, _swarm: &Swarm) -> Output {
+        let event = format!("{}:after:{}", self.name, invocation.get_action().get_id());
+        self.events.lock().unwrap().push(event);
+        output
+    }
+}
+
+/// Rewrites successful outputs
+pub struct Uppercase {}
+
+#[async_trait]
+impl Middleware for Uppercase {
+    async fn after(&self, _invocation: &Invocation<'_>, output: Output, _swarm: &Swarm) -> Output {
+        match output.get_payload::<String>() {
+            Ok(text) => Output::new_success(text.to_uppercase()),
+            Err(_) => output,
+        }
+    }
+}
+
+pub struct Counter {
+    count: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Middleware for Counter {
+    async fn before(&self, _invocation: &Invocation<'_>, _swarm: &Swarm) -> Option<Output> {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        None
+    }
+}
+
+#[tokio::test]
+pub async fn middleware_chain() {
+    let events = Arc::new(Mutex::new(vec![]));
+    let mut swarm = Swarm::default();
+    swarm.register_agent("echo", EchoAgent::default());
+    swarm.add_middleware(Audit {
+        name: "outer".to_string(),
+        events: events.clone(),
+    });
+    swarm.add_middleware(Validation {});
+    swarm.add_middleware(Audit {
+        name: "inner".to_string(),
+        events: events.clone(),
+    });
+    swarm.add_middleware(Uppercase {});
+
+    let output = swarm.execute("echo.echo", &"hello").await;
+    assert_eq!(output.get_payload::<String>().unwrap(), "HELLO");
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![
+            "outer:before:echo.echo",
+            "inner:before:echo.echo",
+            "inner:after:echo.echo",
+            "outer:after:echo.echo",
+        ]
+    );
+
+    // Short-circuited: the inner middlewares and the agent are skipped
+    events.lock().unwrap().clear();
+    let output = swarm.execute("echo.echo", &"").await;
+    assert_eq!(output.get_error().unwrap().code, ErrorCode::InvalidPayload);
+    assert_eq!(output.agent_id, "echo");
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec!["outer:before:echo.echo", "outer:after:echo.echo"]
+    );
+}
+
+#[tokio::test]
+pub async fn agent_middleware() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut swarm = Swarm::default();
+    swarm.register_agent("echo-1", EchoAgent::default());
+    swarm.register_agent("echo-2", EchoAgent::default());
+    swarm.register_agent("other", EchoAgent::default());
+    swarm.add_agent_middleware(
+        "echo-*",
+        Counter {
+            count: count.clone(),
+        },
+    );
+
+    swarm.execute("echo-1.echo", &"hello").await;
+    swarm.execute("echo-2.echo", &"hello").await;
+    swarm.execute("other.echo", &"hello").await;
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+}
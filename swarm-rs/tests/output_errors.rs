@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+use swarm_rs::prelude::*;
+
+#[derive(Serialize, Deserialize)]
+pub struct Greeting {
+    name: String,
+}
+
+#[derive(Default)]
+pub struct GreeterAgent {}
+
+#[agent]
+impl GreeterAgent {
+    #[agent_action]
+    pub async fn greet(&self, greeting: Greeting) -> Result<String, String> {
+        if greeting.name.is_empty() {
+            Err("Missing name".to_string())
+        } else {
+            Ok(format!("Hello {}", greeting.name))
+        }
+    }
+
+    #[agent_workflow]
+    pub async fn greet_all(
+        &self,
+        names: Vec<String>,
+        swarm: &Swarm,
+    ) -> Result<Vec<String>, AgentError> {
+        let mut greetings = vec![];
+        for name in names {
+            let output = swarm.execute("greeter.greet", &Greeting { name }).await;
+            let greeting = output.get_payload().map_err(|error| {
+                AgentError::action_failed("Unable to greet everyone")
+                    .with_cause(error)
+                    .with_retryable(true)
+            })?;
+            greetings.push(greeting);
+        }
+        Ok(greetings)
+    }
+}
+
+fn new_swarm() -> Swarm {
+    let mut swarm = Swarm::default();
+    swarm.register_agent("greeter", GreeterAgent::default());
+    swarm
+}
+
+#[tokio::test]
+pub async fn success_output() {
+    let swarm = new_swarm();
+    let output = swarm
+        .execute(
+            "greeter.greet",
+            &Greeting {
+                name: "User".to_string(),
+            },
+        )
+        .await;
+    assert_eq!(output.get_status(), OutputStatus::Success);
+    assert_eq!(output.get_payload::<String>().unwrap(), "Hello User");
+
+    // Typed accessors report a mismatching payload type instead of panicking
+    let error = output.get_payload::<Vec<u32>>().unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidOutput);
+    assert!(error.cause.is_some());
+}
+
+#[tokio::test]
+pub async fn error_codes() {
+    let swarm = new_swarm();
+
+    let output = swarm
+        .execute(
+            "greeter.greet",
+            &Greeting {
+                name: "".to_string(),
+            },
+        )
+        .await;
+    assert_eq!(output.get_status(), OutputStatus::Error);
+    let error = output.get_error().unwrap();
+    assert_eq!(error.code, ErrorCode::ActionFailed);
+    assert_eq!(error.message, "Missing name");
+    assert_eq!(error.agent_id, "greeter");
+    assert_eq!(error.action, "greet");
+    assert!(!error.retryable);
+
+    let output = swarm.execute("greeter.greet", &42).await;
+    assert_eq!(output.get_error().unwrap().code, ErrorCode::InvalidPayload);
+
+    let output = swarm.execute("greeter.wave", &42).await;
+    assert_eq!(output.get_error().unwrap().code, ErrorCode::UnknownAction);
+
+    let output = swarm.execute("nobody.greet", &42).await;
+    assert_eq!(output.get_error().unwrap().code, ErrorCode::AgentNotFound);
+    assert_eq!(output.get_error().unwrap().agent_id, "nobody");
+}
+
+#[tokio::test]
+pub async fn error_cause_chain() {
+    let swarm = new_swarm();
+    let names = vec!["User".to_string(), "".to_string()];
+    let output = swarm.execute("greeter.greet_all", &names).await;
+
+    let error = output.get_payload::<Vec<String>>().unwrap_err();
+    assert_eq!(error.action, "greet_all");
+    assert!(error.retryable);
+    let codes: Vec<ErrorCode> = error.chain().map(|e| e.code).collect();
+    assert_eq!(
+        codes,
+        vec![ErrorCode::ActionFailed, ErrorCode::ActionFailed]
+    );
+    assert_eq!(error.root_cause().message, "Missing name");
+
+    // The structured error survives the JSON round trip used by the web layer
+    let json = serde_json::to_value(&output).unwrap();
+    assert_eq!(json["status"], "ERROR");
+    assert_eq!(json["error"]["code"], "ACTION_FAILED");
+    let output: Output = serde_json::from_value(json).unwrap();
+    assert_eq!(output.get_error().unwrap().root_cause().action, "greet");
+}
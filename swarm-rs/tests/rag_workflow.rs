@@ -29,6 +29,7 @@ pub struct LLMSummary {
     keywords: String,
 }
 
+#[derive(Default)]
 pub struct RAGDemo {}
 
 #[agent]
@@ -39,7 +40,7 @@ impl RAGDemo {
     }
 
     #[agent_workflow]
-    pub async fn search(&self, rag_query: RagQuery, swarm: &Swarm) -> Result<RagResponse, String> {
+    pub async fn search(&self, rag_query: RagQuery, swarm: &Swarm) -> Result<RagResponse, AgentError> {
         let query = SearchQuery {
             terms: rag_query.text.to_string(),
             lang: None,
@@ -48,11 +49,10 @@ impl RAGDemo {
         // Execute market doc search
         let doc_search_output = swarm.execute("searx_ng.search", &query).await;
 
-        if !doc_search_output.is_success() {
-            return Err("Unable to perform search".to_string())
-        }
         // Handle search output
-        let docs: SearxResponse = doc_search_output.get_payload();
+        let docs: SearxResponse = doc_search_output
+            .get_payload()
+            .map_err(|error| AgentError::action_failed("Unable to perform search").with_cause(error))?;
         let contents: Vec<&str> = docs
             .results
             .iter()
@@ -68,8 +68,10 @@ impl RAGDemo {
 
         let output = swarm.execute("llm-summarizer.execute", &prompt).await;
 
-        let llm_response = output.get_payload::<LLMResponse>();
-        if let Some(llm_summary) = llm_response.get_output::<LLMSummary>() {
+        let llm_response = output
+            .get_payload::<LLMResponse>()
+            .map_err(|error| AgentError::action_failed("Unable to get summary").with_cause(error))?;
+        if let Ok(llm_summary) = llm_response.get_output::<LLMSummary>() {
             // Collect references
             let references = contents.iter().map(|content| content.to_string()).collect();
 
@@ -83,7 +85,7 @@ impl RAGDemo {
 
             Ok(response)
         } else {
-            Err("Unable to decode llm output".to_string())
+            Err(AgentError::action_failed("Unable to decode llm output"))
         }
     }
 }
@@ -95,7 +97,11 @@ pub async fn rag_workflow() {
 
     let rag_agent = RAGDemo::new();
 
+    // Replays the recorded search and completion, `SWARM_FIXTURES=record` records them again
+    let fixture = SwarmFixture::from_env("test-data/fixtures/rag_workflow.json").unwrap();
     let mut agent_swarm = Swarm::default();
+    agent_swarm.add_agent_middleware(&searx_agent.get_id(), fixture.clone());
+    agent_swarm.add_agent_middleware(&llm_agent.get_id(), fixture.clone());
 
     agent_swarm.register_agent(&searx_agent.get_id(), searx_agent);
     agent_swarm.register_agent(&llm_agent.get_id(), llm_agent);
@@ -104,10 +110,11 @@ pub async fn rag_workflow() {
     let text = r#"agentic ai system"#;
     let query = RagQuery::new(text);
     let output = agent_swarm.execute("simple_rag.search", &query).await;
+    assert!(output.is_success(), "{}", output);
+    assert!(fixture.get_misses().is_empty());
 
-    if output.is_success() {
-        println!("SUCCESS : {}", output.get_value());
-    } else {
-        println!("ERROR : {}", output.get_error_message());
-    }
+    let response: RagResponse = output.get_payload().unwrap();
+    assert_eq!(response.user_input, text);
+    assert_eq!(response.references.len(), 2);
+    assert!(!response.summary.is_empty());
 }
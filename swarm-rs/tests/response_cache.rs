@@ -0,0 +1,138 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use serde_json::json;
+use swarm_rs::{prelude::*, utils::file_io};
+
+#[derive(Default)]
+pub struct EchoAgent {
+    calls: AtomicUsize,
+}
+
+#[agent]
+impl EchoAgent {
+    #[agent_action]
+    pub async fn echo(&self, text: serde_json::Value) -> Result<String, String> {
+        let calls = self.calls.fetch_add(1, Ordering::Relaxed) + 1;
+        Ok(format!("{} #{}", text, calls))
+    }
+
+    #[agent_action]
+    pub async fn fail(&self, text: String) -> Result<String, String> {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        Err(format!("Unable to handle {}", text))
+    }
+}
+
+fn new_swarm(cache: ResponseCache) -> Swarm {
+    let mut swarm = Swarm::default();
+    swarm.register_agent("echo", EchoAgent::default());
+    swarm.set_response_cache(Some(cache));
+    swarm
+}
+
+fn calls(swarm: &Swarm) -> usize {
+    swarm
+        .get_agent::<EchoAgent>("echo")
+        .unwrap()
+        .calls
+        .load(Ordering::Relaxed)
+}
+
+#[tokio::test]
+pub async fn memory_cache() {
+    let swarm = new_swarm(ResponseCache::new(MemoryCacheStore::new(2))
+        .include("echo.*")
+        .bypass("*.fail"));
+
+    let output = swarm
+        .execute("echo.echo", &json!({ "text": "hello", "lang": "en" }))
+        .await;
+    assert!(!output.is_cache_hit());
+    assert_eq!(output.get_metadata("cache"), None);
+    let output = swarm
+        .execute("echo.echo", &json!({ "lang": "en", "text": "hello" }))
+        .await;
+    assert!(output.is_cache_hit());
+    assert_eq!(output.agent_id, "echo");
+    assert_eq!(
+        output.get_payload::<String>().unwrap(),
+        "{\"lang\":\"en\",\"text\":\"hello\"} #1"
+    );
+    assert_eq!(calls(&swarm), 1);
+
+    // The least recently used entry is evicted
+    swarm.execute("echo.echo", &"a").await;
+    swarm.execute("echo.echo", &"b").await;
+    assert_eq!(calls(&swarm), 3);
+    assert!(swarm.execute("echo.echo", &"b").await.is_cache_hit());
+    assert!(!swarm
+        .execute("echo.echo", &json!({ "text": "hello", "lang": "en" }))
+        .await
+        .is_cache_hit());
+    assert_eq!(calls(&swarm), 4);
+
+    // Bypassed actions and errors are never cached
+    swarm.execute("echo.fail", &"a").await;
+    swarm.execute("echo.fail", &"a").await;
+    assert_eq!(calls(&swarm), 6);
+}
+
+#[tokio::test]
+pub async fn cache_ttl() {
+    let cache = ResponseCache::new(MemoryCacheStore::new(10))
+        .include("echo.echo")
+        .with_ttl(Duration::from_secs(60))
+        .with_action_ttl("echo.*", Duration::from_millis(50));
+    let swarm = new_swarm(cache);
+
+    swarm.execute("echo.echo", &"hello").await;
+    assert!(swarm.execute("echo.echo", &"hello").await.is_cache_hit());
+    tokio::time::sleep(Duration::from_millis(80)).await;
+    let output = swarm.execute("echo.echo", &"hello").await;
+    assert!(!output.is_cache_hit());
+    assert_eq!(output.get_payload::<String>().unwrap(), "\"hello\" #2");
+}
+
+#[tokio::test]
+pub async fn file_cache() {
+    let cache_dir = "test-data/out/cache";
+    file_io::remove_dir(cache_dir);
+    let swarm = new_swarm(ResponseCache::new(FileCacheStore::new(cache_dir)).include("*"));
+    swarm.execute("echo.echo", &"persisted").await;
+
+    // A new swarm reuses the cached outputs
+    let swarm = new_swarm(ResponseCache::new(FileCacheStore::new(cache_dir)).include("*"));
+    let output = swarm.execute("echo.echo", &"persisted").await;
+    assert!(output.is_cache_hit());
+    assert_eq!(output.get_payload::<String>().unwrap(), "\"persisted\" #1");
+    let cache = output.get_metadata("cache").unwrap();
+    assert!(cache["key"].as_str().unwrap().starts_with("echo_echo-"));
+    assert_eq!(calls(&swarm), 0);
+
+    let action = Action::new("echo.echo", "persisted");
+    swarm.get_response_cache().unwrap().invalidate(&action).unwrap();
+    assert!(!swarm.execute_action(&action).await.is_cache_hit());
+}
+
+#[tokio::test]
+pub async fn cache_scope() {
+    // Actions are only cached when included
+    let swarm = new_swarm(ResponseCache::new(MemoryCacheStore::new(10)));
+    swarm.execute("echo.echo", &"hello").await;
+    assert!(!swarm.execute("echo.echo", &"hello").await.is_cache_hit());
+    assert_eq!(calls(&swarm), 2);
+
+    // Users do not share their cached outputs
+    let swarm = new_swarm(ResponseCache::new(MemoryCacheStore::new(10)).include("echo.*"));
+    let action = Action::new("echo.echo", "hello").with_user("alice");
+    swarm.execute_action(&action).await;
+    assert!(swarm.execute_action(&action).await.is_cache_hit());
+    let action = Action::new("echo.echo", "hello").with_user("bob");
+    let output = swarm.execute_action(&action).await;
+    assert!(!output.is_cache_hit());
+    assert_eq!(output.get_payload::<String>().unwrap(), "\"hello\" #2");
+    assert!(!swarm.execute("echo.echo", &"hello").await.is_cache_hit());
+}
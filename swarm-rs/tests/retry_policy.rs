@@ -0,0 +1,120 @@
+use std::{
+    sync::atomic::{AtomicU32, Ordering},
+    time::{Duration, Instant},
+};
+
+use swarm_rs::prelude::*;
+
+/// Fails with a retryable error until the requested attempt
+#[derive(Default)]
+pub struct FlakyAgent {
+    calls: AtomicU32,
+}
+
+#[agent]
+impl FlakyAgent {
+    #[agent_action]
+    pub async fn call(&self, succeed_at: u32) -> Result<u32, AgentError> {
+        let attempt = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+        if attempt >= succeed_at {
+            Ok(attempt)
+        } else {
+            Err(AgentError::action_failed("Service unavailable").with_retryable(true))
+        }
+    }
+
+    #[agent_action]
+    pub async fn fail(&self, message: String) -> Result<u32, String> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Err(message)
+    }
+
+    #[agent_action]
+    pub async fn reject(&self, message: String) -> Result<u32, AgentError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Err(AgentError::new(ErrorCode::InvalidPayload, &message))
+    }
+}
+
+fn quick_policy(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy::new(max_attempts)
+        .with_backoff(Duration::from_millis(10), Duration::from_millis(50))
+}
+
+#[tokio::test]
+pub async fn agent_retry_policy() {
+    let mut swarm = Swarm::default();
+    let options = AgentOptions::new().with_retry(quick_policy(3));
+    swarm.register_agent_with_options("flaky", FlakyAgent::default(), options);
+
+    let output = swarm.execute("flaky.call", &3).await;
+    assert_eq!(output.get_payload::<u32>().unwrap(), 3);
+
+    // Gives up after max attempts
+    let output = swarm.execute("flaky.call", &100).await;
+    assert!(!output.is_success());
+    let agent = swarm.get_agent::<FlakyAgent>("flaky").unwrap();
+    assert_eq!(agent.calls.load(Ordering::SeqCst), 6);
+}
+
+#[tokio::test]
+pub async fn non_retryable_errors() {
+    let mut swarm = Swarm::default();
+    let options = AgentOptions::new().with_retry(quick_policy(3));
+    swarm.register_agent_with_options("flaky", FlakyAgent::default(), options);
+
+    let output = swarm.execute("flaky.reject", &"Bad request").await;
+    assert!(!output.is_success());
+    let agent = swarm.get_agent::<FlakyAgent>("flaky").unwrap();
+    assert_eq!(agent.calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+pub fn default_retry_predicate() {
+    let declared = RetrySettings::new(3).to_policy();
+    for policy in [RetryPolicy::new(3), declared] {
+        assert!(policy.should_retry(&AgentError::action_failed("Service unavailable"), 1));
+        assert!(policy.should_retry(&AgentError::new(ErrorCode::Timeout, "Timed out"), 1));
+        assert!(policy.should_retry(&AgentError::new(ErrorCode::RateLimited, "Slow down"), 1));
+        assert!(!policy.should_retry(&AgentError::new(ErrorCode::InvalidPayload, "Bad request"), 1));
+        assert!(!policy.should_retry(&AgentError::new(ErrorCode::Cancelled, "Cancelled"), 1));
+        assert!(!policy.should_retry(&AgentError::action_failed("Service unavailable"), 3));
+    }
+}
+
+#[tokio::test]
+pub async fn action_retry_policy() {
+    let mut swarm = Swarm::default();
+    swarm.register_agent("flaky", FlakyAgent::default());
+    let policy = quick_policy(4).retry_on(|error| error.code == ErrorCode::ActionFailed);
+    swarm.set_action_retry_policy("flaky.fail", policy);
+
+    let output = swarm.execute("flaky.fail", &"Bad request").await;
+    assert!(!output.is_success());
+    let agent = swarm.get_agent::<FlakyAgent>("flaky").unwrap();
+    assert_eq!(agent.calls.load(Ordering::SeqCst), 4);
+
+    // No policy for this action
+    let output = swarm.execute("flaky.call", &100).await;
+    assert!(!output.is_success());
+    assert_eq!(agent.calls.load(Ordering::SeqCst), 5);
+}
+
+#[tokio::test]
+pub async fn cancel_retries() {
+    let mut swarm = Swarm::default();
+    let policy = RetryPolicy::new(10).with_backoff(Duration::from_secs(1), Duration::from_secs(1));
+    let options = AgentOptions::new().with_retry(policy);
+    swarm.register_agent_with_options("flaky", FlakyAgent::default(), options);
+
+    let cancellation = CancellationToken::new();
+    let action = Action::new("flaky.call", 100).with_cancellation(cancellation.clone());
+    let abort = async {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        cancellation.cancel();
+    };
+    let start = Instant::now();
+    let (output, _) = tokio::join!(swarm.execute_action(&action), abort);
+    assert!(start.elapsed() < Duration::from_millis(500));
+    assert!(output.is_cancelled());
+}
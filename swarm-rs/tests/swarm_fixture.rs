@@ -0,0 +1,86 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde_json::json;
+use swarm_rs::{prelude::*, utils::file_io};
+
+#[derive(Default)]
+pub struct DiceAgent {
+    rolls: AtomicUsize,
+}
+
+#[agent]
+impl DiceAgent {
+    #[agent_action]
+    pub async fn roll(&self, faces: usize) -> Result<usize, String> {
+        let rolls = self.rolls.fetch_add(1, Ordering::Relaxed);
+        Ok((rolls * 7 + 3) % faces + 1)
+    }
+}
+
+#[derive(Default)]
+pub struct GameAgent {}
+
+#[agent]
+impl GameAgent {
+    #[agent_workflow]
+    pub async fn play(&self, faces: usize, swarm: &Swarm) -> Result<Vec<usize>, AgentError> {
+        let mut rolls = vec![];
+        for _ in 0..3 {
+            rolls.push(swarm.execute("dice.roll", &faces).await.get_payload()?);
+        }
+        Ok(rolls)
+    }
+}
+
+fn new_swarm(fixture: &SwarmFixture, with_dice: bool) -> Swarm {
+    let mut swarm = Swarm::default();
+    swarm.add_agent_middleware("dice", fixture.clone());
+    if with_dice {
+        swarm.register_agent("dice", DiceAgent::default());
+    }
+    swarm.register_agent("game", GameAgent::default());
+    swarm
+}
+
+#[tokio::test]
+pub async fn record_and_replay() {
+    let fixture_path = "test-data/out/fixtures/dice.json";
+    file_io::remove_file(fixture_path);
+
+    let fixture = SwarmFixture::record(fixture_path);
+    let swarm = new_swarm(&fixture, true);
+    let recorded = swarm.execute("game.play", &6).await;
+    assert_eq!(recorded.get_payload::<Vec<usize>>().unwrap(), vec![4, 5, 6]);
+    let entries = Fixture::load(fixture_path).unwrap().entries;
+    // Only the dice rolls are recorded
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0].action_id, "dice.roll");
+    assert_eq!(entries[0].payload, json!(6));
+
+    // The dice agent is not even registered when replaying
+    let fixture = SwarmFixture::replay(fixture_path).unwrap();
+    assert_eq!(fixture.get_mode(), FixtureMode::Replay);
+    let swarm = new_swarm(&fixture, false);
+    let replayed = swarm.execute("game.play", &6).await;
+    assert_eq!(replayed.get_value(), recorded.get_value());
+    // Identical actions exhausted their recordings, the last one is served again
+    let output = swarm.execute("dice.roll", &6).await;
+    assert_eq!(output.get_payload::<usize>().unwrap(), 6);
+    assert!(fixture.get_misses().is_empty());
+
+    let output = swarm.execute("game.play", &20).await;
+    let error = output.get_error().unwrap();
+    assert_eq!(
+        error.root_cause().message,
+        "Action dice.roll with payload 20 is not recorded in test-data/out/fixtures/dice.json"
+    );
+    assert_eq!(fixture.get_misses().len(), 1);
+}
+
+#[tokio::test]
+pub async fn missing_fixture() {
+    let error = SwarmFixture::replay("test-data/out/fixtures/missing.json")
+        .err()
+        .unwrap();
+    assert!(error.contains("missing.json"));
+}
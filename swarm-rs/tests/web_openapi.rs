@@ -0,0 +1,202 @@
+use rocket::{
+    http::{ContentType, Header, Status},
+    local::asynchronous::Client,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use swarm_rs::{
+    prelude::*,
+    utils::{file_io, json_io},
+    web::{
+        web_swarm::{WebOptions, WebSwarm},
+        AuthAgent, NewUser, UserAuth,
+    },
+};
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct Transfer {
+    pub account: String,
+    pub amount: u64,
+}
+
+/// Types named like the bank and swarm ones.
+pub mod ledger {
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, JsonSchema)]
+    pub struct Transfer {
+        pub reference: String,
+    }
+
+    #[derive(Serialize, Deserialize, JsonSchema)]
+    pub struct Output {
+        pub line: usize,
+    }
+}
+
+#[derive(Default)]
+pub struct BankAgent {}
+
+#[agent]
+impl BankAgent {
+    /// Balance of the account.
+    #[agent_action]
+    pub async fn balance(&self, account: String) -> Result<u64, String> {
+        Ok(account.len() as u64 * 100)
+    }
+
+    /// Moves money out of the account.
+    #[agent_action]
+    pub async fn transfer(&self, transfer: Transfer) -> Result<u64, String> {
+        if transfer.amount > 1000 {
+            return Err(format!("Transfer of {} refused", transfer.amount));
+        }
+        Ok(transfer.amount)
+    }
+
+    #[agent_action]
+    pub async fn batch(&self, transfers: Vec<Transfer>) -> Result<usize, String> {
+        Ok(transfers.len())
+    }
+
+    #[agent_action]
+    pub async fn audit(
+        &self,
+        transfers: Vec<ledger::Transfer>,
+    ) -> Result<Vec<ledger::Output>, String> {
+        Ok((0..transfers.len()).map(|line| ledger::Output { line }).collect())
+    }
+}
+
+async fn new_client(users_db: &str) -> (Client, UserAuth, UserAuth) {
+    let mut swarm = Swarm::default();
+    swarm.register_agent("bank", BankAgent::default());
+    file_io::remove_file(users_db);
+    let mut auth_config: Value = json_io::load("test-data/agents/auth.json").unwrap();
+    auth_config["db_path"] = json!(users_db);
+    auth_config["protected_actions"]["bank.transfer"] = json!(["Teller"]);
+    let auth: AuthAgent = serde_json::from_value(auth_config).unwrap();
+    let teller = NewUser::new("teller", "p4ssw0rd", "Teller", "", vec!["Teller"]);
+    let teller: UserAuth = auth.register_user(teller).await.unwrap();
+    let client = NewUser::new("client", "p4ssw0rd", "Client", "", vec!["Client"]);
+    let client: UserAuth = auth.register_user(client).await.unwrap();
+    swarm.register_agent("Auth", auth);
+
+    let rocket = WebSwarm::serve_with_options(swarm, WebOptions::default().with_typed_routes());
+    (Client::tracked(rocket).await.unwrap(), teller, client)
+}
+
+#[tokio::test]
+pub async fn openapi_document() {
+    let (client, _, _) = new_client("test-data/out/openapi-users.json").await;
+    let response = client.get("/api/openapi.json").dispatch().await;
+    assert_eq!(response.status(), Status::Ok);
+    let document: Value = response.into_json().await.unwrap();
+    assert_eq!(document["openapi"], "3.1.0");
+
+    let transfer = &document["paths"]["/api/agents/bank/transfer"]["post"];
+    assert_eq!(transfer["description"], "Moves money out of the account.");
+    assert_eq!(transfer["x-roles"], json!(["Teller"]));
+    assert_eq!(transfer["security"], json!([{ "token": [] }]));
+    let request_schema = &transfer["requestBody"]["content"]["application/json"]["schema"];
+    assert_eq!(request_schema["properties"]["amount"]["type"], "integer");
+    let response_schema = &transfer["responses"]["200"]["content"]["application/json"]["schema"];
+    assert_eq!(response_schema["type"], "integer");
+    assert!(document["components"]["schemas"]["ErrorCode"].is_object());
+
+    let balance = &document["paths"]["/api/agents/bank/balance"]["post"];
+    assert_eq!(balance["x-roles"], Value::Null);
+    let actions = document["paths"]["/api/action"]["post"]["requestBody"]["content"]
+        ["application/json"]["schema"]["oneOf"]
+        .as_array()
+        .unwrap();
+    assert!(actions
+        .iter()
+        .any(|action| action["properties"]["id"]["const"] == "bank.balance"));
+
+    // Types sharing a name get their own component
+    let schemas = &document["components"]["schemas"];
+    let items_ref = |action: &str, direction: &str| {
+        let operation = &document["paths"][format!("/api/agents/bank/{}", action)]["post"];
+        let content = match direction {
+            "request" => &operation["requestBody"]["content"],
+            _ => &operation["responses"]["200"]["content"],
+        };
+        content["application/json"]["schema"]["items"]["$ref"].clone()
+    };
+    assert_eq!(items_ref("batch", "request"), "#/components/schemas/Transfer");
+    assert_eq!(items_ref("audit", "request"), "#/components/schemas/Transfer2");
+    assert_eq!(items_ref("audit", "response"), "#/components/schemas/Output2");
+    assert!(schemas["Transfer"]["properties"]["amount"].is_object());
+    assert!(schemas["Transfer2"]["properties"]["reference"].is_object());
+    assert!(schemas["Output"]["properties"]["status"].is_object());
+    assert!(schemas["Output2"]["properties"]["line"].is_object());
+
+    // No reference is left to the schemas definitions
+    assert!(!document.to_string().contains("#/$defs/"));
+}
+
+#[tokio::test]
+pub async fn typed_routes() {
+    let (client, teller, customer) = new_client("test-data/out/typed-routes-users.json").await;
+    let response = client
+        .post("/api/agents/bank/balance")
+        .header(ContentType::JSON)
+        .body("\"alice\"")
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.into_json::<u64>().await.unwrap(), 500);
+
+    let transfer = json!({ "account": "alice", "amount": 300 });
+    let response = client
+        .post("/api/agents/bank/transfer")
+        .header(ContentType::JSON)
+        .header(Header::new("Token", customer.token.to_string()))
+        .body(transfer.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Forbidden);
+
+    let response = client
+        .post("/api/agents/bank/transfer")
+        .header(ContentType::JSON)
+        .header(Header::new("Token", teller.token.to_string()))
+        .body(transfer.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.into_json::<u64>().await.unwrap(), 300);
+
+    let response = client
+        .post("/api/agents/bank/transfer")
+        .header(ContentType::JSON)
+        .header(Header::new("Token", teller.token.to_string()))
+        .body(json!({ "account": "alice" }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::BadRequest);
+    let error: AgentError = response.into_json().await.unwrap();
+    assert_eq!(error.code, ErrorCode::InvalidPayload);
+
+    let response = client
+        .post("/api/agents/bank/transfer")
+        .header(ContentType::JSON)
+        .header(Header::new("Token", teller.token.to_string()))
+        .body(json!({ "account": "alice", "amount": 5000 }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::InternalServerError);
+    let error: AgentError = response.into_json().await.unwrap();
+    assert_eq!(error.message, "Transfer of 5000 refused");
+
+    let response = client
+        .post("/api/agents/vault/open")
+        .header(ContentType::JSON)
+        .body("{}")
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::NotFound);
+}
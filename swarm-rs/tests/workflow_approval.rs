@@ -0,0 +1,275 @@
+use std::time::Duration;
+
+use rocket::{
+    http::{ContentType, Header, Status},
+    local::asynchronous::Client,
+};
+use serde_json::{json, Value};
+use swarm_rs::{
+    prelude::*,
+    utils::{file_io, json_io},
+    web::{web_swarm::WebSwarm, AuthAgent, NewUser, UserAuth},
+};
+
+#[derive(Default)]
+pub struct PublishAgent {}
+
+#[agent]
+impl PublishAgent {
+    #[agent_action]
+    pub async fn publish(&self, article: String) -> Result<String, String> {
+        Ok(format!("{} published", article))
+    }
+}
+
+fn new_swarm(approvals_dir: &str, timeout_ms: Option<u64>, roles: &[&str]) -> Swarm {
+    let _ = std::fs::remove_dir_all(approvals_dir);
+    let mut swarm = Swarm::default();
+    swarm.register_agent("news", PublishAgent::default());
+    let definition: WorkflowDefinition = serde_json::from_value(json!({
+        "id": "publication",
+        "steps": [
+            {
+                "type": "approval",
+                "id": "review",
+                "title": "Publish {{input}}",
+                "details": { "article": "$.input" },
+                "roles": roles,
+                "timeout_ms": timeout_ms
+            },
+            { "type": "action", "id": "publish", "action": "news.publish", "input": "$.input" }
+        ],
+        "output": { "result": "$.steps.publish", "review": "$.steps.review" }
+    }))
+    .unwrap();
+    swarm.register_agent("publication", WorkflowAgent::new(definition));
+    swarm.set_approvals(
+        Approvals::new(FileApprovalStore::new(approvals_dir))
+            .with_default_outcome(ApprovalOutcome::Approved),
+    );
+    swarm
+}
+
+async fn next_pending(swarm: &Swarm) -> Approval {
+    loop {
+        let pending = swarm.get_approvals().unwrap().list_pending().unwrap();
+        if let Some(approval) = pending.into_iter().next() {
+            return approval;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+#[tokio::test]
+pub async fn approval_decisions() {
+    let swarm = new_swarm("test-data/out/approvals/decisions", None, &["Editor"]);
+
+    let (output, approval) = tokio::join!(swarm.execute("publication", &"release notes"), async {
+        let approval = next_pending(&swarm).await;
+        let decision = ApprovalDecision::approved()
+            .with_approver("alice")
+            .with_comment("Looks good");
+        swarm
+            .get_approvals()
+            .unwrap()
+            .decide(&approval.approval_id, decision)
+            .unwrap()
+    });
+    assert_eq!(approval.title, "Publish release notes");
+    assert_eq!(approval.details, json!({ "article": "release notes" }));
+    assert_eq!(approval.status, ApprovalStatus::Approved);
+    let result = output.get_payload::<Value>().unwrap();
+    assert_eq!(result["result"], "release notes published");
+    assert_eq!(result["review"]["approver"], "alice");
+    assert_eq!(result["review"]["comment"], "Looks good");
+
+    let (output, _) = tokio::join!(swarm.execute("publication", &"draft"), async {
+        let approval = next_pending(&swarm).await;
+        let decision = ApprovalDecision::rejected().with_comment("Not ready");
+        let approvals = swarm.get_approvals().unwrap();
+        approvals
+            .decide(&approval.approval_id, decision.clone())
+            .unwrap();
+        // Decisions are final
+        let error = approvals
+            .decide(&approval.approval_id, decision)
+            .unwrap_err();
+        assert!(error.message.ends_with("is already decided"));
+    });
+    let error = output.get_error().unwrap();
+    assert_eq!(error.message, "Step review rejected");
+    assert_eq!(error.root_cause().message, "Not ready");
+}
+
+#[tokio::test]
+pub async fn approval_timeout() {
+    let swarm = new_swarm("test-data/out/approvals/timeout", Some(50), &["Editor"]);
+    let output = swarm.execute("publication", &"weekly digest").await;
+    let result = output.get_payload::<Value>().unwrap();
+    assert_eq!(result["result"], "weekly digest published");
+    assert_eq!(result["review"]["comment"], "Timed out");
+
+    let approvals = swarm.get_approvals().unwrap().list().unwrap();
+    assert_eq!(approvals[0].status, ApprovalStatus::TimedOut);
+}
+
+#[tokio::test]
+pub async fn awaited_once() {
+    let approvals_dir = "test-data/out/approvals/awaited";
+    let _ = std::fs::remove_dir_all(approvals_dir);
+    let approvals = Approvals::new(FileApprovalStore::new(approvals_dir));
+    let approval = Approval::new("Publish release notes").with_id("release-notes");
+
+    let (decision, _) = tokio::join!(approvals.request(approval.clone()), async {
+        while approvals.list_pending().unwrap().is_empty() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let error = approvals.request(approval.clone()).await.unwrap_err();
+        assert_eq!(error.message, "Approval release-notes is already awaited");
+        approvals
+            .decide("release-notes", ApprovalDecision::approved())
+            .unwrap();
+    });
+    assert_eq!(decision.unwrap().outcome, ApprovalOutcome::Approved);
+
+    // Decided approvals answer right away
+    let decision = approvals.request(approval).await.unwrap();
+    assert_eq!(decision.outcome, ApprovalOutcome::Approved);
+}
+
+#[tokio::test]
+pub async fn web_approvals() {
+    let mut swarm = new_swarm("test-data/out/approvals/web", None, &["Editor"]);
+    let users_db = "test-data/out/approvals/web-users.json";
+    file_io::remove_file(users_db);
+    let mut auth_config: Value = json_io::load("test-data/agents/auth.json").unwrap();
+    auth_config["db_path"] = json!(users_db);
+    let auth: AuthAgent = serde_json::from_value(auth_config).unwrap();
+    let editor = NewUser::new("editor", "p4ssw0rd", "Editor", "", vec!["Editor"]);
+    let editor: UserAuth = auth.register_user(editor).await.unwrap();
+    let reader = NewUser::new("reader", "p4ssw0rd", "Reader", "", vec!["Reader"]);
+    let reader: UserAuth = auth.register_user(reader).await.unwrap();
+    swarm.register_agent("Auth", auth);
+
+    let client = Client::tracked(WebSwarm::serve(swarm)).await.unwrap();
+    let action = Action::new("publication.run", "changelog");
+    let run = client
+        .post("/api/action")
+        .header(ContentType::JSON)
+        .body(serde_json::to_string(&action).unwrap())
+        .dispatch();
+
+    let review = async {
+        let approval = loop {
+            let response = client
+                .get("/api/approvals")
+                .header(Header::new("Token", editor.token.to_string()))
+                .dispatch()
+                .await;
+            let pending: Vec<Approval> = response.into_json().await.unwrap();
+            if let Some(approval) = pending.into_iter().next() {
+                break approval;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        };
+
+        let response = client
+            .get("/api/approvals")
+            .header(Header::new("Token", reader.token.to_string()))
+            .dispatch()
+            .await;
+        assert!(response
+            .into_json::<Vec<Approval>>()
+            .await
+            .unwrap()
+            .is_empty());
+
+        let uri = format!("/api/approvals/{}", approval.approval_id);
+        let decision = json!({ "outcome": "approved", "comment": "Ship it" }).to_string();
+        let response = client
+            .post(&uri)
+            .header(ContentType::JSON)
+            .header(Header::new("Token", reader.token.to_string()))
+            .body(&decision)
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Forbidden);
+
+        let response = client
+            .post(&uri)
+            .header(ContentType::JSON)
+            .header(Header::new("Token", editor.token.to_string()))
+            .body(&decision)
+            .dispatch()
+            .await;
+        let approval: Approval = response.into_json().await.unwrap();
+        assert_eq!(approval.status, ApprovalStatus::Approved);
+    };
+
+    let (response, _) = tokio::join!(run, review);
+    let output: Output = response.into_json().await.unwrap();
+    let result = output.get_payload::<Value>().unwrap();
+    assert_eq!(result["review"]["approver"], "editor");
+    assert_eq!(result["review"]["comment"], "Ship it");
+}
+
+#[tokio::test]
+pub async fn web_approvals_authentication() {
+    let mut swarm = new_swarm("test-data/out/approvals/web-anonymous", None, &[]);
+    let users_db = "test-data/out/approvals/web-anonymous-users.json";
+    file_io::remove_file(users_db);
+    let mut auth_config: Value = json_io::load("test-data/agents/auth.json").unwrap();
+    auth_config["db_path"] = json!(users_db);
+    let auth: AuthAgent = serde_json::from_value(auth_config).unwrap();
+    let reader = NewUser::new("reader", "p4ssw0rd", "Reader", "", vec!["Reader"]);
+    let reader: UserAuth = auth.register_user(reader).await.unwrap();
+    swarm.register_agent("Auth", auth);
+
+    let client = Client::tracked(WebSwarm::serve(swarm)).await.unwrap();
+    let action = Action::new("publication.run", "changelog");
+    let run = client
+        .post("/api/action")
+        .header(ContentType::JSON)
+        .body(serde_json::to_string(&action).unwrap())
+        .dispatch();
+
+    let review = async {
+        let approval = loop {
+            let response = client
+                .get("/api/approvals")
+                .header(Header::new("Token", reader.token.to_string()))
+                .dispatch()
+                .await;
+            let pending: Vec<Approval> = response.into_json().await.unwrap();
+            if let Some(approval) = pending.into_iter().next() {
+                break approval;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        };
+
+        // Approvals without roles still need an authenticated user
+        let uri = format!("/api/approvals/{}", approval.approval_id);
+        let decision = json!({ "outcome": "approved" }).to_string();
+        for token in [None, Some("unknown-token")] {
+            let mut request = client.post(&uri).header(ContentType::JSON).body(&decision);
+            if let Some(token) = token {
+                request = request.header(Header::new("Token", token));
+            }
+            assert_eq!(request.dispatch().await.status(), Status::Forbidden);
+        }
+
+        let response = client
+            .post(&uri)
+            .header(ContentType::JSON)
+            .header(Header::new("Token", reader.token.to_string()))
+            .body(&decision)
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+    };
+
+    let (response, _) = tokio::join!(run, review);
+    let output: Output = response.into_json().await.unwrap();
+    let result = output.get_payload::<Value>().unwrap();
+    assert_eq!(result["review"]["approver"], "reader");
+}
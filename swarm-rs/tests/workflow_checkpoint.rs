@@ -0,0 +1,145 @@
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    Arc,
+};
+
+use rocket::local::asynchronous::Client;
+use serde_json::json;
+use swarm_rs::{prelude::*, web::web_swarm::WebSwarm};
+
+#[derive(Default, Clone)]
+pub struct PagesAgent {
+    calls: Arc<AtomicUsize>,
+    offline: Arc<AtomicBool>,
+}
+
+#[agent]
+impl PagesAgent {
+    #[agent_action]
+    pub async fn fetch(&self, topic: String) -> Result<Vec<String>, String> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Ok(vec![
+            format!("{} intro", topic),
+            format!("{} usage", topic),
+            format!("{} faq", topic),
+        ])
+    }
+
+    #[agent_action]
+    pub async fn digest(&self, page: String) -> Result<String, String> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        if page.ends_with("faq") && self.offline.load(Ordering::SeqCst) {
+            Err("Service offline".to_string())
+        } else {
+            Ok(page.to_uppercase())
+        }
+    }
+}
+
+fn new_swarm(pages: &PagesAgent, store_dir: &str) -> Swarm {
+    let _ = std::fs::remove_dir_all(store_dir);
+    let mut swarm = Swarm::default();
+    swarm.register_agent("pages", pages.clone());
+    let workflow = WorkflowAgent::load("test-data/workflows/page_digest.json").unwrap();
+    swarm.register_agent(&workflow.get_id(), workflow);
+    swarm.set_checkpoint_store(FileCheckpointStore::new(store_dir));
+    swarm
+}
+
+#[tokio::test]
+pub async fn resume_failed_run() {
+    let pages = PagesAgent::default();
+    pages.offline.store(true, Ordering::SeqCst);
+    let swarm = new_swarm(&pages, "test-data/out/checkpoints/resume");
+
+    let output = swarm.execute("page_digest.run", &"rust").await;
+    assert!(!output.is_success());
+    assert_eq!(pages.calls.load(Ordering::SeqCst), 4);
+
+    let store = swarm.get_checkpoint_store().unwrap();
+    let runs = store.list().unwrap();
+    assert_eq!(runs.len(), 1);
+    let run = &runs[0];
+    assert_eq!(run.status, RunStatus::Failed);
+    assert_eq!(run.error.as_ref().unwrap().message, "Step digest failed");
+    let steps: Vec<&str> = run
+        .checkpoints
+        .iter()
+        .map(|c| c.step_path.as_str())
+        .collect();
+    assert_eq!(
+        steps,
+        vec![
+            "pages",
+            "digests[0].digest",
+            "digests[1].digest",
+            "digests[2].digest"
+        ]
+    );
+    assert_eq!(run.last_checkpoint().unwrap().state["page"], "rust faq");
+
+    // Only the failed step runs again
+    pages.offline.store(false, Ordering::SeqCst);
+    let output = swarm.execute("page_digest.resume", &run.run_id).await;
+    assert_eq!(
+        output.get_payload::<Vec<String>>().unwrap(),
+        vec!["RUST INTRO", "RUST USAGE", "RUST FAQ"]
+    );
+    assert_eq!(pages.calls.load(Ordering::SeqCst), 5);
+
+    let run = store.load(&run.run_id).unwrap().unwrap();
+    assert_eq!(run.status, RunStatus::Completed);
+
+    // Completed runs return their output
+    let output = swarm.execute("page_digest.resume", &run.run_id).await;
+    assert_eq!(output.get_payload::<Vec<String>>().unwrap().len(), 3);
+    assert_eq!(pages.calls.load(Ordering::SeqCst), 5);
+
+    let output = swarm.execute("page_digest.resume", &"unknown").await;
+    assert_eq!(output.get_error().unwrap().message, "Unknown run unknown");
+}
+
+#[tokio::test]
+pub async fn web_runs() {
+    let pages = PagesAgent::default();
+    pages.offline.store(true, Ordering::SeqCst);
+    let swarm = new_swarm(&pages, "test-data/out/checkpoints/web");
+    swarm.execute("page_digest.run", &"tokio").await;
+
+    let client = Client::tracked(WebSwarm::serve(swarm)).await.unwrap();
+    let runs: Vec<RunSummary> = client
+        .get("/api/runs")
+        .dispatch()
+        .await
+        .into_json()
+        .await
+        .unwrap();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].status, RunStatus::Failed);
+    assert_eq!(runs[0].last_step.as_deref(), Some("digests[2].digest"));
+
+    let run_uri = format!("/api/runs/{}", runs[0].run_id);
+    let run: WorkflowRun = client
+        .get(&run_uri)
+        .dispatch()
+        .await
+        .into_json()
+        .await
+        .unwrap();
+    assert_eq!(run.input, json!("tokio"));
+    assert_eq!(run.checkpoints.len(), 4);
+
+    pages.offline.store(false, Ordering::SeqCst);
+    let resume_uri = format!("{}/resume", run_uri);
+    let output: Output = client
+        .post(&resume_uri)
+        .dispatch()
+        .await
+        .into_json()
+        .await
+        .unwrap();
+    assert_eq!(output.get_payload::<Vec<String>>().unwrap()[2], "TOKIO FAQ");
+
+    let response = client.get("/api/runs/missing").dispatch().await;
+    assert_eq!(response.status(), rocket::http::Status::NotFound);
+}
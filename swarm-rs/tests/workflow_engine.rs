@@ -0,0 +1,113 @@
+use rocket::{http::ContentType, local::asynchronous::Client};
+use serde_json::{json, Value};
+use swarm_rs::{prelude::*, web::web_swarm::WebSwarm};
+
+#[derive(Default)]
+pub struct TextAgent {}
+
+#[agent]
+impl TextAgent {
+    #[agent_action]
+    pub async fn split(&self, text: String) -> Result<Vec<String>, String> {
+        Ok(text
+            .split_whitespace()
+            .map(|word| word.to_string())
+            .collect())
+    }
+
+    #[agent_action]
+    pub async fn upper(&self, text: String) -> Result<String, String> {
+        Ok(text.to_uppercase())
+    }
+
+    #[agent_action]
+    pub async fn count(&self, words: Vec<String>) -> Result<usize, String> {
+        if words.is_empty() {
+            Err("No words".to_string())
+        } else {
+            Ok(words.len())
+        }
+    }
+}
+
+fn new_swarm() -> Swarm {
+    let mut swarm = Swarm::default();
+    swarm.register_agent("text", TextAgent::default());
+
+    let word_flow: WorkflowAgent = json_io::load("test-data/workflows/word_flow.json").unwrap();
+    swarm.register_agent(&word_flow.get_id(), word_flow);
+    let word_count = WorkflowAgent::load("test-data/workflows/word_count.json").unwrap();
+    swarm.register_agent(&word_count.get_id(), word_count);
+    swarm
+}
+
+#[tokio::test]
+pub async fn json_workflow() {
+    let swarm = new_swarm();
+
+    let input = json!({ "text": "agentic ai system", "max_words": 2 });
+    let output = swarm.execute("word_flow.run", &input).await;
+    assert_eq!(
+        output.get_payload::<Value>().unwrap(),
+        json!({
+            "words": ["AGENTIC!", "AI!", "SYSTEM!"],
+            "count": 3,
+            "summary": "LONG TEXT OF 3 WORDS"
+        })
+    );
+
+    let input = json!({ "text": "agentic ai", "max_words": 2 });
+    let output = swarm.execute("word_flow.run", &input).await;
+    let result = output.get_payload::<Value>().unwrap();
+    assert_eq!(result["summary"], "SHORT TEXT: AGENTIC AI");
+}
+
+#[tokio::test]
+pub async fn step_errors() {
+    let swarm = new_swarm();
+    let output = swarm.execute("word_count", &"one two three four").await;
+    assert_eq!(output.get_payload::<usize>().unwrap(), 4);
+
+    // Step errors are reported with the failing step
+    let output = swarm.execute("word_count.run", &"").await;
+    let error = output.get_error().unwrap();
+    assert_eq!(error.message, "Step count failed");
+    assert_eq!(error.agent_id, "word_count");
+    assert_eq!(error.root_cause().message, "No words");
+
+    let output = swarm.execute("word_count.stop", &"").await;
+    assert_eq!(output.get_error().unwrap().code, ErrorCode::UnknownAction);
+}
+
+#[cfg(feature = "yaml")]
+#[tokio::test]
+pub async fn yaml_workflow() {
+    let mut swarm = Swarm::default();
+    swarm.register_agent("text", TextAgent::default());
+    let word_count = WorkflowAgent::load("test-data/workflows/word_count.yaml").unwrap();
+    swarm.register_agent(&word_count.get_id(), word_count);
+
+    let output = swarm.execute("word_count", &"one two three").await;
+    assert_eq!(output.get_payload::<usize>().unwrap(), 3);
+}
+
+#[cfg(not(feature = "yaml"))]
+#[test]
+pub fn yaml_feature_disabled() {
+    let error = WorkflowAgent::load("test-data/workflows/word_count.yaml").err().unwrap();
+    assert!(error.contains("`yaml` feature"));
+}
+
+#[tokio::test]
+pub async fn workflow_web_action() {
+    let client = Client::tracked(WebSwarm::serve(new_swarm())).await.unwrap();
+    let action = Action::new("word_count.run", "one two");
+    let response = client
+        .post("/api/action")
+        .header(ContentType::JSON)
+        .body(serde_json::to_string(&action).unwrap())
+        .dispatch()
+        .await;
+    let output: Output = response.into_json().await.unwrap();
+    assert_eq!(output.get_payload::<usize>().unwrap(), 2);
+}